@@ -1,13 +1,13 @@
 #[macro_use]
 extern crate criterion;
 
-use criterion::{Criterion, Benchmark, Throughput};
+use criterion::{Criterion, Benchmark, Throughput, black_box};
 use orientations::{Vector3d, Rotation, Quaternion};
 use std::f64::consts::PI;
 
 fn from_angle_axis(c: &mut Criterion) {
     let angle = PI / 2.0;
-    let axis = Vector3d::x();
+    let axis = Vector3d::unit_x();
     let bench = Benchmark::new(
         "from_angle_axis",
         move |b| b.iter(|| Quaternion::from_angle_axis(angle, &axis))
@@ -18,7 +18,7 @@ fn from_angle_axis(c: &mut Criterion) {
 
 fn inverse(c: &mut Criterion) {
     let angle = PI / 2.0;
-    let axis = Vector3d::x();
+    let axis = Vector3d::unit_x();
     let q = Quaternion::from_angle_axis(angle, &axis);
     let bench = Benchmark::new(
         "inverse",
@@ -33,7 +33,7 @@ fn inverse(c: &mut Criterion) {
 
 fn angle_axis(c: &mut Criterion) {
     let angle = PI / 2.0;
-    let axis = Vector3d::x();
+    let axis = Vector3d::unit_x();
     let q = Quaternion::from_angle_axis(angle, &axis);
     let bench = Benchmark::new(
         "angle_axis",
@@ -45,17 +45,17 @@ fn angle_axis(c: &mut Criterion) {
 
 fn before_after(c: &mut Criterion) {
     let angle = PI / 2.0;
-    let q1 = Quaternion::from_angle_axis(angle, &Vector3d::x());
-    let q2 = Quaternion::from_angle_axis(angle, &Vector3d::y());
+    let q1 = Quaternion::from_angle_axis(angle, &Vector3d::unit_x());
+    let q2 = Quaternion::from_angle_axis(angle, &Vector3d::unit_y());
     let bench = Benchmark::new(
         "before",
-        move |b| b.iter(|| q1.before(&q2))
+        move |b| b.iter(|| black_box(q1).before(&black_box(q2)))
     ).with_function(
         "after_safe",
-        move |b| b.iter(|| q2.inverse().unwrap().multiply(&q1.inverse().unwrap()).inverse().unwrap())
+        move |b| b.iter(|| q2.inverse().unwrap().after(&q1.inverse().unwrap()).inverse().unwrap())
     ).with_function(
         "after",
-        move |b| b.iter(|| q2.after(&q1))
+        move |b| b.iter(|| black_box(q2).after(&black_box(q1)))
     ).throughput(Throughput::Elements(1));
 
     c.bench("quaternion:before_after", bench);
@@ -63,9 +63,9 @@ fn before_after(c: &mut Criterion) {
 
 fn rotate_vector(c: &mut Criterion) {
     let angle = PI / 2.0;
-    let axis = Vector3d::z();
+    let axis = Vector3d::unit_z();
     let q = Quaternion::from_angle_axis(angle, &axis);
-    let v = Vector3d::x();
+    let v = Vector3d::unit_x();
     let bench = Benchmark::new(
         "rotate_vector",
         move |b| b.iter(|| q.rotate_vector(&v))
@@ -0,0 +1,178 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::any_rotation::AnyRotation;
+use crate::matrix3::Matrix3;
+use crate::matrix_fisher::proper_svd;
+
+/// Counts of the work done by a `RotationAccumulator` and the drift it
+/// has seen.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DriftStatistics {
+    /// The number of steps composed.
+    pub steps: usize,
+    /// The number of times the rotation has been corrected.
+    pub corrections: usize,
+    /// The drift of the current rotation.
+    pub drift: f64,
+    /// The largest drift seen, before any correction.
+    pub max_drift: f64
+}
+
+/// Composes a long stream of incremental rotations, as in dead
+/// reckoning, and corrects the rounding error that builds up: a
+/// quaternion is renormalized, and a rotation matrix replaced by the
+/// nearest rotation matrix. Correction happens every `interval` steps,
+/// or sooner if the drift exceeds `tolerance`.
+///
+/// Drift is measured after every step. For a quaternion it is the
+/// distance of its norm from one; for a matrix `R` it is the largest
+/// entry of `R^T R - I` in magnitude, as in
+/// `RotationMatrix::is_special_orthogonal`.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let mut accumulator = RotationAccumulator::new(Quaternion::identity(), 100, 1.0e-12);
+/// let omega = Vector3d::new([0.0, 0.0, 0.1]);
+/// for _ in 0..10_000 {
+///     accumulator.integrate(&omega, 0.01);
+/// }
+/// let expected = Quaternion::from_angle_axis(10.0, &Vector3d::unit_z());
+/// assert!(accumulator.rotation().as_quaternion().angle_to(&expected) < 1.0e-10);
+/// assert!(accumulator.statistics().corrections >= 100);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct RotationAccumulator {
+    rotation: AnyRotation,
+    interval: usize,
+    tolerance: f64,
+    since_correction: usize,
+    statistics: DriftStatistics
+}
+
+impl RotationAccumulator {
+    /// Create an accumulator starting from `initial`, which keeps its
+    /// representation throughout.
+    ///
+    /// # Panics
+    /// Panics if `interval` is zero or `tolerance` is negative or NaN.
+    pub fn new<R: Into<AnyRotation>>(initial: R, interval: usize, tolerance: f64) -> Self {
+        assert!(interval > 0, "Correction interval must be positive");
+        assert!(tolerance >= 0.0, "Drift tolerance must be non-negative");
+        let rotation = initial.into();
+        let drift = drift(&rotation);
+        Self {
+            rotation,
+            interval,
+            tolerance,
+            since_correction: 0,
+            statistics: DriftStatistics { drift, max_drift: drift, ..DriftStatistics::default() }
+        }
+    }
+
+    /// The accumulated rotation.
+    #[must_use]
+    pub fn rotation(&self) -> AnyRotation {
+        self.rotation
+    }
+
+    /// The steps taken, corrections made and drift seen so far.
+    #[must_use]
+    pub fn statistics(&self) -> DriftStatistics {
+        self.statistics
+    }
+
+    /// Compose a step, applied after the rotation so far as in
+    /// `Rotation::after`, correcting the result if it is due. Returns
+    /// the new rotation.
+    pub fn push<T: Rotation>(&mut self, step: &T) -> AnyRotation {
+        self.rotation = self.rotation.after(step);
+        self.since_correction += 1;
+        let stats = &mut self.statistics;
+        stats.steps += 1;
+        stats.drift = drift(&self.rotation);
+        stats.max_drift = stats.max_drift.max(stats.drift);
+        if self.since_correction >= self.interval || stats.drift > self.tolerance {
+            self.correct();
+        }
+        self.rotation
+    }
+
+    /// Compose the rotation from a constant body-frame angular velocity
+    /// `omega`, in radians per second, over `dt` seconds; see
+    /// `Rotation::integrate`.
+    pub fn integrate(&mut self, omega: &Vector3d, dt: f64) -> AnyRotation {
+        self.push(&Quaternion::from_rotation_vector(&omega.scalar_multiple(dt)))
+    }
+
+    /// Correct the rotation now, regardless of the schedule.
+    pub fn correct(&mut self) {
+        self.rotation = match self.rotation {
+            AnyRotation::Quaternion(q) => q.normalized().unwrap_or(q).into(),
+            AnyRotation::RotationMatrix(r) => nearest_rotation(&r.as_matrix3()).into()
+        };
+        self.since_correction = 0;
+        self.statistics.corrections += 1;
+        self.statistics.drift = drift(&self.rotation);
+    }
+}
+
+/// How far a rotation has drifted from unit norm or orthogonality.
+fn drift(rotation: &AnyRotation) -> f64 {
+    match rotation {
+        AnyRotation::Quaternion(q) => (q.dot(q).sqrt() - 1.0).abs(),
+        AnyRotation::RotationMatrix(r) => {
+            let m = r.as_matrix3();
+            let gram = m.transpose().multiply(&m) - Matrix3::identity();
+            gram.data.iter().flatten().fold(0.0, |max, x| x.abs().max(max))
+        }
+    }
+}
+
+/// The rotation matrix nearest `m` in the Frobenius norm, `U V^T`.
+fn nearest_rotation(m: &Matrix3) -> RotationMatrix {
+    let (u, _, v) = proper_svd(m);
+    u.after(&v.inverse_unchecked())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrects_on_schedule_and_on_drift() {
+        let step = Quaternion::from_angle_axis(0.001, &Vector3d::new([1.0, -2.0, 0.5]));
+        let expected = Quaternion::from_angle_axis(5.0, &Vector3d::new([1.0, -2.0, 0.5]));
+        for initial in &[AnyRotation::from(Quaternion::identity()), AnyRotation::from(RotationMatrix::identity())] {
+            let mut accumulator = RotationAccumulator::new(*initial, 500, 1.0);
+            for _ in 0..5000 {
+                accumulator.push(&step);
+            }
+            let stats = accumulator.statistics();
+            assert_eq!(5000, stats.steps);
+            assert_eq!(10, stats.corrections);
+            assert!(stats.drift < 1.0e-14);
+            assert!(accumulator.rotation().as_quaternion().angle_to(&expected) < 1.0e-10);
+        }
+
+        // A drifted start is corrected on the first step.
+        let drifted = Quaternion::identity().scale(1.01);
+        let mut accumulator = RotationAccumulator::new(drifted, 1000, 1.0e-6);
+        assert_float_approx_eq!(0.01, accumulator.statistics().drift, 1.0e-12);
+        accumulator.push(&step);
+        assert_eq!(1, accumulator.statistics().corrections);
+        assert!(accumulator.statistics().max_drift > 0.01);
+        assert!(accumulator.statistics().drift < 1.0e-15);
+
+        // Skewed, but within what the `checked` feature allows.
+        let skewed = [[1.0 + 1.0e-7, 0.0, 0.0], [0.0, 1.0, 2.0e-7], [0.0, 0.0, 1.0 - 1.0e-7]];
+        let mut accumulator = RotationAccumulator::new(RotationMatrix::from_rows(skewed.map(Vector3d::new)), 1000, 1.0e-8);
+        accumulator.push(&step);
+        assert_eq!(1, accumulator.statistics().corrections);
+        assert!(accumulator.rotation().as_rotation_matrix().is_special_orthogonal(1.0e-12));
+    }
+}
@@ -0,0 +1,106 @@
+//! Helpers for plain angles in radians: wrapping into a standard range,
+//! signed differences and unit conversion.
+
+use std::f64::consts::PI;
+
+/// Wrap an angle, in radians, into `(-pi, pi]`.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::angles::wrap_to_pi;
+/// use std::f64::consts::PI;
+/// assert!((wrap_to_pi(1.5 * PI) + 0.5 * PI).abs() < 1.0e-12);
+/// assert_eq!(PI, wrap_to_pi(-PI));
+/// ```
+#[must_use]
+pub fn wrap_to_pi(angle: f64) -> f64 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped <= -PI { wrapped + 2.0 * PI } else { wrapped }
+}
+
+/// Wrap an angle, in radians, into `[0, 2 pi)`.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::angles::wrap_to_2pi;
+/// use std::f64::consts::PI;
+/// assert!((wrap_to_2pi(-0.5 * PI) - 1.5 * PI).abs() < 1.0e-12);
+/// assert_eq!(0.0, wrap_to_2pi(2.0 * PI));
+/// ```
+#[must_use]
+pub fn wrap_to_2pi(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(2.0 * PI);
+    // A tiny negative angle rounds up to exactly 2 pi.
+    if wrapped >= 2.0 * PI { 0.0 } else { wrapped }
+}
+
+/// The signed angle, in radians and in `(-pi, pi]`, to turn from `from`
+/// to `to` the short way round; positive is the direction of increasing
+/// angle. Going from 350 to 10 degrees is a turn of +20 degrees, not
+/// -340.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::angles::{shortest_angular_distance, deg_to_rad};
+/// let turn = shortest_angular_distance(deg_to_rad(350.0), deg_to_rad(10.0));
+/// assert!((turn - deg_to_rad(20.0)).abs() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn shortest_angular_distance(from: f64, to: f64) -> f64 {
+    wrap_to_pi(to - from)
+}
+
+/// Convert degrees to radians.
+#[must_use]
+pub fn deg_to_rad(degrees: f64) -> f64 {
+    degrees.to_radians()
+}
+
+/// Convert radians to degrees.
+#[must_use]
+pub fn rad_to_deg(radians: f64) -> f64 {
+    radians.to_degrees()
+}
+
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapping_ranges() {
+        for k in -40..=40 {
+            let angle = 0.37 * f64::from(k);
+            let symmetric = wrap_to_pi(angle);
+            let positive = wrap_to_2pi(angle);
+            assert!(-PI < symmetric && symmetric <= PI);
+            assert!((0.0..2.0 * PI).contains(&positive));
+            // Both differ from the angle by whole turns.
+            for wrapped in [symmetric, positive] {
+                let turns = (angle - wrapped) / (2.0 * PI);
+                assert_float_approx_eq!(turns.round(), turns, 1.0e-12);
+            }
+        }
+        assert_eq!(0.0, wrap_to_2pi(-1.0e-20));
+        assert_eq!(PI, wrap_to_pi(PI));
+        assert_eq!(PI, wrap_to_pi(3.0 * PI));
+    }
+
+    #[test]
+    fn shortest_distance_signs() {
+        assert_float_approx_eq!(0.2, shortest_angular_distance(-0.1, 0.1), 1.0e-15);
+        assert_float_approx_eq!(-0.2, shortest_angular_distance(0.1, -0.1), 1.0e-15);
+        assert_float_approx_eq!(0.2, shortest_angular_distance(PI - 0.1, -PI + 0.1), 1.0e-12);
+        assert_float_approx_eq!(-0.2, shortest_angular_distance(-PI + 0.1, PI - 0.1), 1.0e-12);
+    }
+
+    #[test]
+    fn unit_conversion() {
+        assert_eq!(PI, deg_to_rad(180.0));
+        assert_float_approx_eq!(57.295_779_513_082_32, rad_to_deg(1.0), 1.0e-12);
+    }
+}
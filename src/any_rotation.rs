@@ -0,0 +1,203 @@
+use crate::vector3d::Vector3d;
+use std::iter::Product;
+use crate::rotation::{Rotation, compose_all};
+use crate::orientation::Orientation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::error::OrientationError;
+
+/// A rotation in any of the crate's representations, chosen at run
+/// time. `Rotation` has generic methods and an associated type, so it
+/// cannot be used as `dyn Rotation`; this enum fills that role, letting
+/// collections mix representations and APIs accept whichever one a
+/// caller has.
+///
+/// Composition keeps the representation of the receiver, as it does
+/// for the concrete types.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let steps: Vec<AnyRotation> = vec![
+///     Quaternion::from_angle_axis(0.5, &Vector3d::unit_z()).into(),
+///     Quaternion::from_angle_axis(0.25, &Vector3d::unit_z()).as_rotation_matrix().into(),
+/// ];
+/// let total = steps.iter().fold(AnyRotation::identity(), |total, step| total.before(step));
+/// assert!((total.angle_axis().0 - 0.75).abs() < 1.0e-12);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnyRotation {
+    /// A unit quaternion.
+    Quaternion(Quaternion),
+    /// A rotation matrix.
+    RotationMatrix(RotationMatrix)
+}
+
+impl From<Quaternion> for AnyRotation {
+    fn from(q: Quaternion) -> Self {
+        AnyRotation::Quaternion(q)
+    }
+}
+
+impl From<RotationMatrix> for AnyRotation {
+    fn from(r: RotationMatrix) -> Self {
+        AnyRotation::RotationMatrix(r)
+    }
+}
+
+impl Default for AnyRotation {
+    /// The identity rotation.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Product for AnyRotation {
+    /// Compose left to right; see `compose_all`.
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::identity(), |total, r| total.after(&r))
+    }
+}
+
+impl<'a> Product<&'a AnyRotation> for AnyRotation {
+    /// Compose left to right; see `compose_all`.
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        compose_all(iter)
+    }
+}
+
+impl Rotation for AnyRotation {
+    type Output = Self;
+
+    /// The identity, as a quaternion.
+    fn identity() -> Self {
+        AnyRotation::Quaternion(Quaternion::identity())
+    }
+
+    fn inverse(&self) -> Result<Self, OrientationError> {
+        match self {
+            AnyRotation::Quaternion(q) => q.inverse().map(AnyRotation::Quaternion),
+            AnyRotation::RotationMatrix(r) => r.inverse().map(AnyRotation::RotationMatrix)
+        }
+    }
+
+    fn inverse_unchecked(&self) -> Self {
+        match self {
+            AnyRotation::Quaternion(q) => AnyRotation::Quaternion(q.inverse_unchecked()),
+            AnyRotation::RotationMatrix(r) => AnyRotation::RotationMatrix(r.inverse_unchecked())
+        }
+    }
+
+    fn as_quaternion(&self) -> Quaternion {
+        match self {
+            AnyRotation::Quaternion(q) => *q,
+            AnyRotation::RotationMatrix(r) => r.as_quaternion()
+        }
+    }
+
+    fn as_rotation_matrix(&self) -> RotationMatrix {
+        match self {
+            AnyRotation::Quaternion(q) => q.as_rotation_matrix(),
+            AnyRotation::RotationMatrix(r) => *r
+        }
+    }
+
+    fn angle_axis(&self) -> (f64, Vector3d) {
+        match self {
+            AnyRotation::Quaternion(q) => q.angle_axis(),
+            AnyRotation::RotationMatrix(r) => r.angle_axis()
+        }
+    }
+
+    fn before<T: Rotation>(&self, r: &T) -> Self {
+        match self {
+            AnyRotation::Quaternion(q) => AnyRotation::Quaternion(q.before(r)),
+            AnyRotation::RotationMatrix(m) => AnyRotation::RotationMatrix(m.before(r))
+        }
+    }
+
+    fn after<T: Rotation>(&self, r: &T) -> Self {
+        match self {
+            AnyRotation::Quaternion(q) => AnyRotation::Quaternion(q.after(r)),
+            AnyRotation::RotationMatrix(m) => AnyRotation::RotationMatrix(m.after(r))
+        }
+    }
+
+    fn rotate_vector(&self, v: &Vector3d) -> Vector3d {
+        match self {
+            AnyRotation::Quaternion(q) => q.rotate_vector(v),
+            AnyRotation::RotationMatrix(r) => r.rotate_vector(v)
+        }
+    }
+
+    fn rotate_vector_inverse(&self, v: &Vector3d) -> Vector3d {
+        match self {
+            AnyRotation::Quaternion(q) => q.rotate_vector_inverse(v),
+            AnyRotation::RotationMatrix(r) => r.rotate_vector_inverse(v)
+        }
+    }
+
+    fn rotate_vectors_into(&self, vs: &[Vector3d], rotated: &mut [Vector3d]) {
+        match self {
+            AnyRotation::Quaternion(q) => q.rotate_vectors_into(vs, rotated),
+            AnyRotation::RotationMatrix(r) => r.rotate_vectors_into(vs, rotated)
+        }
+    }
+}
+
+impl Orientation for AnyRotation {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn representations_agree() {
+        let q = Quaternion::from_angle_axis(1.3, &Vector3d::new([1.0, -1.0, 2.0]));
+        let p = Quaternion::from_angle_axis(-0.4, &Vector3d::new([0.0, 1.0, 1.0]));
+        let v = Vector3d::new([0.3, 2.0, -1.0]);
+        let expected = q.before(&p);
+        for a in &[AnyRotation::from(q), AnyRotation::from(q.as_rotation_matrix())] {
+            for b in &[AnyRotation::from(p), AnyRotation::from(p.as_rotation_matrix())] {
+                assert!(a.before(b).as_quaternion().angle_to(&expected) < 1.0e-12);
+                assert!(b.after(a).as_quaternion().angle_to(&expected) < 1.0e-12);
+            }
+            assert_vector_approx_eq!(q.rotate_vector(&v), a.rotate_vector(&v), 1.0e-12);
+            assert_vector_approx_eq!(v, a.inverse().unwrap().rotate_vector(&a.rotate_vector(&v)), 1.0e-12);
+            assert_vector_approx_eq!(v, a.rotate_vector_inverse(&a.rotate_vector(&v)), 1.0e-12);
+            assert_float_approx_eq!(q.heading(), a.heading(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn product_composes_left_to_right() {
+        let joints = [
+            Quaternion::from_angle_axis(0.4, &Vector3d::unit_z()),
+            Quaternion::from_angle_axis(-1.1, &Vector3d::unit_y()),
+            Quaternion::from_angle_axis(0.7, &Vector3d::new([1.0, 1.0, 0.0]))
+        ];
+        let v = Vector3d::new([0.5, -0.3, 2.0]);
+        let expected = joints[0].rotate_vector(&joints[1].rotate_vector(&joints[2].rotate_vector(&v)));
+        let q: Quaternion = joints.iter().product();
+        assert_vector_approx_eq!(expected, q.rotate_vector(&v), 1.0e-12);
+        let r: RotationMatrix = joints.iter().map(Rotation::as_rotation_matrix).product();
+        assert_vector_approx_eq!(expected, r.rotate_vector(&v), 1.0e-12);
+        let mixed = [AnyRotation::from(joints[0]), AnyRotation::from(joints[1].as_rotation_matrix()), AnyRotation::from(joints[2])];
+        assert_vector_approx_eq!(expected, compose_all(&mixed).rotate_vector(&v), 1.0e-12);
+        assert_eq!(Quaternion::identity(), compose_all(&[]));
+    }
+
+    #[test]
+    fn composition_keeps_receiver_representation() {
+        let q = AnyRotation::from(Quaternion::from_angle_axis(0.2, &Vector3d::unit_x()));
+        let r = AnyRotation::from(Quaternion::from_angle_axis(0.3, &Vector3d::unit_y()).as_rotation_matrix());
+        assert!(matches!(q.before(&r), AnyRotation::Quaternion(_)));
+        assert!(matches!(r.after(&q), AnyRotation::RotationMatrix(_)));
+        // Concrete types compose with it too.
+        let concrete: Quaternion = q.as_quaternion().before(&r);
+        assert!(concrete.angle_to(&q.before(&r).as_quaternion()) < 1.0e-12);
+    }
+}
@@ -0,0 +1,174 @@
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+use crate::vector3d::Vector3d;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::matrix3::Matrix3;
+
+// Comparisons are component-wise, as for arrays. In particular `q` and
+// `-q` are not approximately equal even though they are the same
+// rotation; canonicalize the sign first, or use `angle_to`, when that
+// matters.
+
+impl AbsDiffEq for Vector3d {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> f64 {
+        f64::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.data.iter().zip(&other.data).all(|(a, b)| a.abs_diff_eq(b, epsilon))
+    }
+}
+
+impl RelativeEq for Vector3d {
+    fn default_max_relative() -> f64 {
+        f64::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+        self.data.iter().zip(&other.data).all(|(a, b)| a.relative_eq(b, epsilon, max_relative))
+    }
+}
+
+impl UlpsEq for Vector3d {
+    fn default_max_ulps() -> u32 {
+        f64::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: f64, max_ulps: u32) -> bool {
+        self.data.iter().zip(&other.data).all(|(a, b)| a.ulps_eq(b, epsilon, max_ulps))
+    }
+}
+
+impl AbsDiffEq for Quaternion {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> f64 {
+        f64::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.real_part().abs_diff_eq(&other.real_part(), epsilon)
+            && self.imaginary_part().abs_diff_eq(&other.imaginary_part(), epsilon)
+    }
+}
+
+impl RelativeEq for Quaternion {
+    fn default_max_relative() -> f64 {
+        f64::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+        self.real_part().relative_eq(&other.real_part(), epsilon, max_relative)
+            && self.imaginary_part().relative_eq(&other.imaginary_part(), epsilon, max_relative)
+    }
+}
+
+impl UlpsEq for Quaternion {
+    fn default_max_ulps() -> u32 {
+        f64::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: f64, max_ulps: u32) -> bool {
+        self.real_part().ulps_eq(&other.real_part(), epsilon, max_ulps)
+            && self.imaginary_part().ulps_eq(&other.imaginary_part(), epsilon, max_ulps)
+    }
+}
+
+impl AbsDiffEq for Matrix3 {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> f64 {
+        f64::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.rows().iter().zip(&other.rows()).all(|(a, b)| a.abs_diff_eq(b, epsilon))
+    }
+}
+
+impl RelativeEq for Matrix3 {
+    fn default_max_relative() -> f64 {
+        f64::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+        self.rows().iter().zip(&other.rows()).all(|(a, b)| a.relative_eq(b, epsilon, max_relative))
+    }
+}
+
+impl UlpsEq for Matrix3 {
+    fn default_max_ulps() -> u32 {
+        f64::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: f64, max_ulps: u32) -> bool {
+        self.rows().iter().zip(&other.rows()).all(|(a, b)| a.ulps_eq(b, epsilon, max_ulps))
+    }
+}
+
+impl AbsDiffEq for RotationMatrix {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> f64 {
+        f64::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.as_matrix3().abs_diff_eq(&other.as_matrix3(), epsilon)
+    }
+}
+
+impl RelativeEq for RotationMatrix {
+    fn default_max_relative() -> f64 {
+        f64::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+        self.as_matrix3().relative_eq(&other.as_matrix3(), epsilon, max_relative)
+    }
+}
+
+impl UlpsEq for RotationMatrix {
+    fn default_max_ulps() -> u32 {
+        f64::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: f64, max_ulps: u32) -> bool {
+        self.as_matrix3().ulps_eq(&other.as_matrix3(), epsilon, max_ulps)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::{assert_abs_diff_eq, assert_relative_eq, assert_ulps_eq, assert_relative_ne};
+    use crate::rotation::Rotation;
+
+    #[test]
+    fn vectors() {
+        let v = Vector3d::new([1.0, 2.0, 3.0]);
+        let w = Vector3d::new([1.0 + 1.0e-12, 2.0, 3.0]);
+        assert_relative_eq!(v, w, max_relative = 1.0e-10);
+        assert_abs_diff_eq!(v, w, epsilon = 1.0e-10);
+        assert_relative_ne!(v, w);
+        assert_ulps_eq!(v, Vector3d::new([0.1 + 0.2 + 0.7, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn rotations() {
+        let q = Quaternion::from_angle_axis(1.1, &Vector3d::new([1.0, -2.0, 0.5]));
+        let round_trip = q.as_rotation_matrix().as_quaternion();
+        let round_trip = if round_trip.dot(&q) < 0.0 { round_trip.scale(-1.0) } else { round_trip };
+        assert_relative_eq!(q, round_trip, epsilon = 1.0e-12);
+        // Component-wise, so the double cover is not identified.
+        assert_relative_ne!(q, q.scale(-1.0));
+
+        let r = q.as_rotation_matrix();
+        assert_relative_eq!(RotationMatrix::identity(), r.multiply(&r.inverse_unchecked()), epsilon = 1.0e-12);
+        assert_abs_diff_eq!(Matrix3::identity(), r.as_matrix3().multiply(&r.as_matrix3().transpose()),
+                            epsilon = 1.0e-12);
+    }
+}
@@ -0,0 +1,31 @@
+use crate::vector3d::Vector3d;
+use crate::quaternion::Quaternion;
+use crate::error::OrientationError;
+
+/// A common interface for attitude filters, so applications can swap
+/// one filter for another without changing call sites.
+///
+/// Estimates rotate body-frame vectors into the world frame, angular
+/// velocities are expressed in the body frame, and vector observations
+/// are body-frame measurements of known world-frame reference
+/// directions.
+pub trait AttitudeFilter {
+    /// Propagate the estimate with a body-frame angular velocity (in
+    /// radians per second) held constant over `dt` seconds.
+    fn predict(&mut self, gyro: &Vector3d, dt: f64);
+
+    /// Correct the estimate with a body-frame observation of a known
+    /// world-frame reference direction. `noise_variance` is the
+    /// variance of the observation noise; filters without a noise
+    /// model may ignore it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the observation cannot be used, for example
+    /// because a vector has zero magnitude.
+    fn correct_vector(&mut self, observed: &Vector3d, reference: &Vector3d,
+                      noise_variance: f64) -> Result<(), OrientationError>;
+
+    /// The current attitude estimate.
+    fn estimate(&self) -> Quaternion;
+}
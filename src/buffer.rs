@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::sequence::angular_velocity_between;
+use crate::error::OrientationError;
+
+/// What `OrientationBuffer::query` does for times outside the buffered
+/// range.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Extrapolation {
+    /// Return an error.
+    Refuse,
+    /// Return the nearest buffered orientation.
+    Hold,
+    /// Continue at the angular velocity between the two nearest
+    /// buffered samples.
+    ConstantRate
+}
+
+/// A bounded, time-ordered buffer of orientation samples supporting
+/// interpolated lookup at arbitrary times, as needed to align sensors
+/// sampled at different instants.
+///
+/// Once full, inserting a sample evicts the oldest one.
+#[derive(Clone, Debug)]
+pub struct OrientationBuffer {
+    samples: VecDeque<(f64, Quaternion)>,
+    capacity: usize,
+    extrapolation: Extrapolation
+}
+
+impl OrientationBuffer {
+    /// Create an empty buffer holding at most `capacity` samples.
+    ///
+    /// # Panics
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let buffer = OrientationBuffer::new(100, Extrapolation::Hold);
+    /// assert!(buffer.is_empty());
+    /// ```
+    #[must_use]
+    pub fn new(capacity: usize, extrapolation: Extrapolation) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            extrapolation
+        }
+    }
+
+    /// The largest number of samples held.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of samples held.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether the buffer holds no samples.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The earliest and latest buffered times, if any.
+    #[must_use]
+    pub fn time_range(&self) -> Option<(f64, f64)> {
+        Some((self.samples.front()?.0, self.samples.back()?.0))
+    }
+
+    /// Remove every sample.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Add a sample. Samples normally arrive in time order, but late
+    /// samples are inserted in place; a sample at an already buffered
+    /// time replaces the old one.
+    ///
+    /// # Panics
+    /// Panics if the time is not finite.
+    #[allow(clippy::float_cmp)]
+    pub fn insert(&mut self, time: f64, orientation: Quaternion) {
+        assert!(time.is_finite(), "Sample time must be finite");
+        let index = self.samples.partition_point(|(t, _)| *t < time);
+        match self.samples.get_mut(index) {
+            Some(existing) if existing.0 == time => existing.1 = orientation,
+            _ => self.samples.insert(index, (time, orientation))
+        }
+        if self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    /// The orientation at a given time, interpolating between the
+    /// neighboring samples along the shorter arc. Outside the buffered
+    /// range the extrapolation policy applies.
+    ///
+    /// # Errors
+    /// Returns an error if the time is not finite, if the buffer is
+    /// empty, or if the time is out of range and the policy is
+    /// `Extrapolation::Refuse`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mut buffer = OrientationBuffer::new(10, Extrapolation::Refuse);
+    /// buffer.insert(0.0, Quaternion::identity());
+    /// buffer.insert(1.0, Quaternion::from_angle_axis(0.4, &Vector3d::unit_z()));
+    /// let q = buffer.query(0.25).unwrap();
+    /// assert!(q.angle_to(&Quaternion::from_angle_axis(0.1, &Vector3d::unit_z())) < 1.0e-12);
+    /// assert!(buffer.query(1.5).is_err());
+    /// ```
+    pub fn query(&self, time: f64) -> Result<Quaternion, OrientationError> {
+        if !time.is_finite() {
+            return Err(OrientationError::NotFinite)
+        }
+        let (first, last) = self.time_range()
+            .ok_or(OrientationError::InsufficientData("cannot query an empty buffer"))?;
+
+        if time < first || time > last {
+            return self.extrapolate(time, time > last)
+        }
+
+        let index = self.samples.partition_point(|(t, _)| *t < time);
+        let (t1, q1) = self.samples[index];
+        if index == 0 {
+            return Ok(q1)
+        }
+        let (t0, q0) = self.samples[index - 1];
+        Ok(q0.slerp(&q1, (time - t0) / (t1 - t0)))
+    }
+
+    /// Apply the extrapolation policy beyond one end of the buffer.
+    fn extrapolate(&self, time: f64, after: bool) -> Result<Quaternion, OrientationError> {
+        let n = self.samples.len();
+        let (nearest, neighbor) = if after {
+            (self.samples[n - 1], self.samples.get(n.wrapping_sub(2)))
+        } else {
+            (self.samples[0], self.samples.get(1))
+        };
+
+        match (self.extrapolation, neighbor) {
+            (Extrapolation::Refuse, _) => Err(OrientationError::OutOfRange),
+            (Extrapolation::ConstantRate, Some(&(t, q))) => {
+                let omega = angular_velocity_between(&q, &nearest.1, nearest.0 - t);
+                Ok(nearest.1.integrate(&omega, time - nearest.0))
+            },
+            _ => Ok(nearest.1)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector3d::Vector3d;
+
+    fn spinning(t: f64) -> Quaternion {
+        Quaternion::from_angle_axis(0.5 * t, &Vector3d::new([1.0, 0.0, 1.0]))
+    }
+
+    #[test]
+    fn interpolates_constant_rate_exactly() {
+        let mut buffer = OrientationBuffer::new(10, Extrapolation::Refuse);
+        for i in 0..5 {
+            buffer.insert(f64::from(i), spinning(f64::from(i)));
+        }
+        for &t in &[0.0, 0.3, 1.7, 2.0, 3.99, 4.0] {
+            assert_quat_approx_eq!(spinning(t), buffer.query(t).unwrap(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn extrapolation_policies() {
+        let mut buffer = OrientationBuffer::new(10, Extrapolation::Hold);
+        buffer.insert(0.0, spinning(0.0));
+        buffer.insert(1.0, spinning(1.0));
+        assert_quat_approx_eq!(spinning(1.0), buffer.query(3.0).unwrap());
+        assert_quat_approx_eq!(spinning(0.0), buffer.query(-1.0).unwrap());
+
+        buffer.extrapolation = Extrapolation::ConstantRate;
+        assert_quat_approx_eq!(spinning(3.0), buffer.query(3.0).unwrap(), 1.0e-12);
+        assert_quat_approx_eq!(spinning(-1.0), buffer.query(-1.0).unwrap(), 1.0e-12);
+
+        buffer.extrapolation = Extrapolation::Refuse;
+        assert!(buffer.query(3.0).is_err());
+
+        // NaN compares false with everything, so it must not fall
+        // through to the first sample.
+        buffer.extrapolation = Extrapolation::Hold;
+        assert_eq!(Err(OrientationError::NotFinite), buffer.query(f64::NAN));
+        assert_eq!(Err(OrientationError::NotFinite), buffer.query(f64::INFINITY));
+    }
+
+    #[test]
+    fn bounded_memory_and_late_samples() {
+        let mut buffer = OrientationBuffer::new(3, Extrapolation::Refuse);
+        buffer.insert(0.0, spinning(0.0));
+        buffer.insert(2.0, spinning(2.0));
+        buffer.insert(1.0, spinning(1.0));
+        assert_eq!(Some((0.0, 2.0)), buffer.time_range());
+
+        buffer.insert(3.0, spinning(3.0));
+        assert_eq!(3, buffer.len());
+        assert_eq!(Some((1.0, 3.0)), buffer.time_range());
+
+        // Replacing a sample does not grow the buffer.
+        buffer.insert(3.0, Quaternion::identity());
+        assert_eq!(3, buffer.len());
+        assert_eq!(Quaternion::identity(), buffer.query(3.0).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Sample time must be finite")]
+    fn non_finite_insert() {
+        let mut buffer = OrientationBuffer::new(3, Extrapolation::Hold);
+        buffer.insert(f64::NAN, Quaternion::identity());
+    }
+
+    #[test]
+    fn empty_buffer() {
+        let buffer = OrientationBuffer::new(3, Extrapolation::Hold);
+        assert!(buffer.query(0.0).is_err());
+    }
+}
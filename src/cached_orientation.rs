@@ -0,0 +1,179 @@
+use std::cell::Cell;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::orientation::Orientation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::any_rotation::AnyRotation;
+use crate::error::OrientationError;
+
+/// An orientation that keeps whichever representation it was given and
+/// converts to the other only when first needed, caching the result
+/// until the orientation is next set. Composition uses the quaternion
+/// form and rotating vectors the matrix form, so code alternating
+/// between the two converts once per update rather than once per call.
+///
+/// Unlike `CompiledRotation`, nothing is converted up front, which suits
+/// orientations that are updated more often than they are read. The
+/// cache uses `Cell`, so the type is `Send` but not `Sync`.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let step = Quaternion::from_angle_axis(0.01, &Vector3d::unit_z());
+/// let mut orientation = CachedOrientation::default();
+/// for _ in 0..100 {
+///     orientation = orientation.after(&step);
+/// }
+/// let points = orientation.rotate_vectors(&[Vector3d::unit_x(); 10]);
+/// assert!((points[9] - Vector3d::new([1.0_f64.cos(), 1.0_f64.sin(), 0.0])).norm() < 1.0e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachedOrientation {
+    rotation: AnyRotation,
+    quaternion: Cell<Option<Quaternion>>,
+    matrix: Cell<Option<RotationMatrix>>
+}
+
+impl CachedOrientation {
+    /// Create an orientation from a quaternion or rotation matrix.
+    pub fn new<R: Into<AnyRotation>>(rotation: R) -> Self {
+        CachedOrientation { rotation: rotation.into(), quaternion: Cell::new(None), matrix: Cell::new(None) }
+    }
+
+    /// Replace the orientation, discarding any cached conversion.
+    pub fn set<R: Into<AnyRotation>>(&mut self, rotation: R) {
+        *self = Self::new(rotation);
+    }
+
+    /// The orientation in the representation it was given.
+    #[must_use]
+    pub fn rotation(&self) -> AnyRotation {
+        self.rotation
+    }
+
+    fn quaternion(&self) -> Quaternion {
+        match self.rotation {
+            AnyRotation::Quaternion(q) => q,
+            AnyRotation::RotationMatrix(r) => self.quaternion.get().unwrap_or_else(|| {
+                let q = r.as_quaternion();
+                self.quaternion.set(Some(q));
+                q
+            })
+        }
+    }
+
+    fn matrix(&self) -> RotationMatrix {
+        match self.rotation {
+            AnyRotation::RotationMatrix(r) => r,
+            AnyRotation::Quaternion(q) => self.matrix.get().unwrap_or_else(|| {
+                let r = q.as_rotation_matrix();
+                self.matrix.set(Some(r));
+                r
+            })
+        }
+    }
+}
+
+impl From<Quaternion> for CachedOrientation {
+    fn from(q: Quaternion) -> Self {
+        Self::new(q)
+    }
+}
+
+impl From<RotationMatrix> for CachedOrientation {
+    fn from(r: RotationMatrix) -> Self {
+        Self::new(r)
+    }
+}
+
+impl Default for CachedOrientation {
+    /// The identity rotation.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Rotation for CachedOrientation {
+    type Output = Self;
+
+    /// The identity, as a quaternion.
+    fn identity() -> Self {
+        Self::new(Quaternion::identity())
+    }
+
+    fn inverse(&self) -> Result<Self, OrientationError> {
+        self.rotation.inverse().map(Self::new)
+    }
+
+    fn inverse_unchecked(&self) -> Self {
+        Self::new(self.rotation.inverse_unchecked())
+    }
+
+    fn as_quaternion(&self) -> Quaternion {
+        self.quaternion()
+    }
+
+    fn as_rotation_matrix(&self) -> RotationMatrix {
+        self.matrix()
+    }
+
+    fn angle_axis(&self) -> (f64, Vector3d) {
+        self.quaternion().angle_axis()
+    }
+
+    /// Composes as quaternions; the result holds a quaternion.
+    fn before<T: Rotation>(&self, r: &T) -> Self {
+        Self::new(self.quaternion().before(r))
+    }
+
+    /// Composes as quaternions; the result holds a quaternion.
+    fn after<T: Rotation>(&self, r: &T) -> Self {
+        Self::new(self.quaternion().after(r))
+    }
+
+    fn rotate_vector(&self, v: &Vector3d) -> Vector3d {
+        self.matrix().rotate_vector(v)
+    }
+
+    fn rotate_vector_inverse(&self, v: &Vector3d) -> Vector3d {
+        self.matrix().rotate_vector_inverse(v)
+    }
+
+    fn rotate_vectors_into(&self, vs: &[Vector3d], rotated: &mut [Vector3d]) {
+        self.matrix().rotate_vectors_into(vs, rotated);
+    }
+}
+
+impl Orientation for CachedOrientation {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_lazily_and_invalidates_on_set() {
+        let q = Quaternion::from_angle_axis(0.7, &Vector3d::new([1.0, 2.0, -1.0]));
+        let v = Vector3d::new([0.5, -1.0, 2.0]);
+        let mut orientation = CachedOrientation::from(q);
+        assert!(orientation.matrix.get().is_none());
+        assert_vector_approx_eq!(q.rotate_vector(&v), orientation.rotate_vector(&v), 1.0e-12);
+        assert_eq!(Some(q.as_rotation_matrix()), orientation.matrix.get());
+        assert_eq!(q, orientation.as_quaternion());
+
+        let p = Quaternion::from_angle_axis(-0.2, &Vector3d::unit_x());
+        orientation.set(p.as_rotation_matrix());
+        assert!(orientation.matrix.get().is_none());
+        assert!(orientation.quaternion.get().is_none());
+        assert_vector_approx_eq!(p.rotate_vector(&v), orientation.rotate_vector(&v), 1.0e-12);
+        assert!(orientation.as_quaternion().angle_to(&p) < 1.0e-12);
+        assert!(orientation.quaternion.get().is_some());
+
+        let composed = orientation.after(&q);
+        assert!(matches!(composed.rotation(), AnyRotation::Quaternion(_)));
+        assert!(composed.as_quaternion().angle_to(&p.after(&q)) < 1.0e-12);
+        assert_vector_approx_eq!(v, composed.rotate_vector_inverse(&composed.rotate_vector(&v)), 1.0e-12);
+    }
+}
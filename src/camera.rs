@@ -0,0 +1,134 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+
+/// The rotation carrying vectors expressed in an OpenCV camera frame
+/// (x right, y down, z forward) into an OpenGL camera frame (x right, y
+/// up, z backward). It is a half turn about x and its own inverse.
+///
+/// A camera-from-world rotation `r` in OpenCV form becomes
+/// `r.before(&opencv_to_opengl())` in OpenGL form.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // OpenCV looks along +z; OpenGL looks along -z.
+/// let forward = opencv_to_opengl().rotate_vector(&Vector3d::unit_z());
+/// assert!((forward + Vector3d::unit_z()).norm() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn opencv_to_opengl() -> Quaternion {
+    Quaternion::new(0.0, Vector3d::unit_x())
+}
+
+/// The rotation carrying OpenGL camera-frame vectors into the OpenCV
+/// camera frame. This is the same rotation as `opencv_to_opengl`.
+#[must_use]
+pub fn opengl_to_opencv() -> Quaternion {
+    opencv_to_opengl()
+}
+
+/// Camera extrinsics: the rigid transform taking world points into the
+/// camera frame, `x_camera = rotation * x_world + translation`. This is
+/// the form of OpenCV's `rvec`/`tvec` and of the rotation and
+/// translation parts of an OpenGL view matrix; which camera axes apply
+/// depends on the convention it came from.
+#[derive(Copy, Clone, Debug)]
+pub struct Extrinsics {
+    /// The camera-from-world rotation.
+    pub rotation: Quaternion,
+    /// The world origin expressed in the camera frame.
+    pub translation: Vector3d
+}
+
+impl Extrinsics {
+    /// Create extrinsics from a camera-from-world rotation and
+    /// translation.
+    #[must_use]
+    pub fn new(rotation: Quaternion, translation: Vector3d) -> Self {
+        Self {
+            rotation,
+            translation
+        }
+    }
+
+    /// Extrinsics for a camera at `position` in the world whose
+    /// camera-frame vectors are carried into the world frame by
+    /// `orientation`.
+    #[must_use]
+    pub fn from_camera_pose(orientation: &Quaternion, position: &Vector3d) -> Self {
+        let rotation = orientation.inverse_unchecked();
+        Self::new(rotation, rotation.rotate_vector(position).negate())
+    }
+
+    /// The position of the camera center in the world frame.
+    #[must_use]
+    pub fn camera_position(&self) -> Vector3d {
+        self.rotation.rotate_vector_inverse(&self.translation).negate()
+    }
+
+    /// Express a world point in the camera frame.
+    #[must_use]
+    pub fn transform_point(&self, point: &Vector3d) -> Vector3d {
+        self.rotation.rotate_vector(point) + self.translation
+    }
+
+    /// Convert OpenCV extrinsics into the equivalent OpenGL view
+    /// transform for the same physical camera.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// // An OpenCV camera at the origin looking along world +z.
+    /// let cv = Extrinsics::new(Quaternion::identity(), Vector3d::zero());
+    /// let gl = cv.opencv_to_opengl();
+    /// // A point ahead of the camera has negative OpenGL depth.
+    /// let p = gl.transform_point(&Vector3d::new([0.0, 0.0, 5.0]));
+    /// assert!((p.data[2] + 5.0).abs() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn opencv_to_opengl(&self) -> Self {
+        self.change_camera_frame(&opencv_to_opengl())
+    }
+
+    /// Convert an OpenGL view transform into OpenCV extrinsics for the
+    /// same physical camera.
+    #[must_use]
+    pub fn opengl_to_opencv(&self) -> Self {
+        self.change_camera_frame(&opengl_to_opencv())
+    }
+
+    fn change_camera_frame(&self, change: &Quaternion) -> Self {
+        Self::new(self.rotation.before(change), change.rotate_vector(&self.translation))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversion_preserves_camera_and_image_directions() {
+        let orientation = Quaternion::from_angle_axis(0.7, &Vector3d::new([0.2, 1.0, -0.4]));
+        let position = Vector3d::new([1.0, -2.0, 0.5]);
+        let cv = Extrinsics::from_camera_pose(&orientation, &position);
+        let gl = cv.opencv_to_opengl();
+        assert_vector_approx_eq!(position, cv.camera_position(), 1.0e-12);
+        assert_vector_approx_eq!(position, gl.camera_position(), 1.0e-12);
+
+        // A world point right of and above the optical axis, in front
+        // of the camera.
+        let point = orientation.rotate_vector(&Vector3d::new([0.3, -0.2, 4.0])) + position;
+        let in_cv = cv.transform_point(&point);
+        let in_gl = gl.transform_point(&point);
+        assert_vector_approx_eq!(Vector3d::new([0.3, -0.2, 4.0]), in_cv, 1.0e-12);
+        assert_vector_approx_eq!(Vector3d::new([0.3, 0.2, -4.0]), in_gl, 1.0e-12);
+
+        let back = gl.opengl_to_opencv();
+        assert!(back.rotation.angle_to(&cv.rotation) < 1.0e-12);
+        assert_vector_approx_eq!(cv.translation, back.translation, 1.0e-12);
+    }
+}
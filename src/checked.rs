@@ -0,0 +1,94 @@
+use crate::vector3d::Vector3d;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+
+/// How far from unit norm, or from orthogonality, a rotation may drift
+/// before the `checked` feature reports it. Loose enough for the drift
+/// that accumulates over long integrations.
+const UNIT_TOLERANCE: f64 = 1.0e-6;
+
+// With the `checked` feature, these assert that a value passed into or
+// produced by an operation is finite and, for rotations, normalized,
+// naming the operation in the panic message so the first bad value is
+// caught where it appears. Without the feature they return the value
+// unchanged and compile away.
+
+/// Check that a vector is finite.
+#[inline]
+#[track_caller]
+pub(crate) fn vector(what: &str, v: Vector3d) -> Vector3d {
+    if cfg!(feature = "checked") {
+        assert!(v.is_finite(), "{} is not finite: {:?}", what, v);
+    }
+    v
+}
+
+/// Check that a quaternion is finite, but not necessarily a rotation.
+#[inline]
+#[track_caller]
+pub(crate) fn quaternion(what: &str, q: Quaternion) -> Quaternion {
+    if cfg!(feature = "checked") {
+        assert!(q.is_finite(), "{} is not finite: {:?}", what, q);
+    }
+    q
+}
+
+/// Check that a quaternion is a finite unit quaternion.
+#[inline]
+#[track_caller]
+pub(crate) fn unit_quaternion(what: &str, q: Quaternion) -> Quaternion {
+    if cfg!(feature = "checked") {
+        assert!(q.is_finite(), "{} is not finite: {:?}", what, q);
+        assert!(q.is_normalized(UNIT_TOLERANCE), "{} is not a unit quaternion: {:?}", what, q);
+    }
+    q
+}
+
+/// Check that a rotation matrix is finite and special orthogonal.
+#[inline]
+#[track_caller]
+pub(crate) fn rotation_matrix(what: &str, r: RotationMatrix) -> RotationMatrix {
+    if cfg!(feature = "checked") {
+        assert!(r.is_finite(), "{} is not finite: {:?}", what, r);
+        assert!(r.is_special_orthogonal(UNIT_TOLERANCE), "{} is not a rotation: {:?}", what, r);
+    }
+    r
+}
+
+
+#[cfg(all(test, feature = "checked"))]
+mod tests {
+    use crate::vector3d::Vector3d;
+    use crate::rotation::Rotation;
+    use crate::quaternion::Quaternion;
+    use crate::rotation_matrix::RotationMatrix;
+
+    #[test]
+    #[should_panic(expected = "result of Quaternion::multiply is not finite")]
+    fn nan_is_caught_where_it_appears() {
+        let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x());
+        let bad = Quaternion::new(f64::NAN, Vector3d::zero());
+        let _ = q.multiply(&bad);
+    }
+
+    #[test]
+    #[should_panic(expected = "rotation passed to Quaternion::rotate_vector is not a unit quaternion")]
+    fn unnormalized_rotation_is_caught() {
+        let _ = Quaternion::new(2.0, Vector3d::zero()).rotate_vector(&Vector3d::unit_x());
+    }
+
+    #[test]
+    #[should_panic(expected = "rotation passed to RotationMatrix::rotate_vector is not a rotation")]
+    fn reflection_is_caught() {
+        let flip = RotationMatrix::from_rows([Vector3d::unit_x(), Vector3d::unit_y(), -Vector3d::unit_z()]);
+        let _ = flip.rotate_vector(&Vector3d::unit_x());
+    }
+
+    #[test]
+    fn valid_pipelines_pass() {
+        let q = Quaternion::from_angle_axis(1.0, &Vector3d::new([1.0, 2.0, 3.0]));
+        let r = q.integrate(&Vector3d::new([0.1, 0.2, 0.3]), 0.01).as_rotation_matrix();
+        let v = r.after(&q).rotate_vector(&Vector3d::unit_y());
+        assert!(v.is_finite());
+    }
+}
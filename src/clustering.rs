@@ -0,0 +1,171 @@
+use crate::quaternion::Quaternion;
+use crate::statistics::mean_rotation;
+use crate::error::OrientationError;
+
+/// A partition of a set of rotations into clusters.
+#[derive(Clone, Debug)]
+pub struct Clustering {
+    /// The cluster of each input rotation, as an index into `centers`.
+    pub assignments: Vec<usize>,
+    /// The representative rotation of each cluster: the intrinsic mean
+    /// of its members.
+    pub centers: Vec<Quaternion>
+}
+
+impl Clustering {
+    /// The number of rotations assigned to each cluster.
+    #[must_use]
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.centers.len()];
+        for &a in &self.assignments {
+            sizes[a] += 1;
+        }
+        sizes
+    }
+}
+
+/// Cluster rotations into `k` groups by k-means on SO(3): each rotation
+/// is assigned to the center with the smallest geodesic angle, and each
+/// center is moved to the intrinsic mean of its members, until the
+/// assignments stop changing or `max_iterations` rounds have run.
+///
+/// Centers are seeded deterministically by farthest-point selection,
+/// starting from the first rotation, so the result is reproducible and
+/// well-separated hypotheses each get a center. Rotations are compared
+/// up to sign.
+///
+/// # Errors
+/// Returns an error if `k` is zero or larger than the number of
+/// rotations.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // Two groups of detections, around the identity and a half turn.
+/// let flip = Quaternion::from_angle_axis(std::f64::consts::PI, &Vector3d::unit_z());
+/// let detections: Vec<Quaternion> = [0.01, -0.02, 0.015].iter()
+///     .flat_map(|&a| {
+///         let noise = Quaternion::from_angle_axis(a, &Vector3d::unit_x());
+///         vec![noise, noise.before(&flip)]
+///     })
+///     .collect();
+/// let clustering = cluster_rotations(&detections, 2, 20).unwrap();
+/// assert_eq!(vec![3, 3], clustering.sizes());
+/// assert!(clustering.centers[1].angle_to(&flip) < 0.02);
+/// ```
+pub fn cluster_rotations(rotations: &[Quaternion], k: usize,
+                         max_iterations: usize) -> Result<Clustering, OrientationError> {
+    if k == 0 || k > rotations.len() {
+        return Err(OrientationError::InvalidParameter("cluster count must be positive and at most the number of rotations"))
+    }
+
+    enter_span!(DEBUG, "cluster_rotations", count = rotations.len(), k);
+    let mut centers = farthest_point_seeds(rotations, k);
+    let mut assignments = assign(rotations, &centers);
+    for _ in 0..max_iterations {
+        for (c, center) in centers.iter_mut().enumerate() {
+            let members: Vec<Quaternion> = rotations.iter()
+                .zip(assignments.iter())
+                .filter(|&(_, &a)| a == c)
+                .map(|(q, _)| *q)
+                .collect();
+            // A cluster left empty keeps its center.
+            if !members.is_empty() {
+                *center = mean_rotation(&members);
+            }
+        }
+        let updated = assign(rotations, &centers);
+        emit_event!(TRACE, reassigned = updated.iter().zip(&assignments).filter(|(a, b)| a != b).count(),
+                    "clustering iteration");
+        if updated == assignments {
+            break
+        }
+        assignments = updated;
+    }
+    Ok(Clustering { assignments, centers })
+}
+
+/// Pick `k` of the rotations, each as far as possible from those
+/// already picked.
+fn farthest_point_seeds(rotations: &[Quaternion], k: usize) -> Vec<Quaternion> {
+    let mut centers = vec![rotations[0]];
+    let mut distances: Vec<f64> = rotations.iter().map(|q| q.angle_to(&rotations[0])).collect();
+    while centers.len() < k {
+        let (farthest, _) = distances.iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap_or((0, &0.0));
+        let center = rotations[farthest];
+        for (d, q) in distances.iter_mut().zip(rotations.iter()) {
+            *d = d.min(q.angle_to(&center));
+        }
+        centers.push(center);
+    }
+    centers
+}
+
+/// The index of the nearest center to each rotation.
+fn assign(rotations: &[Quaternion], centers: &[Quaternion]) -> Vec<usize> {
+    rotations.iter()
+        .map(|q| {
+            centers.iter()
+                .map(|c| q.angle_to(c))
+                .enumerate()
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or(0, |(i, _)| i)
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::vector3d::Vector3d;
+    use crate::rotation::Rotation;
+
+    #[test]
+    fn recovers_separated_clusters() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let truths = [
+            Quaternion::identity(),
+            Quaternion::from_angle_axis(1.5, &Vector3d::new([1.0, 0.0, 1.0])),
+            Quaternion::from_angle_axis(2.5, &Vector3d::new([-0.5, 1.0, 0.2]))
+        ];
+        let mut rotations = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..60 {
+            let q = Quaternion::random_perturbation(&mut rng, &truths[i % 3], 0.05).unwrap();
+            // Either sign is the same rotation.
+            rotations.push(if i % 2 == 0 { q } else { q.scale(-1.0) });
+            labels.push(i % 3);
+        }
+        let clustering = cluster_rotations(&rotations, 3, 50).unwrap();
+        assert_eq!(vec![20, 20, 20], clustering.sizes());
+        for (a, label) in clustering.assignments.iter().zip(labels.iter()) {
+            let center = clustering.centers[*a];
+            assert!(center.angle_to(&truths[*label]) < 0.05);
+        }
+    }
+
+    #[test]
+    fn single_cluster_is_the_mean() {
+        let rotations = [
+            Quaternion::from_angle_axis(0.1, &Vector3d::unit_x()),
+            Quaternion::from_angle_axis(-0.1, &Vector3d::unit_x())
+        ];
+        let clustering = cluster_rotations(&rotations, 1, 10).unwrap();
+        assert_eq!(vec![0, 0], clustering.assignments);
+        assert!(clustering.centers[0].angle_to(&Quaternion::identity()) < 1.0e-9);
+    }
+
+    #[test]
+    fn invalid_cluster_count() {
+        let rotations = [Quaternion::identity()];
+        assert!(cluster_rotations(&rotations, 0, 10).is_err());
+        assert!(cluster_rotations(&rotations, 2, 10).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::orientation::Orientation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::error::OrientationError;
+
+/// A rotation prepared for applying to many vectors. It holds both the
+/// quaternion and its rotation matrix, converted once up front: vectors
+/// are rotated with the matrix, nine multiplications each, while
+/// composition uses the quaternion, which stays unit norm under repeated
+/// products more cheaply than a matrix stays orthogonal.
+///
+/// Composition converts the result again, so it pays for itself when
+/// each rotation is applied to a few vectors or more.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let q = Quaternion::from_angle_axis(std::f64::consts::FRAC_PI_2, &Vector3d::unit_z());
+/// let compiled = CompiledRotation::from(q);
+/// let points = vec![Vector3d::unit_x(); 100];
+/// let rotated = compiled.rotate_vectors(&points);
+/// assert!((rotated[99] - Vector3d::unit_y()).norm() < 1.0e-12);
+/// assert_eq!(q, compiled.as_quaternion());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CompiledRotation {
+    quaternion: Quaternion,
+    matrix: RotationMatrix
+}
+
+impl CompiledRotation {
+    /// Compile any rotation.
+    #[must_use]
+    pub fn new<R: Rotation>(rotation: &R) -> Self {
+        rotation.as_quaternion().into()
+    }
+}
+
+impl From<Quaternion> for CompiledRotation {
+    fn from(quaternion: Quaternion) -> Self {
+        CompiledRotation { quaternion, matrix: quaternion.as_rotation_matrix() }
+    }
+}
+
+impl From<RotationMatrix> for CompiledRotation {
+    /// Keeps `matrix` as given, so vectors are rotated exactly as
+    /// `matrix` would rotate them.
+    fn from(matrix: RotationMatrix) -> Self {
+        CompiledRotation { quaternion: matrix.as_quaternion(), matrix }
+    }
+}
+
+impl From<CompiledRotation> for Quaternion {
+    fn from(r: CompiledRotation) -> Self {
+        r.quaternion
+    }
+}
+
+impl Default for CompiledRotation {
+    /// The identity rotation.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Rotation for CompiledRotation {
+    type Output = Self;
+
+    fn identity() -> Self {
+        CompiledRotation { quaternion: Quaternion::identity(), matrix: RotationMatrix::identity() }
+    }
+
+    fn inverse(&self) -> Result<Self, OrientationError> {
+        let quaternion = self.quaternion.inverse()?;
+        Ok(CompiledRotation { quaternion, matrix: self.matrix.inverse_unchecked() })
+    }
+
+    fn inverse_unchecked(&self) -> Self {
+        CompiledRotation {
+            quaternion: self.quaternion.inverse_unchecked(),
+            matrix: self.matrix.inverse_unchecked()
+        }
+    }
+
+    fn as_quaternion(&self) -> Quaternion {
+        self.quaternion
+    }
+
+    fn as_rotation_matrix(&self) -> RotationMatrix {
+        self.matrix
+    }
+
+    fn angle_axis(&self) -> (f64, Vector3d) {
+        self.quaternion.angle_axis()
+    }
+
+    fn before<T: Rotation>(&self, r: &T) -> Self {
+        self.quaternion.before(r).into()
+    }
+
+    fn after<T: Rotation>(&self, r: &T) -> Self {
+        self.quaternion.after(r).into()
+    }
+
+    fn rotate_vector(&self, v: &Vector3d) -> Vector3d {
+        self.matrix.rotate_vector(v)
+    }
+
+    fn rotate_vector_inverse(&self, v: &Vector3d) -> Vector3d {
+        self.matrix.rotate_vector_inverse(v)
+    }
+
+    fn rotate_vectors_into(&self, vs: &[Vector3d], rotated: &mut [Vector3d]) {
+        self.matrix.rotate_vectors_into(vs, rotated);
+    }
+}
+
+impl Orientation for CompiledRotation {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_quaternion() {
+        let q = Quaternion::from_angle_axis(0.9, &Vector3d::new([1.0, -2.0, 0.5]));
+        let p = Quaternion::from_angle_axis(-0.3, &Vector3d::unit_y());
+        let compiled = CompiledRotation::from(q);
+        let v = Vector3d::new([0.4, 1.0, -3.0]);
+        assert_vector_approx_eq!(q.rotate_vector(&v), compiled.rotate_vector(&v), 1.0e-12);
+        assert_vector_approx_eq!(v, compiled.rotate_vector_inverse(&compiled.rotate_vector(&v)), 1.0e-12);
+
+        let composed = compiled.before(&p);
+        assert!(composed.as_quaternion().angle_to(&q.before(&p)) < 1.0e-12);
+        assert_vector_approx_eq!(q.before(&p).rotate_vector(&v), composed.rotate_vector(&v), 1.0e-12);
+        assert!(p.after(&compiled).angle_to(&p.after(&q)) < 1.0e-12);
+
+        let inverse = compiled.inverse().unwrap();
+        assert_vector_approx_eq!(v, inverse.rotate_vector(&compiled.rotate_vector(&v)), 1.0e-12);
+        assert_float_approx_eq!(q.heading(), compiled.heading(), 1.0e-12);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::attitude_filter::AttitudeFilter;
+use crate::error::OrientationError;
+
+/// A complementary filter blending a gyro-integrated orientation with
+/// an absolute orientation source such as accelerometer tilt or a
+/// vision-based pose.
+///
+/// The gyro is trusted over time scales shorter than the time constant
+/// and the absolute source over longer ones. Orientations rotate
+/// body-frame vectors into the world frame, and angular velocities are
+/// expressed in the body frame.
+#[derive(Copy, Clone, Debug)]
+pub struct ComplementaryFilter {
+    time_constant: f64,
+    estimate: Quaternion,
+    last_dt: f64
+}
+
+impl ComplementaryFilter {
+    /// Create a new filter from an initial orientation and a time
+    /// constant in seconds. A time constant of zero means the absolute
+    /// source is trusted completely.
+    ///
+    /// # Panics
+    /// Panics if the time constant is negative or not finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let filter = ComplementaryFilter::new(Quaternion::identity(), 0.5);
+    /// assert_eq!(Quaternion::identity(), filter.estimate());
+    /// ```
+    #[must_use]
+    pub fn new(initial: Quaternion, time_constant: f64) -> Self {
+        assert!(time_constant >= 0.0 && time_constant.is_finite(),
+                "Time constant must be non-negative and finite");
+        Self {
+            time_constant,
+            estimate: initial,
+            last_dt: 0.0
+        }
+    }
+
+    /// The current orientation estimate.
+    #[must_use]
+    pub fn estimate(&self) -> Quaternion {
+        self.estimate
+    }
+
+    /// The time constant, in seconds.
+    #[must_use]
+    pub fn time_constant(&self) -> f64 {
+        self.time_constant
+    }
+
+    /// Propagate the estimate using a body-frame angular velocity (in
+    /// radians per second) held constant over `dt` seconds.
+    pub fn propagate(&mut self, gyro: &Vector3d, dt: f64) {
+        self.estimate = self.estimate.integrate(gyro, dt);
+        self.renormalize();
+    }
+
+    /// Pull the estimate towards an absolute orientation measurement,
+    /// weighted by how much time (`dt` seconds) has passed relative to
+    /// the time constant.
+    pub fn correct(&mut self, absolute: &Quaternion, dt: f64) {
+        let denominator = self.time_constant + dt;
+        let alpha = if denominator > 0.0 { dt / denominator } else { 1.0 };
+
+        // Slerp takes the shorter arc, so an absolute source reporting
+        // -q instead of q does not drag the estimate the long way round.
+        self.estimate = self.estimate.slerp(absolute, alpha);
+        self.renormalize();
+    }
+
+    /// Propagate with the gyro and then correct with the absolute
+    /// measurement, returning the new estimate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mut filter = ComplementaryFilter::new(Quaternion::identity(), 0.0);
+    /// let absolute = Quaternion::from_angle_axis(0.1, &Vector3d::unit_z());
+    /// let q = filter.update(&Vector3d::zero(), &absolute, 0.01);
+    /// assert_eq!(absolute, q);
+    /// ```
+    pub fn update(&mut self, gyro: &Vector3d, absolute: &Quaternion, dt: f64) -> Quaternion {
+        self.propagate(gyro, dt);
+        self.correct(absolute, dt);
+        self.estimate
+    }
+
+    /// Pull the estimate towards agreement with a body-frame
+    /// observation of a known world-frame reference direction, weighted
+    /// as in `correct`. Only the two degrees of freedom the observation
+    /// constrains are corrected.
+    ///
+    /// # Errors
+    /// Returns an error if either vector has norm close to zero.
+    pub fn correct_with_vector(&mut self, observed: &Vector3d, reference: &Vector3d,
+                               dt: f64) -> Result<(), OrientationError> {
+        let denominator = self.time_constant + dt;
+        let alpha = if denominator > 0.0 { dt / denominator } else { 1.0 };
+
+        // The body-frame rotation taking the observation onto the
+        // predicted direction is the full correction; apply a fraction
+        // of it.
+        let predicted = self.estimate.rotate_vector_inverse(reference);
+        let full = Quaternion::from_two_vectors(observed, &predicted)?;
+        let partial = Quaternion::from_rotation_vector(&full.rotation_vector().scalar_multiple(alpha));
+        emit_event!(TRACE, alpha, correction = alpha * full.angle_axis().0, "complementary filter correction");
+        self.estimate = partial.before(&self.estimate);
+        self.renormalize();
+        Ok(())
+    }
+
+    /// Remove accumulated floating point drift from the estimate.
+    fn renormalize(&mut self) {
+        if let Ok(q) = self.estimate.normalized() {
+            self.estimate = q;
+        }
+    }
+}
+
+impl AttitudeFilter for ComplementaryFilter {
+    fn predict(&mut self, gyro: &Vector3d, dt: f64) {
+        self.last_dt = dt;
+        self.propagate(gyro, dt);
+    }
+
+    /// Correct with a vector observation, weighted by the time step of
+    /// the most recent prediction. The noise variance is ignored; the
+    /// time constant plays its role.
+    fn correct_vector(&mut self, observed: &Vector3d, reference: &Vector3d,
+                      _noise_variance: f64) -> Result<(), OrientationError> {
+        self.correct_with_vector(observed, reference, self.last_dt)
+    }
+
+    fn estimate(&self) -> Quaternion {
+        self.estimate
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn gyro_only_integrates_rotation() {
+        let mut filter = ComplementaryFilter::new(Quaternion::identity(), 1.0);
+        let gyro = Vector3d::unit_z().scalar_multiple(PI / 2.0);
+        for _ in 0..100 {
+            filter.propagate(&gyro, 0.01);
+        }
+        let expected = Quaternion::from_angle_axis(PI / 2.0, &Vector3d::unit_z());
+        assert_quat_approx_eq!(expected, filter.estimate());
+    }
+
+    #[test]
+    fn zero_time_constant_tracks_absolute() {
+        let mut filter = ComplementaryFilter::new(Quaternion::identity(), 0.0);
+        let gyro = Vector3d::unit_x();
+        let absolute = Quaternion::from_angle_axis(0.3, &Vector3d::unit_y());
+        let q = filter.update(&gyro, &absolute, 0.01);
+        assert_quat_approx_eq!(absolute, q);
+    }
+
+    #[test]
+    fn converges_to_absolute() {
+        let mut filter = ComplementaryFilter::new(Quaternion::identity(), 0.1);
+        let absolute = Quaternion::from_angle_axis(0.5, &Vector3d::unit_x());
+        for _ in 0..1000 {
+            filter.update(&Vector3d::zero(), &absolute, 0.01);
+        }
+        assert_quat_approx_eq!(absolute, filter.estimate());
+    }
+
+    #[test]
+    fn absolute_in_opposite_hemisphere() {
+        let q = Quaternion::from_angle_axis(0.5, &Vector3d::unit_x());
+        let mut filter = ComplementaryFilter::new(q, 0.1);
+        // The same rotation, represented by -q.
+        let flipped = Quaternion::from_angle_axis(0.5 + 2.0 * PI, &Vector3d::unit_x());
+        filter.correct(&flipped, 0.01);
+        assert_quat_approx_eq!(q, filter.estimate());
+    }
+
+    #[test]
+    #[should_panic(expected = "Time constant must be non-negative and finite")]
+    fn negative_time_constant() {
+        let _ = ComplementaryFilter::new(Quaternion::identity(), -1.0);
+    }
+
+    #[test]
+    fn vector_correction_levels() {
+        let truth = Quaternion::from_angle_axis(0.3, &Vector3d::unit_y());
+        let up = Vector3d::unit_z();
+        let observed = truth.inverse_unchecked().rotate_vector(&up);
+        let mut filter = ComplementaryFilter::new(Quaternion::identity(), 0.0);
+        filter.correct_with_vector(&observed, &up, 0.01).unwrap();
+        assert_quat_approx_eq!(truth, filter.estimate());
+    }
+
+    #[test]
+    fn vector_correction_through_trait() {
+        let truth = Quaternion::from_angle_axis(0.3, &Vector3d::unit_y());
+        let up = Vector3d::unit_z();
+        let observed = truth.inverse_unchecked().rotate_vector(&up);
+        let mut filter = ComplementaryFilter::new(Quaternion::identity(), 0.1);
+        for _ in 0..1000 {
+            filter.predict(&Vector3d::zero(), 0.01);
+            filter.correct_vector(&observed, &up, 0.0).unwrap();
+        }
+        assert_quat_approx_eq!(truth, AttitudeFilter::estimate(&filter));
+    }
+}
@@ -1 +1,4 @@
 pub const DBL_EPSILON: f64 = 2.220_446_049_250_313e-16;
+
+/// Standard gravity, in meters per second squared.
+pub const STANDARD_GRAVITY: f64 = 9.806_65;
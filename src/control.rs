@@ -0,0 +1,104 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::{Quaternion, ErrorVectorMap};
+
+/// The tracking error between a desired and an actual attitude, in the
+/// form a PD attitude controller consumes.
+///
+/// Every vector is expressed in the actual body frame and points the
+/// way the body should turn: a control torque
+/// `kp * attitude_error + kd * rate_error` drives the body towards the
+/// desired attitude and rate.
+#[derive(Copy, Clone, Debug)]
+pub struct AttitudeError {
+    /// The rotation from the actual to the desired attitude, in the
+    /// actual body frame: `desired = actual * error_quaternion`. Its
+    /// real part is non-negative, so it describes the shorter way round.
+    pub error_quaternion: Quaternion,
+    /// The small-angle attitude error, twice the imaginary part of the
+    /// error quaternion. This equals the rotation vector to first order
+    /// and saturates smoothly for large errors.
+    pub attitude_error: Vector3d,
+    /// The desired angular velocity, carried into the actual body frame,
+    /// minus the actual angular velocity.
+    pub rate_error: Vector3d
+}
+
+/// Compute the attitude and rate errors between a desired and an actual
+/// state. Attitudes rotate body-frame vectors into the world frame and
+/// each angular velocity is expressed in its own body frame.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // The body needs to turn a little about its +z axis.
+/// let desired = Quaternion::from_angle_axis(0.1, &Vector3d::unit_z());
+/// let e = attitude_error(&desired, &Quaternion::identity(), &Vector3d::zero(), &Vector3d::zero());
+/// assert!(e.attitude_error.data[2] > 0.0);
+/// assert!((e.attitude_error - Vector3d::unit_z().scalar_multiple(0.1)).norm() < 1.0e-3);
+/// ```
+#[must_use]
+pub fn attitude_error(desired: &Quaternion, actual: &Quaternion, desired_rate: &Vector3d,
+                      actual_rate: &Vector3d) -> AttitudeError {
+    let error = actual.inverse_unchecked().multiply(desired);
+    let error = if error.real_part() < 0.0 { error.scale(-1.0) } else { error };
+    AttitudeError {
+        error_quaternion: error,
+        attitude_error: error.to_error_vector(ErrorVectorMap::TwiceImaginary),
+        rate_error: error.rotate_vector(desired_rate) - *actual_rate
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn error_quaternion_composes_to_desired() {
+        let desired = Quaternion::from_angle_axis(1.2, &Vector3d::new([0.3, -1.0, 0.5]));
+        let actual = Quaternion::from_angle_axis(-0.4, &Vector3d::new([1.0, 0.2, 0.0]));
+        let e = attitude_error(&desired, &actual, &Vector3d::zero(), &Vector3d::zero());
+        assert!(actual.multiply(&e.error_quaternion).angle_to(&desired) < 1.0e-12);
+    }
+
+    #[test]
+    fn sign_of_representation_does_not_matter() {
+        let desired = Quaternion::from_angle_axis(0.5, &Vector3d::unit_x());
+        // The same attitude, represented by -q.
+        let flipped = Quaternion::from_angle_axis(0.5 + 2.0 * PI, &Vector3d::unit_x());
+        let a = attitude_error(&desired, &Quaternion::identity(), &Vector3d::zero(), &Vector3d::zero());
+        let b = attitude_error(&flipped, &Quaternion::identity(), &Vector3d::zero(), &Vector3d::zero());
+        assert_vector_approx_eq!(a.attitude_error, b.attitude_error, 1.0e-12);
+        assert!(a.attitude_error.data[0] > 0.0);
+    }
+
+    #[test]
+    fn desired_rate_is_carried_into_actual_frame() {
+        // Actual is a quarter turn about z from desired, so desired +x
+        // is actual +y.
+        let actual = Quaternion::from_angle_axis(-PI / 2.0, &Vector3d::unit_z());
+        let e = attitude_error(&Quaternion::identity(), &actual, &Vector3d::unit_x(), &Vector3d::zero());
+        assert_vector_approx_eq!(Vector3d::unit_y(), e.rate_error, 1.0e-12);
+    }
+
+    #[test]
+    fn pd_loop_converges() {
+        // A unit-inertia body without gyroscopic coupling, driven to a
+        // fixed attitude from a large initial error and a tumble.
+        let desired = Quaternion::from_angle_axis(2.5, &Vector3d::new([1.0, 1.0, -1.0]));
+        let mut q = Quaternion::identity();
+        let mut omega = Vector3d::new([0.5, -0.3, 0.2]);
+        let dt = 0.01;
+        for _ in 0..3000 {
+            let e = attitude_error(&desired, &q, &Vector3d::zero(), &omega);
+            let torque = e.attitude_error.scalar_multiple(4.0) + e.rate_error.scalar_multiple(3.0);
+            omega += torque.scalar_multiple(dt);
+            q = q.integrate(&omega, dt);
+        }
+        assert!(q.angle_to(&desired) < 1.0e-6);
+        assert!(omega.norm() < 1.0e-6);
+    }
+}
@@ -0,0 +1,230 @@
+use std::f64::consts::FRAC_1_SQRT_2;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::matrix3::Matrix3;
+
+/// The rotation carrying vectors expressed in a north-east-down world
+/// frame into the corresponding east-north-up frame. It swaps the first
+/// two axes and negates the third, and is its own inverse.
+///
+/// Apply it with `rotate_vector` to world-frame vectors, including
+/// angular velocities expressed in the world frame.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let north = Vector3d::unit_x();
+/// let enu = ned_to_enu().rotate_vector(&north);
+/// assert!((enu - Vector3d::unit_y()).norm() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn ned_to_enu() -> Quaternion {
+    Quaternion::new(0.0, Vector3d::new([FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0]))
+}
+
+/// The rotation carrying east-north-up vectors into north-east-down.
+/// This is the same rotation as `ned_to_enu`.
+#[must_use]
+pub fn enu_to_ned() -> Quaternion {
+    ned_to_enu()
+}
+
+/// The rotation carrying vectors expressed in a forward-right-down body
+/// frame into the corresponding forward-left-up frame. It negates the
+/// second and third axes and is its own inverse.
+///
+/// Apply it with `rotate_vector` to body-frame vectors, including
+/// body-frame angular velocities from a gyro.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // A right turn in FRD is a positive yaw rate about down.
+/// let rate = Vector3d::unit_z().scalar_multiple(0.5);
+/// let flu = frd_to_flu().rotate_vector(&rate);
+/// assert!((flu + rate).norm() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn frd_to_flu() -> Quaternion {
+    Quaternion::new(0.0, Vector3d::unit_x())
+}
+
+/// The rotation carrying forward-left-up vectors into
+/// forward-right-down. This is the same rotation as `frd_to_flu`.
+#[must_use]
+pub fn flu_to_frd() -> Quaternion {
+    frd_to_flu()
+}
+
+/// Convert an attitude rotating FRD body vectors into the NED world
+/// frame, as flight stacks report it, into the same physical attitude
+/// rotating FLU body vectors into the ENU world frame, as robotics and
+/// vision systems expect.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // Level, heading north.
+/// let enu = ned_frd_to_enu_flu(&Quaternion::identity());
+/// // The nose points north, which is ENU y.
+/// assert!((enu.rotate_vector(&Vector3d::unit_x()) - Vector3d::unit_y()).norm() < 1.0e-12);
+/// // Body up is world up.
+/// assert!((enu.rotate_vector(&Vector3d::unit_z()) - Vector3d::unit_z()).norm() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn ned_frd_to_enu_flu(attitude: &Quaternion) -> Quaternion {
+    flu_to_frd().before(attitude).before(&ned_to_enu())
+}
+
+/// Convert an attitude rotating FLU body vectors into the ENU world
+/// frame into the same physical attitude rotating FRD body vectors into
+/// the NED world frame. The inverse of `ned_frd_to_enu_flu`.
+#[must_use]
+pub fn enu_flu_to_ned_frd(attitude: &Quaternion) -> Quaternion {
+    frd_to_flu().before(attitude).before(&enu_to_ned())
+}
+
+/// The coordinate conventions of common robotics and game engines.
+///
+/// Converting between them is a change of basis: vectors are permuted
+/// and sign-flipped, and rotations are conjugated by the same change of
+/// basis, so they describe the same physical motion. Between left- and
+/// right-handed systems this reverses the apparent sense of rotation.
+/// Only directions are converted; scaling positions between units, such
+/// as Unreal's centimetres, is left to the caller.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoordinateSystem {
+    /// ROS (REP 103) and most robotics software: right-handed, x
+    /// forward, y left, z up.
+    Ros,
+    /// Unity: left-handed, x right, y up, z forward.
+    Unity,
+    /// Unreal Engine: left-handed, x forward, y right, z up.
+    Unreal
+}
+
+impl CoordinateSystem {
+    /// Express a vector given in this coordinate system in another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// // Unity's up axis is ROS's z.
+    /// let up = CoordinateSystem::Unity.convert_vector(&Vector3d::unit_y(), CoordinateSystem::Ros);
+    /// assert!((up - Vector3d::unit_z()).norm() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn convert_vector(self, v: &Vector3d, to: Self) -> Vector3d {
+        self.change_of_basis(to).multiply_vector(v)
+    }
+
+    /// Express a rotation given in this coordinate system in another,
+    /// so that it moves the same physical directions to the same places.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// // A yaw to the left in ROS is a rotation about Unity's up axis.
+    /// let yaw = Quaternion::from_angle_axis(0.5, &Vector3d::unit_z());
+    /// let unity = CoordinateSystem::Ros.convert_rotation(&yaw, CoordinateSystem::Unity);
+    /// let (angle, axis) = unity.angle_axis();
+    /// assert!((angle - 0.5).abs() < 1.0e-12);
+    /// // Left-handed, so the same turn is negative about up.
+    /// assert!((axis + Vector3d::unit_y()).norm() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn convert_rotation(self, q: &Quaternion, to: Self) -> Quaternion {
+        // The axis of a rotation is a pseudovector: it also flips sign
+        // when the handedness changes.
+        let m = self.change_of_basis(to);
+        let (real, imaginary) = (q.real_part(), q.imaginary_part());
+        Quaternion::new(real, m.multiply_vector(&imaginary).scalar_multiple(m.determinant()))
+    }
+
+    /// The matrix taking coordinates in this system to ROS coordinates.
+    fn to_ros(self) -> Matrix3 {
+        match self {
+            CoordinateSystem::Ros => Matrix3::identity(),
+            CoordinateSystem::Unity => Matrix3::new([
+                [0.0, 0.0, 1.0],
+                [-1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0]
+            ]),
+            CoordinateSystem::Unreal => Matrix3::from_diagonal(&Vector3d::new([1.0, -1.0, 1.0]))
+        }
+    }
+
+    /// The matrix taking coordinates in this system to `to`.
+    fn change_of_basis(self, to: Self) -> Matrix3 {
+        to.to_ros().transpose().multiply(&self.to_ros())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_rotations() {
+        let v = Vector3d::new([1.0, 2.0, 3.0]);
+        assert_vector_approx_eq!(Vector3d::new([2.0, 1.0, -3.0]), ned_to_enu().rotate_vector(&v), 1.0e-12);
+        assert_vector_approx_eq!(Vector3d::new([1.0, -2.0, -3.0]), frd_to_flu().rotate_vector(&v), 1.0e-12);
+        assert_vector_approx_eq!(v, enu_to_ned().rotate_vector(&ned_to_enu().rotate_vector(&v)), 1.0e-12);
+        assert_vector_approx_eq!(v, flu_to_frd().rotate_vector(&frd_to_flu().rotate_vector(&v)), 1.0e-12);
+    }
+
+    #[test]
+    fn attitude_conversion_preserves_physical_directions() {
+        let ned = Quaternion::from_angle_axis(0.8, &Vector3d::new([0.3, -1.0, 2.0]));
+        let enu = ned_frd_to_enu_flu(&ned);
+        // A body vector, expressed in each convention, lands on the same
+        // world direction, expressed in each convention.
+        let body_frd = Vector3d::new([0.5, -0.2, 1.0]);
+        let body_flu = frd_to_flu().rotate_vector(&body_frd);
+        assert_vector_approx_eq!(ned_to_enu().rotate_vector(&ned.rotate_vector(&body_frd)),
+                                 enu.rotate_vector(&body_flu), 1.0e-12);
+        assert!(enu_flu_to_ned_frd(&enu).angle_to(&ned) < 1.0e-12);
+    }
+
+    #[test]
+    fn engine_conversions_match_matrix_conjugation() {
+        let systems = [CoordinateSystem::Ros, CoordinateSystem::Unity, CoordinateSystem::Unreal];
+        let q = Quaternion::from_angle_axis(1.1, &Vector3d::new([0.3, -1.0, 0.6]));
+        let v = Vector3d::new([0.5, 2.0, -1.5]);
+        for from in systems {
+            for to in systems {
+                let converted = from.convert_rotation(&q, to);
+                // Rotating then converting equals converting then rotating.
+                assert_vector_approx_eq!(from.convert_vector(&q.rotate_vector(&v), to),
+                                         converted.rotate_vector(&from.convert_vector(&v, to)), 1.0e-12);
+                assert_vector_approx_eq!(v, to.convert_vector(&from.convert_vector(&v, to), from), 1.0e-12);
+                assert!(to.convert_rotation(&converted, from).angle_to(&q) < 1.0e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn engine_axes() {
+        let forward = CoordinateSystem::Ros.convert_vector(&Vector3d::unit_x(), CoordinateSystem::Unity);
+        assert_vector_approx_eq!(Vector3d::unit_z(), forward, 1.0e-12);
+        let left = CoordinateSystem::Ros.convert_vector(&Vector3d::unit_y(), CoordinateSystem::Unreal);
+        assert_vector_approx_eq!(Vector3d::unit_y().negate(), left, 1.0e-12);
+        let right = CoordinateSystem::Unreal.convert_vector(&Vector3d::unit_y(), CoordinateSystem::Unity);
+        assert_vector_approx_eq!(Vector3d::unit_x(), right, 1.0e-12);
+    }
+
+    #[test]
+    fn heading_east() {
+        // Yawing 90 degrees right in NED/FRD faces east.
+        let ned = Quaternion::from_angle_axis(std::f64::consts::FRAC_PI_2, &Vector3d::unit_z());
+        let enu = ned_frd_to_enu_flu(&ned);
+        assert_vector_approx_eq!(Vector3d::unit_x(), enu.rotate_vector(&Vector3d::unit_x()), 1.0e-12);
+    }
+}
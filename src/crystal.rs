@@ -0,0 +1,175 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::symmetry::{SymmetryGroup, symmetric_representative};
+
+/// The proper rotations of the crystallographic point groups, one for
+/// each crystal system's Laue class. Orientations are taken to rotate
+/// crystal-frame vectors into the sample frame, with the crystal frame
+/// set up in the usual way for each system: the principal axis along z
+/// and, where there are two-fold axes perpendicular to it, one along x.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CrystalSymmetry {
+    /// Point group 432, with 24 rotations.
+    Cubic,
+    /// Point group 622, with 12 rotations.
+    Hexagonal,
+    /// Point group 32, with 6 rotations.
+    Trigonal,
+    /// Point group 422, with 8 rotations.
+    Tetragonal,
+    /// Point group 222, with 4 rotations.
+    Orthorhombic,
+    /// Point group 2, with 2 rotations; the unique axis is y.
+    Monoclinic,
+    /// Point group 1: the identity only.
+    Triclinic
+}
+
+impl CrystalSymmetry {
+    /// The symmetry group of the crystal system.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// assert_eq!(24, CrystalSymmetry::Cubic.group().order());
+    /// ```
+    #[must_use]
+    pub fn group(self) -> SymmetryGroup {
+        match self {
+            CrystalSymmetry::Cubic => SymmetryGroup::octahedral(),
+            CrystalSymmetry::Hexagonal => SymmetryGroup::dihedral(6),
+            CrystalSymmetry::Trigonal => SymmetryGroup::dihedral(3),
+            CrystalSymmetry::Tetragonal => SymmetryGroup::dihedral(4),
+            CrystalSymmetry::Orthorhombic => SymmetryGroup::dihedral(2),
+            CrystalSymmetry::Monoclinic => SymmetryGroup::generated(&[(2.0, [0.0, 1.0, 0.0])]),
+            CrystalSymmetry::Triclinic => SymmetryGroup::cyclic(1)
+        }
+    }
+}
+
+/// The misorientation between two crystal orientations: the smallest
+/// rotation, over all symmetry-equivalent descriptions of both, taking
+/// the first crystal lattice onto the second. Returns the angle in
+/// radians and the axis in the crystal frame of `a`, as by `angle_axis`.
+///
+/// Where several equivalent axes share the smallest angle, one of them
+/// is returned; they differ only by a symmetry of the crystal.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // Grains 100 degrees apart about a cube axis are only 10 degrees
+/// // apart, since a quarter turn maps the cube lattice onto itself.
+/// let a = Quaternion::identity();
+/// let b = Quaternion::from_angle_axis(100.0_f64.to_radians(), &Vector3d::unit_z());
+/// let (angle, axis) = misorientation(&a, &b, CrystalSymmetry::Cubic);
+/// assert!((angle - 10.0_f64.to_radians()).abs() < 1.0e-12);
+/// // The axis is one of the equivalent cube axes.
+/// assert!(axis.data.iter().any(|c| c.abs() > 1.0 - 1.0e-12));
+/// ```
+#[must_use]
+pub fn misorientation(a: &Quaternion, b: &Quaternion, symmetry: CrystalSymmetry) -> (f64, Vector3d) {
+    let group = symmetry.group();
+    let rotations = group.rotations();
+    let delta = a.inverse_unchecked().multiply(b);
+    rotations.iter()
+        .map(|s| symmetric_representative(&s.inverse_unchecked().multiply(&delta), rotations,
+                                          &Quaternion::identity()))
+        .map(|q| q.angle_axis())
+        .min_by(|x, y| x.0.total_cmp(&y.0))
+        .unwrap_or_else(|| delta.angle_axis())
+}
+
+/// Reduce an orientation into the fundamental zone of the crystal
+/// symmetry: the equivalent orientation with the smallest rotation
+/// angle from the reference orientation of the crystal frame. The sign
+/// is chosen with a non-negative real part.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let q = Quaternion::from_angle_axis(2.0, &Vector3d::new([1.0, 2.0, 3.0]));
+/// let reduced = reduce_to_fundamental_zone(&q, CrystalSymmetry::Cubic);
+/// // The largest angle in the cubic fundamental zone is about 62.8 degrees.
+/// assert!(reduced.angle_to(&Quaternion::identity()) < 62.8_f64.to_radians());
+/// ```
+#[must_use]
+pub fn reduce_to_fundamental_zone(orientation: &Quaternion, symmetry: CrystalSymmetry) -> Quaternion {
+    symmetric_representative(orientation, symmetry.group().rotations(), &Quaternion::identity())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const SYSTEMS: [CrystalSymmetry; 7] = [
+        CrystalSymmetry::Cubic, CrystalSymmetry::Hexagonal, CrystalSymmetry::Trigonal,
+        CrystalSymmetry::Tetragonal, CrystalSymmetry::Orthorhombic, CrystalSymmetry::Monoclinic,
+        CrystalSymmetry::Triclinic
+    ];
+
+    #[test]
+    fn groups_are_closed() {
+        let orders = [24, 12, 6, 8, 4, 2, 1];
+        for (symmetry, &order) in SYSTEMS.iter().zip(orders.iter()) {
+            let group = symmetry.group();
+            let rotations = group.rotations();
+            assert_eq!(order, rotations.len());
+            for a in rotations {
+                for b in rotations {
+                    let product = a.multiply(b);
+                    assert!(rotations.iter().any(|r| r.angle_to(&product) < 1.0e-9));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn misorientation_is_symmetric_and_invariant() {
+        let a = Quaternion::from_angle_axis(0.9, &Vector3d::new([1.0, -2.0, 0.4]));
+        let b = Quaternion::from_angle_axis(-1.7, &Vector3d::new([0.3, 1.0, 1.0]));
+        for &symmetry in &SYSTEMS {
+            let group = symmetry.group();
+            let rotations = group.rotations();
+            let (angle, _) = misorientation(&a, &b, symmetry);
+            let (reverse, _) = misorientation(&b, &a, symmetry);
+            assert_float_approx_eq!(angle, reverse, 1.0e-12);
+            let s = rotations[rotations.len() - 1];
+            let (equivalent, _) = misorientation(&a.multiply(&s), &b, symmetry);
+            assert_float_approx_eq!(angle, equivalent, 1.0e-12);
+            assert!(angle <= a.angle_to(&b) + 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn cubic_twin_misorientation() {
+        // The twin relationship, 60 degrees about <111>, is already the
+        // smallest description.
+        let twin = Quaternion::from_angle_axis(PI / 3.0, &Vector3d::new([1.0, 1.0, 1.0]));
+        let (angle, axis) = misorientation(&Quaternion::identity(), &twin, CrystalSymmetry::Cubic);
+        assert_float_approx_eq!(PI / 3.0, angle, 1.0e-12);
+        for component in &axis.data {
+            assert_float_approx_eq!(1.0 / 3.0_f64.sqrt(), component.abs(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn fundamental_zone_is_canonical() {
+        let q = Quaternion::from_angle_axis(2.4, &Vector3d::new([-0.2, 0.7, 1.0]));
+        for &symmetry in &SYSTEMS {
+            let group = symmetry.group();
+            let rotations = group.rotations();
+            let reduced = reduce_to_fundamental_zone(&q, symmetry);
+            for s in rotations {
+                let other = reduce_to_fundamental_zone(&q.multiply(s), symmetry);
+                assert!(reduced.angle_to(&other) < 1.0e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::matrix3::Matrix3;
+use crate::integration::quaternion_rate;
+use crate::error::OrientationError;
+
+/// The rotational state of a rigid body.
+#[derive(Copy, Clone, Debug)]
+pub struct RigidBodyState {
+    /// The orientation, rotating body-frame vectors into the inertial
+    /// frame.
+    pub orientation: Quaternion,
+    /// The body-frame angular velocity, in radians per second.
+    pub angular_velocity: Vector3d
+}
+
+impl RigidBodyState {
+    /// The angular momentum in the inertial frame, which is conserved in
+    /// the absence of external torque.
+    #[must_use]
+    pub fn angular_momentum(&self, inertia: &Matrix3) -> Vector3d {
+        self.orientation.rotate_vector(&inertia.multiply_vector(&self.angular_velocity))
+    }
+
+    /// The rotational kinetic energy.
+    #[must_use]
+    pub fn kinetic_energy(&self, inertia: &Matrix3) -> f64 {
+        0.5 * self.angular_velocity.dot(&inertia.multiply_vector(&self.angular_velocity))
+    }
+}
+
+/// Integrate Euler's rotational equations for a rigid body with no
+/// external torque. See `propagate_rigid_body`.
+///
+/// # Errors
+/// Returns an error if the inertia tensor is singular.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let inertia = Matrix3::from_diagonal(&Vector3d::new([1.0, 2.0, 3.0]));
+/// let initial = RigidBodyState {
+///     orientation: Quaternion::identity(),
+///     angular_velocity: Vector3d::new([0.1, 1.0, 0.1]),
+/// };
+/// let states = propagate_torque_free(&initial, &inertia, 10.0, 1000).unwrap();
+/// let (h0, h1) = (initial.angular_momentum(&inertia), states[1000].1.angular_momentum(&inertia));
+/// assert!((h0 - h1).norm() < 1.0e-6);
+/// ```
+pub fn propagate_torque_free(initial: &RigidBodyState, inertia: &Matrix3, duration: f64,
+                             steps: usize) -> Result<Vec<(f64, RigidBodyState)>, OrientationError> {
+    propagate_rigid_body(initial, inertia, |_, _| Vector3d::zero(), duration, steps)
+}
+
+/// Integrate Euler's rotational equations,
+/// `J w' = torque - w x (J w)`, together with the attitude kinematics,
+/// using `steps` steps of fourth-order Runge-Kutta over `duration`
+/// seconds. The inertia tensor `J` is expressed in the body frame, and
+/// the torque callback receives the time since the start and the
+/// current state and returns the body-frame external torque.
+///
+/// Returns the state at the start of every step and at the end, paired
+/// with the time since the start.
+///
+/// # Errors
+/// Returns an error if the inertia tensor is singular.
+#[allow(clippy::cast_precision_loss)]
+pub fn propagate_rigid_body<F>(initial: &RigidBodyState, inertia: &Matrix3, torque: F, duration: f64,
+                               steps: usize) -> Result<Vec<(f64, RigidBodyState)>, OrientationError>
+    where F: Fn(f64, &RigidBodyState) -> Vector3d {
+    let inverse = inertia.inverse()?;
+    let derivative = |t: f64, state: &RigidBodyState| {
+        let w = state.angular_velocity;
+        let gyroscopic = w.cross(&inertia.multiply_vector(&w));
+        let acceleration = inverse.multiply_vector(&(torque(t, state) - gyroscopic));
+        (quaternion_rate(&state.orientation, &w), acceleration)
+    };
+    let advance = |state: &RigidBodyState, k: &(Quaternion, Vector3d), h: f64| RigidBodyState {
+        orientation: state.orientation.add(&k.0.scale(h)),
+        angular_velocity: state.angular_velocity + k.1.scalar_multiple(h)
+    };
+
+    let steps = steps.max(1);
+    let dt = duration / steps as f64;
+    let mut states = Vec::with_capacity(steps + 1);
+    let mut state = *initial;
+    states.push((0.0, state));
+    for i in 0..steps {
+        let t = i as f64 * dt;
+        let k1 = derivative(t, &state);
+        let k2 = derivative(t + dt / 2.0, &advance(&state, &k1, dt / 2.0));
+        let k3 = derivative(t + dt / 2.0, &advance(&state, &k2, dt / 2.0));
+        let k4 = derivative(t + dt, &advance(&state, &k3, dt));
+
+        let q = k1.0.add(&k2.0.scale(2.0)).add(&k3.0.scale(2.0)).add(&k4.0);
+        let w = k1.1 + k2.1.scalar_multiple(2.0) + k3.1.scalar_multiple(2.0) + k4.1;
+        let next = advance(&state, &(q, w), dt / 6.0);
+        state = RigidBodyState {
+            orientation: next.orientation.normalized().unwrap_or(next.orientation),
+            angular_velocity: next.angular_velocity
+        };
+        states.push((t + dt, state));
+    }
+    Ok(states)
+}
+
+/// The rate of change of a vector as seen from the inertial frame, by
+/// the transport theorem: `d/dt|inertial v = d/dt|body v + w x v`.
+///
+/// Every argument and the result are expressed in body-frame
+/// coordinates: `vector` is the vector itself, `body_derivative` its
+/// rate of change as seen by an observer rotating with the body, and
+/// `angular_velocity` the body-frame angular velocity of the body
+/// relative to the inertial frame. Rotate the result by the body
+/// orientation for inertial-frame coordinates.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // A point fixed on a body spinning about z at 2 rad/s moves
+/// // tangentially when viewed from outside.
+/// let r = Vector3d::unit_x();
+/// let v = inertial_derivative(&r, &Vector3d::zero(), &Vector3d::unit_z().scalar_multiple(2.0));
+/// assert!((v - Vector3d::unit_y().scalar_multiple(2.0)).norm() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn inertial_derivative(vector: &Vector3d, body_derivative: &Vector3d,
+                           angular_velocity: &Vector3d) -> Vector3d {
+    *body_derivative + angular_velocity.cross(vector)
+}
+
+/// The rate of change of a vector as seen from the rotating body frame,
+/// the inverse of `inertial_derivative`:
+/// `d/dt|body v = d/dt|inertial v - w x v`. All vectors are in
+/// body-frame coordinates.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // A vector fixed in inertial space appears to turn backwards to an
+/// // observer on a spinning body.
+/// let w = Vector3d::unit_z();
+/// let d = body_derivative(&Vector3d::unit_x(), &Vector3d::zero(), &w);
+/// assert!((d + Vector3d::unit_y()).norm() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn body_derivative(vector: &Vector3d, inertial_derivative: &Vector3d,
+                       angular_velocity: &Vector3d) -> Vector3d {
+    *inertial_derivative - angular_velocity.cross(vector)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inertia() -> Matrix3 {
+        Matrix3::new([
+            [2.0, 0.1, 0.0],
+            [0.1, 3.0, -0.2],
+            [0.0, -0.2, 5.0]
+        ])
+    }
+
+    #[test]
+    fn conserves_momentum_and_energy() {
+        let initial = RigidBodyState {
+            orientation: Quaternion::from_angle_axis(0.5, &Vector3d::new([1.0, 2.0, 3.0])),
+            angular_velocity: Vector3d::new([0.3, -1.0, 0.5])
+        };
+        let j = inertia();
+        let states = propagate_torque_free(&initial, &j, 20.0, 4000).unwrap();
+        let (h0, e0) = (initial.angular_momentum(&j), initial.kinetic_energy(&j));
+        for (_, s) in &states {
+            assert_vector_approx_eq!(h0, s.angular_momentum(&j), 1.0e-8);
+            assert_float_approx_eq!(e0, s.kinetic_energy(&j), 1.0e-8);
+        }
+    }
+
+    #[test]
+    fn principal_axis_spin_is_steady() {
+        let j = Matrix3::from_diagonal(&Vector3d::new([1.0, 2.0, 3.0]));
+        let initial = RigidBodyState {
+            orientation: Quaternion::identity(),
+            angular_velocity: Vector3d::unit_z().scalar_multiple(2.0)
+        };
+        let states = propagate_torque_free(&initial, &j, 1.0, 100).unwrap();
+        let last = states[100].1;
+        assert_vector_approx_eq!(initial.angular_velocity, last.angular_velocity, 1.0e-12);
+        assert_quat_approx_eq!(Quaternion::from_angle_axis(2.0, &Vector3d::unit_z()), last.orientation, 1.0e-9);
+    }
+
+    #[test]
+    fn intermediate_axis_is_unstable() {
+        // Spin about the intermediate axis with a tiny perturbation
+        // flips over: the Dzhanibekov effect.
+        let j = Matrix3::from_diagonal(&Vector3d::new([1.0, 2.0, 3.0]));
+        let initial = RigidBodyState {
+            orientation: Quaternion::identity(),
+            angular_velocity: Vector3d::new([1.0e-3, 1.0, 1.0e-3])
+        };
+        let states = propagate_torque_free(&initial, &j, 40.0, 8000).unwrap();
+        assert!(states.iter().any(|(_, s)| s.angular_velocity.data[1] < -0.9));
+    }
+
+    #[test]
+    fn constant_torque_spins_up() {
+        let j = Matrix3::from_diagonal(&Vector3d::new([2.0, 2.0, 2.0]));
+        let initial = RigidBodyState {
+            orientation: Quaternion::identity(),
+            angular_velocity: Vector3d::zero()
+        };
+        let states = propagate_rigid_body(&initial, &j, |_, _| Vector3d::unit_x(), 2.0, 200).unwrap();
+        let last = states[200].1;
+        assert_vector_approx_eq!(Vector3d::unit_x(), last.angular_velocity, 1.0e-12);
+        assert_quat_approx_eq!(Quaternion::from_angle_axis(1.0, &Vector3d::unit_x()), last.orientation, 1.0e-9);
+    }
+
+    #[test]
+    fn transport_theorem_matches_finite_difference() {
+        // A body-fixed point, differenced in the inertial frame.
+        let q = Quaternion::from_angle_axis(0.7, &Vector3d::new([1.0, 0.5, -0.3]));
+        let w = Vector3d::new([0.4, -1.1, 0.8]);
+        let r = Vector3d::new([0.2, 1.0, 0.5]);
+        let dt = 1.0e-6;
+        let later = q.integrate(&w, dt);
+        let numeric = (later.rotate_vector(&r) - q.rotate_vector(&r)).scalar_multiple(1.0 / dt);
+        let analytic = q.rotate_vector(&inertial_derivative(&r, &Vector3d::zero(), &w));
+        assert_vector_approx_eq!(numeric, analytic, 1.0e-5);
+
+        let rate = Vector3d::new([0.3, 0.0, -2.0]);
+        assert_vector_approx_eq!(rate, body_derivative(&r, &inertial_derivative(&r, &rate, &w), &w), 1.0e-12);
+    }
+
+    #[test]
+    fn small_inertia() {
+        // A 10 g, 2 cm fragment; torque-free motion does not depend on
+        // the scale of the inertia tensor.
+        let initial = RigidBodyState {
+            orientation: Quaternion::from_angle_axis(0.5, &Vector3d::new([1.0, 2.0, 3.0])),
+            angular_velocity: Vector3d::new([0.3, -1.0, 0.5])
+        };
+        let j = inertia();
+        let expected = propagate_torque_free(&initial, &j, 5.0, 500).unwrap();
+        let states = propagate_torque_free(&initial, &j.scalar_multiple(6.7e-7 / 2.0), 5.0, 500).unwrap();
+        let (last, expected_last) = (states[500].1, expected[500].1);
+        assert_vector_approx_eq!(expected_last.angular_velocity, last.angular_velocity, 1.0e-12);
+        assert_quat_approx_eq!(expected_last.orientation, last.orientation, 1.0e-12);
+    }
+
+    #[test]
+    fn singular_inertia() {
+        let initial = RigidBodyState {
+            orientation: Quaternion::identity(),
+            angular_velocity: Vector3d::unit_x()
+        };
+        assert!(propagate_torque_free(&initial, &Matrix3::zero(), 1.0, 10).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::f64::consts::PI;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+
+/// The Earth's mean rotation rate relative to inertial space, in
+/// radians per second of UT1.
+pub const EARTH_ROTATION_RATE: f64 = 7.292_115_146_706_979e-5;
+
+/// Axis conventions for a local-level frame on the Earth's surface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LocalLevel {
+    /// East, north, up.
+    Enu,
+    /// North, east, down.
+    Ned
+}
+
+/// The rotation carrying vectors expressed in the Earth-centered,
+/// Earth-fixed frame into the local-level frame at a geodetic latitude
+/// and longitude, both in radians. Its inverse carries local vectors
+/// back into ECEF.
+///
+/// Geodetic latitude is the angle between the equatorial plane and the
+/// ellipsoid normal, as GNSS receivers report it, so the local vertical
+/// follows the ellipsoid normal rather than pointing at the Earth's
+/// center. Height does not affect the rotation.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // On the equator at the prime meridian, ECEF x points straight up.
+/// let q = ecef_to_local(0.0, 0.0, LocalLevel::Enu);
+/// assert!((q.rotate_vector(&Vector3d::unit_x()) - Vector3d::unit_z()).norm() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn ecef_to_local(latitude: f64, longitude: f64, frame: LocalLevel) -> Quaternion {
+    let (sin_lat, cos_lat) = latitude.sin_cos();
+    let (sin_lon, cos_lon) = longitude.sin_cos();
+    let east = Vector3d::new([-sin_lon, cos_lon, 0.0]);
+    let north = Vector3d::new([-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat]);
+    let up = Vector3d::new([cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]);
+
+    // The rows are the local axes expressed in ECEF.
+    let rows = match frame {
+        LocalLevel::Enu => [east, north, up],
+        LocalLevel::Ned => [north, east, up.negate()]
+    };
+    RotationMatrix::from_rows(rows).as_quaternion()
+}
+
+/// The Earth rotation angle in radians, in `[0, 2 pi)`, at `seconds`
+/// of UT1 since the J2000.0 epoch (noon on 1 January 2000), using the
+/// IERS 2003 linear model.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // One sidereal day later, the Earth has turned a full revolution.
+/// let sidereal_day = 2.0 * std::f64::consts::PI / EARTH_ROTATION_RATE;
+/// let difference = earth_rotation_angle(sidereal_day) - earth_rotation_angle(0.0);
+/// assert!(difference.abs() < 1.0e-9);
+/// ```
+#[must_use]
+pub fn earth_rotation_angle(seconds: f64) -> f64 {
+    let days = seconds / 86_400.0;
+    // Adding the whole-day count separately keeps the large term from
+    // swamping the fraction.
+    let turns = days.fract() + 0.779_057_273_264_0 + 0.002_737_811_911_354_48 * days;
+    2.0 * PI * turns.rem_euclid(1.0)
+}
+
+/// The rotation carrying vectors expressed in an Earth-centered
+/// inertial frame into the Earth-centered, Earth-fixed frame, at
+/// `seconds` of UT1 since the J2000.0 epoch.
+///
+/// This is a rotation by the Earth rotation angle about the shared z
+/// axis only. It ignores precession, nutation and polar motion, so the
+/// inertial frame is effectively the celestial intermediate frame of
+/// date; against J2000 coordinates the error, dominated by precession,
+/// grows by about a seventh of a degree per decade from the epoch.
+/// That is adequate for most low-orbit simulation, but not for precise
+/// orbit determination.
+///
+/// Velocities additionally need the transport term: see
+/// `body_derivative` with angular velocity `EARTH_ROTATION_RATE` about
+/// z.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let q = eci_to_ecef(3600.0);
+/// let back = ecef_to_eci(3600.0).rotate_vector(&q.rotate_vector(&Vector3d::unit_x()));
+/// assert!((back - Vector3d::unit_x()).norm() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn eci_to_ecef(seconds: f64) -> Quaternion {
+    Quaternion::from_angle_axis(-earth_rotation_angle(seconds), &Vector3d::unit_z())
+}
+
+/// The rotation carrying Earth-fixed vectors into the inertial frame,
+/// the inverse of `eci_to_ecef`.
+#[must_use]
+pub fn ecef_to_eci(seconds: f64) -> Quaternion {
+    Quaternion::from_angle_axis(earth_rotation_angle(seconds), &Vector3d::unit_z())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+    use crate::conventions::enu_to_ned;
+
+    #[test]
+    fn axes_at_known_locations() {
+        // At the north pole, up is ECEF z.
+        let pole = ecef_to_local(FRAC_PI_2, 0.3, LocalLevel::Enu);
+        assert_vector_approx_eq!(Vector3d::unit_z(), pole.rotate_vector(&Vector3d::unit_z()), 1.0e-12);
+
+        // On the equator at 90 degrees east, ECEF y is up and ECEF x
+        // points west.
+        let q = ecef_to_local(0.0, FRAC_PI_2, LocalLevel::Enu);
+        assert_vector_approx_eq!(Vector3d::unit_z(), q.rotate_vector(&Vector3d::unit_y()), 1.0e-12);
+        assert_vector_approx_eq!(Vector3d::unit_x().negate(), q.rotate_vector(&Vector3d::unit_x()), 1.0e-12);
+
+        // ECEF z, the rotation axis, lies north and up in mid-latitudes.
+        let q = ecef_to_local(0.7, -1.2, LocalLevel::Ned);
+        let axis = q.rotate_vector(&Vector3d::unit_z());
+        assert_vector_approx_eq!(Vector3d::new([0.7_f64.cos(), 0.0, -0.7_f64.sin()]), axis, 1.0e-12);
+    }
+
+    #[test]
+    fn earth_rotation() {
+        // The IERS reference value at the epoch.
+        assert_float_approx_eq!(2.0 * PI * 0.779_057_273_264_0, earth_rotation_angle(0.0), 1.0e-15);
+
+        // A fixed point on the equator, seen from inertial space, moves
+        // east at the rotation rate.
+        let t = 1.0e8;
+        let dt = 1.0;
+        let site = Vector3d::unit_x();
+        let before = ecef_to_eci(t).rotate_vector(&site);
+        let after = ecef_to_eci(t + dt).rotate_vector(&site);
+        let moved = before.cross(&after).data[2].asin();
+        assert_float_approx_eq!(EARTH_ROTATION_RATE * dt, moved, 1.0e-12);
+        assert!(eci_to_ecef(t).angle_to(&ecef_to_eci(t).inverse_unchecked()) < 1.0e-12);
+    }
+
+    #[test]
+    fn ned_matches_enu_convention_change() {
+        let (lat, lon) = (-0.6, 2.5);
+        let enu = ecef_to_local(lat, lon, LocalLevel::Enu);
+        let ned = ecef_to_local(lat, lon, LocalLevel::Ned);
+        assert!(enu.before(&enu_to_ned()).angle_to(&ned) < 1.0e-12);
+    }
+}
@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::fmt;
+
+/// The ways an operation on rotations can fail.
+///
+/// Variants carrying a `&'static str` describe which argument or input
+/// was at fault; the others are self-explanatory. New variants may be
+/// added, so matches should include a wildcard arm.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OrientationError {
+    /// A vector or quaternion has norm too close to zero to normalize
+    /// or invert.
+    ZeroNorm,
+    /// A matrix that should be a rotation is not orthogonal.
+    NotOrthogonal,
+    /// An input is infinite or NaN.
+    NotFinite,
+    /// A matrix is singular and cannot be inverted.
+    Singular,
+    /// A covariance matrix is not positive semi-definite.
+    NotPositiveSemiDefinite,
+    /// Two directions that must span a plane are parallel.
+    Parallel,
+    /// Timestamps or knot times are not strictly increasing.
+    NotIncreasing,
+    /// A query time lies outside the range of the data.
+    OutOfRange,
+    /// Two frames of a transform graph are not connected.
+    NotConnected,
+    /// A link would break the tree structure of a transform graph, or
+    /// change the kind of an existing link.
+    InvalidLink(&'static str),
+    /// There are too few inputs, or they do not determine an answer.
+    InsufficientData(&'static str),
+    /// A parameter is outside its allowed range.
+    InvalidParameter(&'static str),
+    /// A string could not be parsed as a vector or rotation.
+    InvalidFormat(&'static str)
+}
+
+impl fmt::Display for OrientationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrientationError::ZeroNorm => write!(f, "norm is too close to zero"),
+            OrientationError::NotOrthogonal => write!(f, "matrix is not orthogonal"),
+            OrientationError::NotFinite => write!(f, "value is not finite"),
+            OrientationError::Singular => write!(f, "matrix is singular"),
+            OrientationError::NotPositiveSemiDefinite => write!(f, "matrix is not positive semi-definite"),
+            OrientationError::Parallel => write!(f, "directions are parallel"),
+            OrientationError::NotIncreasing => write!(f, "times are not strictly increasing"),
+            OrientationError::OutOfRange => write!(f, "time is outside the range of the data"),
+            OrientationError::NotConnected => write!(f, "frames are not connected"),
+            OrientationError::InvalidLink(reason)
+            | OrientationError::InsufficientData(reason)
+            | OrientationError::InvalidParameter(reason)
+            | OrientationError::InvalidFormat(reason) => write!(f, "{reason}")
+        }
+    }
+}
+
+impl Error for OrientationError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!("norm is too close to zero", OrientationError::ZeroNorm.to_string());
+        let error = OrientationError::InvalidParameter("window is too short");
+        assert_eq!("window is too short", error.to_string());
+        let boxed: Box<dyn Error> = Box::new(error);
+        assert_eq!("window is too short", boxed.to_string());
+    }
+
+    #[test]
+    fn errors_can_be_matched() {
+        use crate::vector3d::Vector3d;
+        assert_eq!(Err(OrientationError::ZeroNorm), Vector3d::zero().normalized());
+    }
+}
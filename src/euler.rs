@@ -0,0 +1,279 @@
+use std::f64::consts::{PI, FRAC_PI_2};
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::units::Radians;
+use crate::angles::wrap_to_pi;
+
+/// Below this distance from zero or a half turn, the middle Euler
+/// angle is treated as gimbal lock.
+const GIMBAL_LOCK_THRESHOLD: f64 = 1.0e-7;
+
+/// An intrinsic Euler angle sequence: rotations about body axes, each
+/// about the axis as moved by the rotations before it. For example
+/// `Zyx` is yaw about z, then pitch about the new y, then roll about the
+/// newest x.
+///
+/// An extrinsic sequence, about fixed world axes, is the intrinsic
+/// sequence with the axes and the angles reversed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum EulerSequence {
+    Xyz, Xzy, Yxz, Yzx, Zxy, Zyx,
+    Xyx, Xzx, Yxy, Yzy, Zxz, Zyz
+}
+
+impl EulerSequence {
+    /// The indices of the three axes, in order.
+    fn axes(self) -> [usize; 3] {
+        match self {
+            EulerSequence::Xyz => [0, 1, 2],
+            EulerSequence::Xzy => [0, 2, 1],
+            EulerSequence::Yxz => [1, 0, 2],
+            EulerSequence::Yzx => [1, 2, 0],
+            EulerSequence::Zxy => [2, 0, 1],
+            EulerSequence::Zyx => [2, 1, 0],
+            EulerSequence::Xyx => [0, 1, 0],
+            EulerSequence::Xzx => [0, 2, 0],
+            EulerSequence::Yxy => [1, 0, 1],
+            EulerSequence::Yzy => [1, 2, 1],
+            EulerSequence::Zxz => [2, 0, 2],
+            EulerSequence::Zyz => [2, 1, 2]
+        }
+    }
+
+    /// Whether the first and last axes coincide (a proper Euler
+    /// sequence) rather than all three differing (Tait-Bryan).
+    fn is_proper(self) -> bool {
+        let axes = self.axes();
+        axes[0] == axes[2]
+    }
+}
+
+fn basis(index: usize) -> Vector3d {
+    let mut data = [0.0; 3];
+    data[index] = 1.0;
+    Vector3d::new(data)
+}
+
+impl Quaternion {
+    /// Compose intrinsic Euler angles, in radians or given as `Degrees`,
+    /// in the given sequence. The result rotates body-frame vectors into
+    /// the world frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_euler_angles(EulerSequence::Zyx, [0.3, 0.0, 0.0]);
+    /// assert!(q.angle_to(&Quaternion::from_angle_axis(0.3, &Vector3d::unit_z())) < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn from_euler_angles<A: Into<Radians>>(sequence: EulerSequence, angles: [A; 3]) -> Self {
+        let axes = sequence.axes();
+        IntoIterator::into_iter(angles).zip(axes).fold(Self::identity(), |q, (angle, axis)| {
+            q.multiply(&Self::from_angle_axis(angle, &basis(axis)))
+        })
+    }
+
+    /// Decompose into intrinsic Euler angles, in radians, in the given
+    /// sequence. The first and last angles are in `(-pi, pi]`. The middle
+    /// angle is in `[-pi/2, pi/2]` for Tait-Bryan sequences and `[0, pi]`
+    /// for proper Euler sequences. At gimbal lock, where only the sum or
+    /// difference of the first and last angles is determined, the last
+    /// angle is set to zero.
+    ///
+    /// The method is that of Bernardes and Viollet (2022), which works
+    /// directly on the quaternion and is accurate near gimbal lock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let angles = [0.1, -0.4, 2.0];
+    /// let q = Quaternion::from_euler_angles(EulerSequence::Zyx, angles);
+    /// let recovered = q.euler_angles(EulerSequence::Zyx);
+    /// for n in 0..3 {
+    ///     assert!((angles[n] - recovered[n]).abs() < 1.0e-12);
+    /// }
+    /// ```
+    #[must_use]
+    #[allow(clippy::many_single_char_names, clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+    pub fn euler_angles(&self, sequence: EulerSequence) -> [f64; 3] {
+        // Work with the equivalent extrinsic sequence, reversed.
+        let axes = sequence.axes();
+        let (i, j) = (axes[2], axes[1]);
+        let proper = sequence.is_proper();
+        let k = if proper { 3 - i - j } else { axes[0] };
+        let sign = ((i as i64 - j as i64) * (j as i64 - k as i64) * (k as i64 - i as i64) / 2) as f64;
+
+        let w = self.real_part();
+        let v = self.imaginary_part().data;
+        let (a, b, c, d) = if proper {
+            (w, v[i], v[j], v[k] * sign)
+        } else {
+            (w - v[j], v[i] + v[k] * sign, v[j] + w, v[k] * sign - v[i])
+        };
+
+        let mut middle = 2.0 * c.hypot(d).atan2(a.hypot(b));
+        let locked = middle.abs() <= GIMBAL_LOCK_THRESHOLD || (middle - PI).abs() <= GIMBAL_LOCK_THRESHOLD;
+        let half_sum = b.atan2(a);
+        let half_difference = d.atan2(c);
+        let (mut first, mut last) = (half_sum - half_difference, half_sum + half_difference);
+        if !proper {
+            last *= sign;
+            middle -= FRAC_PI_2;
+        }
+        // Back to the intrinsic order.
+        std::mem::swap(&mut first, &mut last);
+
+        if locked {
+            // Only the first and last angles together are determined;
+            // zero the last and attribute the whole turn to the first.
+            let r = self.multiply(&Self::from_angle_axis(-middle, &basis(axes[1])));
+            first = 2.0 * r.imaginary_part().data[axes[0]].atan2(r.real_part());
+            last = 0.0;
+        }
+        [wrap_to_pi(first), wrap_to_pi(middle), wrap_to_pi(last)]
+    }
+
+    /// Clamp each intrinsic Euler angle of the rotation, in the given
+    /// sequence, to its `(min, max)` limits in radians, and recompose.
+    /// Returns the clamped rotation and whether any angle was clamped.
+    /// This is the usual way of enforcing per-axis joint limits.
+    ///
+    /// Every rotation has two Euler decompositions away from gimbal
+    /// lock. Both are tried, and the clamped result closest to the
+    /// original rotation is returned, so limits that admit the rotation
+    /// under either decomposition leave it unchanged.
+    ///
+    /// # Panics
+    /// Panics if a lower limit exceeds its upper limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// // An elbow that bends about y from 0 to 150 degrees only.
+    /// let limits = [(0.0, 0.0), (0.0, 150.0_f64.to_radians()), (0.0, 0.0)];
+    /// let bent = Quaternion::from_angle_axis(-0.5, &Vector3d::unit_y());
+    /// let (clamped, was_clamped) = bent.clamp_euler_angles(EulerSequence::Xyz, limits);
+    /// assert!(was_clamped);
+    /// assert!(clamped.angle_to(&Quaternion::identity()) < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn clamp_euler_angles(&self, sequence: EulerSequence, limits: [(f64, f64); 3]) -> (Self, bool) {
+        let angles = self.euler_angles(sequence);
+        // The other decomposition of the same rotation.
+        let alternate = if sequence.is_proper() {
+            [wrap_to_pi(angles[0] + PI), -angles[1], wrap_to_pi(angles[2] + PI)]
+        } else {
+            [wrap_to_pi(angles[0] + PI), wrap_to_pi(PI - angles[1]), wrap_to_pi(angles[2] + PI)]
+        };
+
+        for (min, max) in &limits {
+            assert!(min <= max, "Lower limit must not exceed upper limit");
+        }
+        let within = |angles: &[f64; 3]| angles.iter()
+            .zip(limits.iter())
+            .all(|(angle, (min, max))| min <= angle && angle <= max);
+        if within(&angles) || within(&alternate) {
+            return (*self, false)
+        }
+
+        let clamp = |angles: [f64; 3]| {
+            let mut clamped = angles;
+            for (angle, (min, max)) in clamped.iter_mut().zip(limits.iter()) {
+                *angle = angle.clamp(*min, *max);
+            }
+            clamped
+        };
+        let candidates = [clamp(angles), clamp(alternate)];
+        let (first, second) = (Self::from_euler_angles(sequence, candidates[0]),
+                               Self::from_euler_angles(sequence, candidates[1]));
+        if first.angle_to(self) <= second.angle_to(self) { (first, true) } else { (second, true) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEQUENCES: [EulerSequence; 12] = [
+        EulerSequence::Xyz, EulerSequence::Xzy, EulerSequence::Yxz, EulerSequence::Yzx,
+        EulerSequence::Zxy, EulerSequence::Zyx, EulerSequence::Xyx, EulerSequence::Xzx,
+        EulerSequence::Yxy, EulerSequence::Yzy, EulerSequence::Zxz, EulerSequence::Zyz
+    ];
+
+    #[test]
+    fn round_trips_in_every_sequence() {
+        for &sequence in &SEQUENCES {
+            let middle = if sequence.is_proper() { 1.1 } else { -0.7 };
+            let angles = [2.5, middle, -1.3];
+            let q = Quaternion::from_euler_angles(sequence, angles);
+            let recovered = q.euler_angles(sequence);
+            for n in 0..3 {
+                assert_float_approx_eq!(angles[n], recovered[n], 1.0e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn decomposition_recomposes() {
+        let q = Quaternion::from_angle_axis(2.0, &Vector3d::new([0.3, -0.8, 0.5]));
+        for &sequence in &SEQUENCES {
+            let angles = q.euler_angles(sequence);
+            assert!(Quaternion::from_euler_angles(sequence, angles).angle_to(&q) < 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn intrinsic_order() {
+        // Yaw a quarter turn, then pitch about the new y (old -x), which
+        // pitches the nose, now along y, downwards.
+        let q = Quaternion::from_euler_angles(EulerSequence::Zyx, [FRAC_PI_2, 0.4, 0.0]);
+        let expected = Quaternion::from_angle_axis(FRAC_PI_2, &Vector3d::unit_z())
+            .multiply(&Quaternion::from_angle_axis(0.4, &Vector3d::unit_y()));
+        assert!(q.angle_to(&expected) < 1.0e-12);
+        let nose = Vector3d::new([0.0, 0.4_f64.cos(), -0.4_f64.sin()]);
+        assert_vector_approx_eq!(nose, q.rotate_vector(&Vector3d::unit_x()), 1.0e-12);
+    }
+
+    #[test]
+    fn gimbal_lock() {
+        for &sequence in &SEQUENCES {
+            let middle = if sequence.is_proper() { 0.0 } else { FRAC_PI_2 };
+            let q = Quaternion::from_euler_angles(sequence, [0.4, middle, 0.3]);
+            let angles = q.euler_angles(sequence);
+            assert_float_approx_eq!(0.0, angles[2]);
+            assert!(Quaternion::from_euler_angles(sequence, angles).angle_to(&q) < 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn clamping() {
+        let limits = [(-0.5, 0.5), (-0.2, 0.2), (-1.0, 1.0)];
+        let inside = Quaternion::from_euler_angles(EulerSequence::Zyx, [0.4, -0.1, 0.9]);
+        let (same, clamped) = inside.clamp_euler_angles(EulerSequence::Zyx, limits);
+        assert!(!clamped);
+        assert_eq!(inside, same);
+
+        let outside = Quaternion::from_euler_angles(EulerSequence::Zyx, [0.4, 0.6, 0.9]);
+        let (limited, clamped) = outside.clamp_euler_angles(EulerSequence::Zyx, limits);
+        assert!(clamped);
+        let expected = Quaternion::from_euler_angles(EulerSequence::Zyx, [0.4, 0.2, 0.9]);
+        assert!(limited.angle_to(&expected) < 1.0e-12);
+    }
+
+    #[test]
+    fn clamping_accepts_alternate_decomposition() {
+        // Within limits only in its second decomposition, where the
+        // middle angle exceeds a quarter turn.
+        let limits = [(-4.0, 4.0), (0.0, 3.0), (-4.0, 4.0)];
+        let q = Quaternion::from_euler_angles(EulerSequence::Xyz, [0.0, 2.0, 0.0]);
+        let (same, clamped) = q.clamp_euler_angles(EulerSequence::Xyz, limits);
+        assert!(!clamped);
+        assert_eq!(q, same);
+    }
+}
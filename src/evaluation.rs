@@ -0,0 +1,189 @@
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::statistics::mean_rotation;
+use crate::error::OrientationError;
+
+/// Geodesic errors between an estimated and a ground-truth orientation
+/// trajectory, with summary statistics. All angles are in radians.
+#[derive(Clone, Debug)]
+pub struct RotationErrors {
+    /// The error for each compared pair.
+    pub errors: Vec<f64>,
+    /// The root-mean-square error.
+    pub rmse: f64,
+    /// The mean error.
+    pub mean: f64,
+    /// The largest error.
+    pub max: f64,
+    /// The world-frame rotation applied to the estimate before
+    /// comparison: the truth is compared with `alignment * estimate`.
+    pub alignment: Quaternion
+}
+
+impl RotationErrors {
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn new(errors: Vec<f64>, alignment: Quaternion) -> Self {
+        let n = errors.len() as f64;
+        let rmse = (errors.iter().map(|e| e * e).sum::<f64>() / n).sqrt();
+        let mean = errors.iter().sum::<f64>() / n;
+        let max = errors.iter().copied().fold(0.0, f64::max);
+        Self {
+            errors,
+            rmse,
+            mean,
+            max,
+            alignment
+        }
+    }
+}
+
+/// The absolute rotation error between an estimated trajectory and the
+/// ground truth, paired by index.
+///
+/// The two trajectories may be expressed in different world frames. If
+/// `align` is set, the estimate is first rotated by the constant
+/// world-frame rotation minimizing the sum of squared geodesic errors;
+/// otherwise the frames are assumed to coincide.
+///
+/// # Errors
+/// Returns an error if the trajectories are empty or differ in length.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let offset = Quaternion::from_angle_axis(1.0, &Vector3d::unit_z());
+/// let truth = [Quaternion::identity(), Quaternion::from_angle_axis(0.5, &Vector3d::unit_x())];
+/// let estimated = [truth[0].before(&offset), truth[1].before(&offset)];
+/// let errors = absolute_rotation_error(&estimated, &truth, true).unwrap();
+/// assert!(errors.rmse < 1.0e-9);
+/// ```
+pub fn absolute_rotation_error(estimated: &[Quaternion], truth: &[Quaternion],
+                               align: bool) -> Result<RotationErrors, OrientationError> {
+    check_lengths(estimated, truth)?;
+
+    let alignment = if align {
+        let offsets: Vec<Quaternion> = estimated.iter()
+            .zip(truth.iter())
+            .map(|(e, t)| e.inverse_unchecked().before(t))
+            .collect();
+        mean_rotation(&offsets)
+    } else {
+        Quaternion::identity()
+    };
+
+    let errors = estimated.iter()
+        .zip(truth.iter())
+        .map(|(e, t)| e.before(&alignment).angle_to(t))
+        .collect();
+    Ok(RotationErrors::new(errors, alignment))
+}
+
+/// The relative rotation error between an estimated trajectory and the
+/// ground truth, paired by index: for each sample, the geodesic error in
+/// the rotation from that sample to the one `delta` samples later. This
+/// measures drift over a fixed interval and needs no alignment.
+///
+/// # Errors
+/// Returns an error if the trajectories differ in length, if `delta` is
+/// zero, or if there are not more than `delta` samples.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let truth: Vec<Quaternion> = (0..10).map(|i| Quaternion::from_angle_axis(0.1 * f64::from(i), &Vector3d::unit_y())).collect();
+/// // An estimate drifting by 0.01 radians per sample about z.
+/// let estimated: Vec<Quaternion> = truth.iter().enumerate()
+///     .map(|(i, q)| q.before(&Quaternion::from_angle_axis(0.01 * i as f64, &Vector3d::unit_z())))
+///     .collect();
+/// let errors = relative_rotation_error(&estimated, &truth, 1).unwrap();
+/// assert!(errors.max < 0.011);
+/// ```
+pub fn relative_rotation_error(estimated: &[Quaternion], truth: &[Quaternion],
+                               delta: usize) -> Result<RotationErrors, OrientationError> {
+    check_lengths(estimated, truth)?;
+    if delta == 0 || estimated.len() <= delta {
+        return Err(OrientationError::InvalidParameter("delta must be positive and shorter than the trajectories"))
+    }
+
+    let relative = |q: &[Quaternion], i: usize| q[i].inverse_unchecked().multiply(&q[i + delta]);
+    let errors = (0..estimated.len() - delta)
+        .map(|i| relative(estimated, i).angle_to(&relative(truth, i)))
+        .collect();
+    Ok(RotationErrors::new(errors, Quaternion::identity()))
+}
+
+pub(crate) fn check_lengths(estimated: &[Quaternion], truth: &[Quaternion]) -> Result<(), OrientationError> {
+    if estimated.is_empty() || estimated.len() != truth.len() {
+        return Err(OrientationError::InsufficientData("trajectories must be non-empty and of equal length"))
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::random::standard_normal_vector;
+    use crate::vector3d::Vector3d;
+
+    fn truth() -> Vec<Quaternion> {
+        (0..100)
+            .map(|i| Quaternion::from_angle_axis(0.05 * f64::from(i), &Vector3d::new([1.0, 2.0, 0.5])))
+            .collect()
+    }
+
+    #[test]
+    fn alignment_recovers_frame_offset() {
+        let offset = Quaternion::from_angle_axis(2.0, &Vector3d::new([0.3, -1.0, 0.2]));
+        let mut rng = StdRng::seed_from_u64(3);
+        let truth = truth();
+        let estimated: Vec<Quaternion> = truth.iter()
+            .map(|q| {
+                let noise = Quaternion::from_rotation_vector(&standard_normal_vector(&mut rng).scalar_multiple(0.01));
+                noise.before(q).before(&offset.inverse_unchecked())
+            })
+            .collect();
+
+        let unaligned = absolute_rotation_error(&estimated, &truth, false).unwrap();
+        let aligned = absolute_rotation_error(&estimated, &truth, true).unwrap();
+        assert!(unaligned.mean > 1.0);
+        assert!(aligned.rmse < 0.03);
+        assert!(aligned.alignment.angle_to(&offset) < 0.01);
+        assert_eq!(100, aligned.errors.len());
+    }
+
+    #[test]
+    fn summary_statistics() {
+        let truth = [Quaternion::identity(); 2];
+        let estimated = [
+            Quaternion::from_angle_axis(0.3, &Vector3d::unit_x()),
+            Quaternion::from_angle_axis(0.4, &Vector3d::unit_y())
+        ];
+        let errors = absolute_rotation_error(&estimated, &truth, false).unwrap();
+        assert_float_approx_eq!(0.35, errors.mean, 1.0e-12);
+        assert_float_approx_eq!(0.4, errors.max, 1.0e-12);
+        assert_float_approx_eq!(0.125_f64.sqrt(), errors.rmse, 1.0e-12);
+    }
+
+    #[test]
+    fn relative_error_ignores_frame_offset() {
+        let offset = Quaternion::from_angle_axis(1.0, &Vector3d::unit_z());
+        let truth = truth();
+        let estimated: Vec<Quaternion> = truth.iter().map(|q| q.before(&offset)).collect();
+        let errors = relative_rotation_error(&estimated, &truth, 10).unwrap();
+        assert_eq!(90, errors.errors.len());
+        assert!(errors.max < 1.0e-9);
+    }
+
+    #[test]
+    fn mismatched_trajectories() {
+        let truth = truth();
+        assert!(absolute_rotation_error(&truth[1..], &truth, false).is_err());
+        assert!(relative_rotation_error(&truth, &truth, 0).is_err());
+        assert!(relative_rotation_error(&truth, &truth, 100).is_err());
+    }
+}
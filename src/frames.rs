@@ -0,0 +1,218 @@
+//! Rotations and vectors tagged with the frames they relate.
+//!
+//! The types here wrap the untagged `Quaternion` and `Vector3d` with
+//! zero-sized frame markers, so that composing rotations whose frames do
+//! not chain, or rotating a vector expressed in the wrong frame, fails to
+//! compile. Frames are any types the caller chooses, typically empty
+//! structs.
+//!
+//! # Examples
+//!
+//! ```
+//! use orientations::frames;
+//! use orientations::{Quaternion, Vector3d};
+//!
+//! struct Camera;
+//! struct Body;
+//! struct World;
+//!
+//! let camera_to_body = frames::Rotation::<Camera, Body>::new(
+//!     Quaternion::from_angle_axis(0.1, &Vector3d::unit_y()));
+//! let body_to_world = frames::Rotation::<Body, World>::new(
+//!     Quaternion::from_angle_axis(1.0, &Vector3d::unit_z()));
+//!
+//! let camera_to_world = camera_to_body.then(&body_to_world);
+//! let boresight = frames::Vector3d::<Camera>::new(Vector3d::unit_z());
+//! let in_world: frames::Vector3d<World> = camera_to_world.rotate_vector(&boresight);
+//! ```
+//!
+//! Mixing up the order is caught at compile time:
+//!
+//! ```compile_fail
+//! use orientations::frames;
+//! use orientations::Quaternion;
+//!
+//! struct Camera;
+//! struct Body;
+//! struct World;
+//!
+//! let camera_to_body = frames::Rotation::<Camera, Body>::new(Quaternion::identity());
+//! let body_to_world = frames::Rotation::<Body, World>::new(Quaternion::identity());
+//! let wrong = body_to_world.then(&camera_to_body);
+//! ```
+//!
+//! as is rotating a vector from the wrong frame:
+//!
+//! ```compile_fail
+//! use orientations::frames;
+//! use orientations::{Quaternion, Vector3d};
+//!
+//! struct Body;
+//! struct World;
+//!
+//! let body_to_world = frames::Rotation::<Body, World>::new(Quaternion::identity());
+//! let v = frames::Vector3d::<World>::new(Vector3d::unit_x());
+//! let wrong = body_to_world.rotate_vector(&v);
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Sub};
+use crate::rotation::Rotation as _;
+use crate::quaternion::Quaternion;
+
+/// A rotation carrying vectors expressed in frame `From` into frame
+/// `To`.
+pub struct Rotation<From, To> {
+    quaternion: Quaternion,
+    frames: PhantomData<fn(From) -> To>
+}
+
+impl<From, To> Rotation<From, To> {
+    /// Tag a quaternion that rotates `From`-frame vectors into the `To`
+    /// frame.
+    #[must_use]
+    pub fn new(quaternion: Quaternion) -> Self {
+        Self {
+            quaternion,
+            frames: PhantomData
+        }
+    }
+
+    /// The underlying untagged quaternion.
+    #[must_use]
+    pub fn quaternion(&self) -> Quaternion {
+        self.quaternion
+    }
+
+    /// The rotation from `To` back to `From`.
+    #[must_use]
+    pub fn inverse(&self) -> Rotation<To, From> {
+        Rotation::new(self.quaternion.inverse_unchecked())
+    }
+
+    /// This rotation followed by `next`, carrying `From`-frame vectors
+    /// into the frame `next` leads to.
+    #[must_use]
+    pub fn then<Next>(&self, next: &Rotation<To, Next>) -> Rotation<From, Next> {
+        Rotation::new(self.quaternion.before(&next.quaternion))
+    }
+
+    /// Express a `From`-frame vector in the `To` frame.
+    #[must_use]
+    pub fn rotate_vector(&self, v: &Vector3d<From>) -> Vector3d<To> {
+        Vector3d::new(self.quaternion.rotate_vector(&v.vector))
+    }
+}
+
+impl<F> Rotation<F, F> {
+    /// The identity rotation of a frame onto itself.
+    #[must_use]
+    pub fn identity() -> Self {
+        Self::new(Quaternion::identity())
+    }
+}
+
+impl<From, To> Clone for Rotation<From, To> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<From, To> Copy for Rotation<From, To> {}
+
+impl<From, To> fmt::Debug for Rotation<From, To> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.quaternion)
+    }
+}
+
+/// A vector expressed in frame `F`.
+pub struct Vector3d<F> {
+    vector: crate::vector3d::Vector3d,
+    frame: PhantomData<fn() -> F>
+}
+
+impl<F> Vector3d<F> {
+    /// Tag a vector as expressed in frame `F`.
+    #[must_use]
+    pub fn new(vector: crate::vector3d::Vector3d) -> Self {
+        Self {
+            vector,
+            frame: PhantomData
+        }
+    }
+
+    /// The underlying untagged vector.
+    #[must_use]
+    pub fn vector(&self) -> crate::vector3d::Vector3d {
+        self.vector
+    }
+}
+
+impl<F> Clone for Vector3d<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for Vector3d<F> {}
+
+impl<F> PartialEq for Vector3d<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vector == other.vector
+    }
+}
+
+impl<F> fmt::Debug for Vector3d<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.vector)
+    }
+}
+
+impl<F> Add for Vector3d<F> {
+    type Output = Self;
+
+    /// Add two vectors expressed in the same frame.
+    fn add(self, other: Self) -> Self {
+        Self::new(self.vector + other.vector)
+    }
+}
+
+impl<F> Sub for Vector3d<F> {
+    type Output = Self;
+
+    /// Subtract two vectors expressed in the same frame.
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.vector - other.vector)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector3d::Vector3d as Untagged;
+
+    struct A;
+    struct B;
+    struct C;
+
+    #[test]
+    fn composition_matches_untagged() {
+        let p = Quaternion::from_angle_axis(0.4, &Untagged::unit_x());
+        let q = Quaternion::from_angle_axis(-1.1, &Untagged::new([1.0, 2.0, 0.0]));
+        let a_to_b = Rotation::<A, B>::new(p);
+        let b_to_c = Rotation::<B, C>::new(q);
+        let v = Vector3d::<A>::new(Untagged::new([0.3, -0.2, 1.0]));
+
+        let direct = a_to_b.then(&b_to_c).rotate_vector(&v);
+        let stepwise = b_to_c.rotate_vector(&a_to_b.rotate_vector(&v));
+        assert_vector_approx_eq!(direct.vector(), stepwise.vector(), 1.0e-12);
+        assert_vector_approx_eq!(q.rotate_vector(&p.rotate_vector(&v.vector())), direct.vector(), 1.0e-12);
+
+        let back = a_to_b.then(&b_to_c).inverse().rotate_vector(&direct);
+        assert_vector_approx_eq!(v.vector(), back.vector(), 1.0e-12);
+        assert_vector_approx_eq!(v.vector(), Rotation::<A, A>::identity().rotate_vector(&v).vector(), 1.0e-12);
+    }
+}
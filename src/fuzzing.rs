@@ -0,0 +1,77 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+use crate::vector3d::Vector3d;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+
+// These build values from raw bits, without normalizing, so fuzzers
+// reach the zero, non-unit and non-finite inputs that conversion and
+// inversion must handle.
+
+impl<'a> Arbitrary<'a> for Vector3d {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(<[f64; 3]>::arbitrary(u)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <[f64; 3]>::size_hint(depth)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Quaternion {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(f64::arbitrary(u)?, Vector3d::arbitrary(u)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <[f64; 4]>::size_hint(depth)
+    }
+}
+
+impl<'a> Arbitrary<'a> for RotationMatrix {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_rows(<[Vector3d; 3]>::arbitrary(u)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <[f64; 9]>::size_hint(depth)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotation::Rotation;
+
+    #[test]
+    fn consumes_raw_bits() {
+        let mut bytes = Vec::new();
+        for x in [1.0_f64, f64::NAN, 0.0, f64::INFINITY] {
+            bytes.extend_from_slice(&x.to_le_bytes());
+        }
+        let q = Quaternion::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert!(q.dot(&q).is_nan());
+        // Conversions must not panic on such input, unless the `checked`
+        // feature has been asked to.
+        if cfg!(not(feature = "checked")) {
+            let _ = q.as_rotation_matrix();
+            let _ = q.inverse();
+        }
+    }
+
+    #[test]
+    fn size_hints_cover_floats() {
+        assert_eq!((24, Some(24)), Vector3d::size_hint(0));
+        assert_eq!((32, Some(32)), Quaternion::size_hint(0));
+        assert_eq!((72, Some(72)), RotationMatrix::size_hint(0));
+    }
+
+    #[test]
+    fn matrix_from_bytes() {
+        let bytes: Vec<u8> = (0..72).collect();
+        let r = RotationMatrix::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        if cfg!(not(feature = "checked")) {
+            let _ = r.as_quaternion();
+        }
+    }
+}
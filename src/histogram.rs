@@ -0,0 +1,162 @@
+use std::f64::consts::PI;
+use crate::quaternion::Quaternion;
+use crate::euler::EulerSequence;
+use crate::error::OrientationError;
+
+/// A histogram over the space of rotations with equal-volume bins.
+///
+/// Bins follow the Hopf fibration: a rotation is located by the
+/// direction it carries the z-axis to, a point on the sphere, and by
+/// its twist about that direction, as in the ZYZ Euler angles
+/// `(phi, theta, psi)`. With `n` bands uniform in `cos(theta)`, `2n`
+/// sectors in `phi` and `2n` sectors in `psi`, every one of the `4n^3`
+/// bins has the same Haar measure, so counts of uniformly distributed
+/// rotations are uniform across bins and histograms of orientation
+/// distributions need no volume correction. Bins are roughly `pi / n`
+/// radians across, narrowing towards the poles of the sphere.
+#[derive(Clone, Debug)]
+pub struct OrientationHistogram {
+    resolution: usize,
+    counts: Vec<u64>
+}
+
+impl OrientationHistogram {
+    /// Create an empty histogram with `resolution` polar bands.
+    ///
+    /// # Errors
+    /// Returns an error if `resolution` is zero.
+    pub fn new(resolution: usize) -> Result<Self, OrientationError> {
+        if resolution == 0 {
+            return Err(OrientationError::InvalidParameter("histogram resolution must be positive"))
+        }
+        Ok(Self {
+            resolution,
+            counts: vec![0; 4 * resolution.pow(3)]
+        })
+    }
+
+    /// The index of the bin containing a rotation.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn bin(&self, rotation: &Quaternion) -> usize {
+        let n = self.resolution;
+        let [phi, theta, psi] = rotation.euler_angles(EulerSequence::Zyz);
+        let scaled = |fraction: f64, bins: usize| ((fraction * bins as f64) as usize).min(bins - 1);
+        let band = scaled((1.0 - theta.cos()) / 2.0, n);
+        let sector = scaled((phi + PI) / (2.0 * PI), 2 * n);
+        let twist = scaled((psi + PI) / (2.0 * PI), 2 * n);
+        (band * 2 * n + sector) * 2 * n + twist
+    }
+
+    /// The rotation at the center of a bin.
+    ///
+    /// # Panics
+    /// Panics if `index` is not a bin of this histogram.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn bin_center(&self, index: usize) -> Quaternion {
+        assert!(index < self.counts.len(), "Bin index out of range");
+        let n = self.resolution;
+        let (band, sector, twist) = (index / (4 * n * n), (index / (2 * n)) % (2 * n), index % (2 * n));
+        let center = |i: usize, bins: usize| (i as f64 + 0.5) / bins as f64;
+        let theta = (1.0 - 2.0 * center(band, n)).acos();
+        let phi = 2.0 * PI * center(sector, 2 * n) - PI;
+        let psi = 2.0 * PI * center(twist, 2 * n) - PI;
+        Quaternion::from_euler_angles(EulerSequence::Zyz, [phi, theta, psi])
+    }
+
+    /// Add a rotation to its bin.
+    pub fn insert(&mut self, rotation: &Quaternion) {
+        let index = self.bin(rotation);
+        self.counts[index] += 1;
+    }
+
+    /// The number of rotations inserted into the bin containing
+    /// `rotation`.
+    #[must_use]
+    pub fn count(&self, rotation: &Quaternion) -> u64 {
+        self.counts[self.bin(rotation)]
+    }
+
+    /// The count in each bin, by index.
+    #[must_use]
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// The total number of rotations inserted.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The indices of the bins whose centers are within `radius` radians
+    /// of `rotation`, for smoothing or voting over a neighborhood. The
+    /// bin containing `rotation` is always included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mut histogram = OrientationHistogram::new(6).unwrap();
+    /// let vote = Quaternion::from_angle_axis(1.0, &Vector3d::new([1.0, 2.0, 0.5]));
+    /// histogram.insert(&vote);
+    /// let nearby = Quaternion::from_angle_axis(1.1, &Vector3d::new([1.0, 2.0, 0.5]));
+    /// let votes: u64 = histogram.neighbors(&nearby, 0.8).iter().map(|&i| histogram.counts()[i]).sum();
+    /// assert_eq!(1, votes);
+    /// ```
+    #[must_use]
+    pub fn neighbors(&self, rotation: &Quaternion, radius: f64) -> Vec<usize> {
+        let own = self.bin(rotation);
+        (0..self.counts.len())
+            .filter(|&i| i == own || self.bin_center(i).angle_to(rotation) <= radius)
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::vector3d::Vector3d;
+    use crate::rotation::Rotation;
+
+    #[test]
+    fn centers_lie_in_their_bins() {
+        let histogram = OrientationHistogram::new(3).unwrap();
+        assert_eq!(108, histogram.counts().len());
+        for i in 0..histogram.counts().len() {
+            assert_eq!(i, histogram.bin(&histogram.bin_center(i)));
+        }
+    }
+
+    #[test]
+    fn uniform_rotations_fill_bins_evenly() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut histogram = OrientationHistogram::new(2).unwrap();
+        let n = 64000;
+        for _ in 0..n {
+            let q = Quaternion::random_within_angle(&mut rng, &Quaternion::identity(), PI).unwrap();
+            histogram.insert(&q);
+        }
+        assert_eq!(n, histogram.total());
+        let expected = n / 32;
+        for &count in histogram.counts() {
+            assert!(count.abs_diff(expected) < expected / 10);
+        }
+    }
+
+    #[test]
+    fn sign_and_count() {
+        let mut histogram = OrientationHistogram::new(4).unwrap();
+        let q = Quaternion::from_angle_axis(2.0, &Vector3d::new([0.0, 1.0, -1.0]));
+        histogram.insert(&q);
+        histogram.insert(&q.scale(-1.0));
+        assert_eq!(2, histogram.count(&q));
+        assert_eq!(0, histogram.count(&Quaternion::identity()));
+        assert!(histogram.neighbors(&q, 0.0).contains(&histogram.bin(&q)));
+        assert!(OrientationHistogram::new(0).is_err());
+    }
+}
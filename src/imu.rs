@@ -0,0 +1,272 @@
+use std::ops::Range;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::constants::STANDARD_GRAVITY;
+use crate::error::OrientationError;
+
+/// The result of estimating a constant gyro bias from stationary data.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GyroBiasEstimate {
+    /// The estimated bias, in radians per second.
+    pub bias: Vector3d,
+    /// The per-axis sample variance of the gyro noise, in radians
+    /// squared per second squared.
+    pub noise_variance: Vector3d,
+    /// The per-axis variance of the bias estimate itself, which shrinks
+    /// as the window grows.
+    pub bias_variance: Vector3d
+}
+
+/// Estimate a constant gyro bias from a window of samples collected
+/// while the sensor was stationary.
+///
+/// If `stillness_threshold` is given, every sample must lie within that
+/// distance (in radians per second) of the mean; otherwise the sensor
+/// is assumed to have moved and an error is returned.
+///
+/// # Errors
+/// Returns an error if fewer than two samples are given, or if the
+/// stillness check fails.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let samples = [
+///     Vector3d::new([0.011, -0.02, 0.003]),
+///     Vector3d::new([0.009, -0.02, 0.001]),
+///     Vector3d::new([0.010, -0.02, 0.002]),
+/// ];
+/// let estimate = estimate_gyro_bias(&samples, Some(0.01)).unwrap();
+/// assert!((estimate.bias - Vector3d::new([0.01, -0.02, 0.002])).norm() < 1.0e-12);
+/// ```
+#[allow(clippy::cast_precision_loss)]
+pub fn estimate_gyro_bias(samples: &[Vector3d], stillness_threshold: Option<f64>)
+                          -> Result<GyroBiasEstimate, OrientationError> {
+    if samples.len() < 2 {
+        return Err(OrientationError::InsufficientData("need at least two samples to estimate a gyro bias"))
+    }
+
+    let n = samples.len() as f64;
+    let bias = Vector3d::mean(samples)?;
+
+    if let Some(threshold) = stillness_threshold {
+        if samples.iter().any(|s| (*s - bias).norm() > threshold) {
+            return Err(OrientationError::InsufficientData("gyro samples exceed the stillness threshold"))
+        }
+    }
+
+    let mut sum_squares = [0.0; 3];
+    for s in samples {
+        let d = *s - bias;
+        for (total, x) in sum_squares.iter_mut().zip(d.data.iter()) {
+            *total += x * x;
+        }
+    }
+    let noise_variance = Vector3d::new(sum_squares).scalar_multiple(1.0 / (n - 1.0));
+
+    Ok(GyroBiasEstimate {
+        bias,
+        noise_variance,
+        bias_variance: noise_variance.scalar_multiple(1.0 / n)
+    })
+}
+
+
+/// Thresholds deciding whether an IMU is stationary.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StillnessDetector {
+    /// The magnitude of gravity, in the accelerometer's units.
+    pub gravity: f64,
+    /// The largest allowed difference between the accelerometer
+    /// magnitude and gravity.
+    pub accel_threshold: f64,
+    /// The largest allowed gyro magnitude, in radians per second.
+    pub gyro_threshold: f64,
+    /// The fewest consecutive still samples that count as a still
+    /// window.
+    pub min_samples: usize
+}
+
+impl Default for StillnessDetector {
+    /// Thresholds suited to a consumer-grade IMU reporting in meters
+    /// per second squared and radians per second.
+    fn default() -> Self {
+        Self {
+            gravity: STANDARD_GRAVITY,
+            accel_threshold: 0.2,
+            gyro_threshold: 0.05,
+            min_samples: 10
+        }
+    }
+}
+
+impl StillnessDetector {
+    /// Whether a single accelerometer/gyro sample looks stationary.
+    #[must_use]
+    pub fn is_still(&self, accel: &Vector3d, gyro: &Vector3d) -> bool {
+        (accel.norm() - self.gravity).abs() <= self.accel_threshold
+            && gyro.norm() <= self.gyro_threshold
+    }
+
+    /// The longest run of consecutive still samples, if it has at
+    /// least `min_samples` samples. The accelerometer and gyro buffers
+    /// are paired by index.
+    #[must_use]
+    pub fn longest_still_window(&self, accel: &[Vector3d], gyro: &[Vector3d]) -> Option<Range<usize>> {
+        let mut best = 0..0;
+        let mut start = 0;
+        for (i, (a, g)) in accel.iter().zip(gyro.iter()).enumerate() {
+            if !self.is_still(a, g) {
+                start = i + 1;
+            } else if i + 1 - start > best.len() {
+                best = start..i + 1;
+            }
+        }
+
+        if best.len() >= self.min_samples.max(1) { Some(best) } else { None }
+    }
+}
+
+/// An initial orientation estimate from stationary IMU data.
+#[derive(Clone, Debug)]
+pub struct CoarseAlignment {
+    /// The orientation, rotating body-frame vectors into a world frame
+    /// with x east, y north and z up.
+    pub attitude: Quaternion,
+    /// Whether the yaw (heading) was observed. Without a magnetometer
+    /// only the tilt is observable: the yaw is then arbitrary, chosen as
+    /// the shortest rotation leveling the body.
+    pub yaw_observable: bool,
+    /// The samples used, as indices into the input buffers.
+    pub window: Range<usize>,
+    /// The gyro bias estimated over the same window.
+    pub gyro_bias: GyroBiasEstimate
+}
+
+/// Detect the longest still window in paired accelerometer and gyro
+/// buffers and level the body using the average specific force, which
+/// at rest points up. If magnetometer samples (paired by index) are
+/// given, they fix the yaw so that magnetic north lies along world y.
+///
+/// # Errors
+/// Returns an error if no still window is found, or if the averaged
+/// accelerometer or magnetometer vectors are degenerate.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let accel = vec![Vector3d::new([0.0, 0.0, 9.81]); 20];
+/// let gyro = vec![Vector3d::zero(); 20];
+/// let detector = StillnessDetector::default();
+/// let alignment = coarse_alignment(&accel, &gyro, None, &detector).unwrap();
+/// assert!(!alignment.yaw_observable);
+/// ```
+pub fn coarse_alignment(accel: &[Vector3d], gyro: &[Vector3d], magnetometer: Option<&[Vector3d]>,
+                        detector: &StillnessDetector) -> Result<CoarseAlignment, OrientationError> {
+    let window = detector.longest_still_window(accel, gyro)
+        .ok_or(OrientationError::InsufficientData("no still window found in IMU data"))?;
+    let up = Vector3d::mean(&accel[window.clone()])?.normalized()?;
+    let gyro_bias = estimate_gyro_bias(&gyro[window.clone()], None)?;
+
+    let (attitude, yaw_observable) = match magnetometer {
+        Some(m) if m.len() >= window.end => {
+            // TRIAD with up as the primary direction: the rows of the
+            // body-to-world matrix are east, north and up in the body
+            // frame.
+            let field = Vector3d::mean(&m[window.clone()])?;
+            let east = field.cross(&up).normalized()?;
+            let north = up.cross(&east);
+            let r = RotationMatrix::from_rows([east, north, up]);
+            (r.as_quaternion(), true)
+        },
+        Some(_) => return Err(OrientationError::InsufficientData("magnetometer buffer is shorter than the still window")),
+        None => (Quaternion::from_two_vectors(&up, &Vector3d::unit_z())?, false)
+    };
+
+    Ok(CoarseAlignment {
+        attitude,
+        yaw_observable,
+        window,
+        gyro_bias
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Vector3d> {
+        vec![
+            Vector3d::new([0.1, 0.0, -0.1]),
+            Vector3d::new([0.3, 0.0, -0.1]),
+            Vector3d::new([0.2, 0.0, -0.4]),
+            Vector3d::new([0.2, 0.0, -0.2]),
+        ]
+    }
+
+    #[test]
+    fn bias_and_variance() {
+        let estimate = estimate_gyro_bias(&samples(), None).unwrap();
+        assert_vector_approx_eq!(Vector3d::new([0.2, 0.0, -0.2]), estimate.bias);
+        let expected = Vector3d::new([0.02 / 3.0, 0.0, 0.06 / 3.0]);
+        assert_vector_approx_eq!(expected, estimate.noise_variance, 1.0e-12);
+        assert_vector_approx_eq!(expected.scalar_multiple(0.25), estimate.bias_variance, 1.0e-12);
+    }
+
+    #[test]
+    fn moving_sensor_fails_stillness_check() {
+        assert!(estimate_gyro_bias(&samples(), Some(0.1)).is_err());
+        assert!(estimate_gyro_bias(&samples(), Some(0.3)).is_ok());
+    }
+
+    #[test]
+    fn too_few_samples() {
+        assert!(estimate_gyro_bias(&[Vector3d::zero()], None).is_err());
+    }
+
+    #[test]
+    fn longest_still_window() {
+        let detector = StillnessDetector { min_samples: 2, ..StillnessDetector::default() };
+        let g = Vector3d::unit_z().scalar_multiple(STANDARD_GRAVITY);
+        let accel = [g, g, g.scalar_multiple(2.0), g, g, g, g];
+        let gyro = [Vector3d::zero(); 7];
+        assert_eq!(Some(3..7), detector.longest_still_window(&accel, &gyro));
+
+        let spinning = [Vector3d::unit_x(); 7];
+        assert_eq!(None, detector.longest_still_window(&accel, &spinning));
+    }
+
+    #[test]
+    fn coarse_alignment_levels_body() {
+        let truth = Quaternion::from_angle_axis(0.3, &Vector3d::new([1.0, -1.0, 0.0]));
+        let up = Vector3d::unit_z().scalar_multiple(STANDARD_GRAVITY);
+        let accel = vec![truth.inverse_unchecked().rotate_vector(&up); 20];
+        let gyro = vec![Vector3d::new([0.01, 0.0, -0.01]); 20];
+        let alignment = coarse_alignment(&accel, &gyro, None, &StillnessDetector::default()).unwrap();
+
+        assert!(!alignment.yaw_observable);
+        assert_eq!(0..20, alignment.window);
+        assert_vector_approx_eq!(Vector3d::new([0.01, 0.0, -0.01]), alignment.gyro_bias.bias);
+        assert_vector_approx_eq!(Vector3d::unit_z(), alignment.attitude.rotate_vector(&accel[0].normalized().unwrap()));
+    }
+
+    #[test]
+    fn coarse_alignment_with_magnetometer() {
+        let truth = Quaternion::from_angle_axis(2.0, &Vector3d::new([0.2, -0.1, 1.0]));
+        let up = Vector3d::unit_z().scalar_multiple(STANDARD_GRAVITY);
+        let field = Vector3d::new([0.0, 20.0, -40.0]);
+        let to_body = truth.inverse_unchecked();
+        let accel = vec![to_body.rotate_vector(&up); 20];
+        let gyro = vec![Vector3d::zero(); 20];
+        let mag = vec![to_body.rotate_vector(&field); 20];
+        let alignment = coarse_alignment(&accel, &gyro, Some(&mag), &StillnessDetector::default()).unwrap();
+
+        assert!(alignment.yaw_observable);
+        assert_quat_approx_eq!(truth, alignment.attitude);
+    }
+}
@@ -0,0 +1,262 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+
+/// The time derivative of an orientation rotating at body-frame angular
+/// velocity `omega`: `q' = q * (0, omega) / 2`.
+pub(crate) fn quaternion_rate(q: &Quaternion, omega: &Vector3d) -> Quaternion {
+    q.multiply(&Quaternion::pure(*omega)).scale(0.5)
+}
+
+/// A single classical Runge-Kutta step of the quaternion kinematics,
+/// given the angular velocity at the start, middle and end of the step.
+fn rk4_step(q: &Quaternion, omega: [Vector3d; 3], dt: f64) -> Quaternion {
+    let [start, middle, end] = omega;
+    let k1 = quaternion_rate(q, &start);
+    let k2 = quaternion_rate(&q.add(&k1.scale(dt / 2.0)), &middle);
+    let k3 = quaternion_rate(&q.add(&k2.scale(dt / 2.0)), &middle);
+    let k4 = quaternion_rate(&q.add(&k3.scale(dt)), &end);
+
+    let increment = k1.add(&k2.scale(2.0)).add(&k3.scale(2.0)).add(&k4).scale(dt / 6.0);
+    let next = q.add(&increment);
+    next.normalized().unwrap_or(next)
+}
+
+/// Propagate an orientation from time `t0` over `duration` seconds with
+/// a time-varying body-frame angular velocity, using `steps` steps of
+/// fourth-order Runge-Kutta on the quaternion kinematics. The result is
+/// renormalized after every step.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // Spin up about z with constant angular acceleration; after one
+/// // second the angle is 1/2.
+/// let q = propagate_rk4(&Quaternion::identity(), |t| Vector3d::unit_z().scalar_multiple(t), 0.0, 1.0, 10);
+/// let expected = Quaternion::from_angle_axis(0.5, &Vector3d::unit_z());
+/// assert!(q.angle_to(&expected) < 1.0e-6);
+/// ```
+#[allow(clippy::cast_precision_loss)]
+pub fn propagate_rk4<F>(initial: &Quaternion, omega: F, t0: f64, duration: f64, steps: usize) -> Quaternion
+    where F: Fn(f64) -> Vector3d {
+    let steps = steps.max(1);
+    let dt = duration / steps as f64;
+    (0..steps).fold(*initial, |q, i| {
+        let t = t0 + dt * i as f64;
+        rk4_step(&q, [omega(t), omega(t + dt / 2.0), omega(t + dt)], dt)
+    })
+}
+
+/// Propagate an orientation through a buffer of body-frame angular
+/// velocity samples spaced `dt` seconds apart, using one step of
+/// fourth-order Runge-Kutta per interval. The angular velocity at the
+/// middle of each interval is the average of its end points. The
+/// result covers `(samples.len() - 1) * dt` seconds.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let samples = vec![Vector3d::unit_x(); 101];
+/// let q = propagate_rk4_samples(&Quaternion::identity(), &samples, 0.01);
+/// let expected = Quaternion::from_angle_axis(1.0, &Vector3d::unit_x());
+/// assert!(q.angle_to(&expected) < 1.0e-6);
+/// ```
+#[must_use]
+pub fn propagate_rk4_samples(initial: &Quaternion, samples: &[Vector3d], dt: f64) -> Quaternion {
+    samples.windows(2).fold(*initial, |q, pair| {
+        let middle = (pair[0] + pair[1]).scalar_multiple(0.5);
+        rk4_step(&q, [pair[0], middle, pair[1]], dt)
+    })
+}
+
+/// Propagate an orientation over `dt` seconds with a body-frame angular
+/// velocity that changes linearly, starting at `omega` with constant
+/// angular acceleration `alpha`. The rotation-vector increment keeps
+/// the second-order (coning) term,
+/// `omega * dt + alpha * dt^2 / 2 + (omega x alpha) * dt^3 / 12`,
+/// which a piecewise-constant rate assumption drops.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let q = propagate_second_order(&Quaternion::identity(), &Vector3d::zero(), &Vector3d::unit_z(), 1.0);
+/// let expected = Quaternion::from_angle_axis(0.5, &Vector3d::unit_z());
+/// assert!(q.angle_to(&expected) < 1.0e-12);
+/// ```
+#[must_use]
+pub fn propagate_second_order(initial: &Quaternion, omega: &Vector3d, alpha: &Vector3d, dt: f64) -> Quaternion {
+    let first = omega.scalar_multiple(dt) + alpha.scalar_multiple(dt * dt / 2.0);
+    let coning = omega.cross(alpha).scalar_multiple(dt * dt * dt / 12.0);
+    let increment = Quaternion::from_rotation_vector(&(first + coning));
+    initial.multiply(&increment)
+}
+
+/// Dead-reckon an orientation forward by `latency` seconds from its
+/// current body-frame angular velocity, for example to hide display or
+/// network latency. If the angular acceleration is known it is included
+/// as in `propagate_second_order`; otherwise the rate is held constant.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let omega = Vector3d::unit_z().scalar_multiple(2.0);
+/// let q = predict(&Quaternion::identity(), &omega, 0.02, None);
+/// let expected = Quaternion::from_angle_axis(0.04, &Vector3d::unit_z());
+/// assert!(q.angle_to(&expected) < 1.0e-12);
+/// ```
+#[must_use]
+pub fn predict(current: &Quaternion, omega: &Vector3d, latency: f64, alpha: Option<&Vector3d>) -> Quaternion {
+    match alpha {
+        Some(alpha) => propagate_second_order(current, omega, alpha, latency),
+        None => current.integrate(omega, latency)
+    }
+}
+
+/// The number of terms kept in a Magnus expansion.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MagnusOrder {
+    /// Keep only the integral of the angular velocity. Local error is
+    /// third order in the window length.
+    Second,
+    /// Also keep the first commutator (coning) term. Local error is
+    /// fifth order in the window length.
+    Fourth
+}
+
+/// Propagate an orientation across a window of body-frame angular
+/// velocity samples spaced `dt` seconds apart with a single Magnus
+/// expansion step, applying one exponential for the whole window
+/// rather than one per sample. The angular velocity is taken to vary
+/// linearly between samples, and the Magnus terms are integrated
+/// exactly for that interpolant.
+///
+/// The expansion converges while the total rotation over the window is
+/// well below pi. The second-order truncation ignores coning entirely;
+/// the fourth-order one keeps the coning term, including the part
+/// accumulated within each sample interval.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let samples = vec![Vector3d::unit_y(); 11];
+/// let q = propagate_magnus(&Quaternion::identity(), &samples, 0.01, MagnusOrder::Fourth);
+/// let expected = Quaternion::from_angle_axis(0.1, &Vector3d::unit_y());
+/// assert!(q.angle_to(&expected) < 1.0e-12);
+/// ```
+#[must_use]
+pub fn propagate_magnus(initial: &Quaternion, samples: &[Vector3d], dt: f64, order: MagnusOrder) -> Quaternion {
+    // theta is the running integral of the angular velocity.
+    let mut theta = Vector3d::zero();
+    let mut coning = Vector3d::zero();
+    for pair in samples.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let segment = (a + b).scalar_multiple(dt / 2.0);
+
+        // The integral of theta(t) x omega(t) over the segment, for
+        // omega varying linearly from a to b.
+        coning = coning + theta.cross(&segment) + a.cross(&b).scalar_multiple(dt * dt / 6.0);
+        theta += segment;
+    }
+
+    let exponent = match order {
+        MagnusOrder::Second => theta,
+        MagnusOrder::Fourth => theta + coning.scalar_multiple(0.5)
+    };
+    initial.multiply(&Quaternion::from_rotation_vector(&exponent))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_rate_matches_exponential() {
+        let omega = Vector3d::new([0.4, -1.0, 2.0]);
+        let q0 = Quaternion::from_angle_axis(0.3, &Vector3d::unit_y());
+        let q = propagate_rk4(&q0, |_| omega, 0.0, 0.5, 20);
+        assert_quat_approx_eq!(q0.integrate(&omega, 0.5), q, 1.0e-7);
+    }
+
+    #[test]
+    fn beats_piecewise_constant_steps() {
+        // A rate vector that changes direction, so the rotation axis
+        // moves. The reference uses many fine exponential steps.
+        let omega = |t: f64| Vector3d::new([t.cos(), t.sin(), 0.5]);
+        let reference = (0..100_000).fold(Quaternion::identity(), |q, i| {
+            let t = (f64::from(i) + 0.5) * 1.0e-5;
+            q.integrate(&omega(t), 1.0e-5)
+        });
+
+        let coarse = (0..10).fold(Quaternion::identity(), |q, i| {
+            q.integrate(&omega(f64::from(i) * 0.1), 0.1)
+        });
+        let rk4 = propagate_rk4(&Quaternion::identity(), omega, 0.0, 1.0, 10);
+
+        assert!(rk4.angle_to(&reference) < 1.0e-5);
+        assert!(rk4.angle_to(&reference) < coarse.angle_to(&reference) / 100.0);
+    }
+
+    #[test]
+    fn samples_match_closure() {
+        let omega = |t: f64| Vector3d::new([1.0, t, -t]);
+        let samples: Vec<Vector3d> = (0..=20).map(|i| omega(f64::from(i) * 0.05)).collect();
+        let from_samples = propagate_rk4_samples(&Quaternion::identity(), &samples, 0.05);
+        let from_closure = propagate_rk4(&Quaternion::identity(), omega, 0.0, 1.0, 20);
+        assert_quat_approx_eq!(from_closure, from_samples, 1.0e-12);
+    }
+
+    #[test]
+    fn too_few_samples_is_identity() {
+        let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_y());
+        assert_eq!(q, propagate_rk4_samples(&q, &[Vector3d::unit_x()], 0.1));
+    }
+
+    #[test]
+    fn second_order_includes_coning() {
+        let omega = Vector3d::new([2.0, 0.0, 1.0]);
+        let alpha = Vector3d::new([0.0, 3.0, -1.0]);
+        let dt = 0.2;
+        let rate = |t: f64| omega + alpha.scalar_multiple(t);
+        let reference = propagate_rk4(&Quaternion::identity(), rate, 0.0, dt, 1000);
+
+        let second = propagate_second_order(&Quaternion::identity(), &omega, &alpha, dt);
+        let midpoint = Quaternion::identity().integrate(&rate(dt / 2.0), dt);
+        assert!(second.angle_to(&reference) < 1.0e-4);
+        assert!(second.angle_to(&reference) < midpoint.angle_to(&reference) / 100.0);
+    }
+
+    #[test]
+    fn magnus_captures_coning() {
+        // Classic coning motion: the rate vector sweeps a cone at a
+        // frequency the samples only just resolve.
+        let rate = |t: f64| Vector3d::new([0.2 * (8.0 * t).cos(), 0.2 * (8.0 * t).sin(), 0.1]);
+        let dt = 0.02;
+        let samples: Vec<Vector3d> = (0..=25).map(|i| rate(f64::from(i) * dt)).collect();
+        let reference = propagate_rk4(&Quaternion::identity(), rate, 0.0, 0.5, 10_000);
+
+        let second = propagate_magnus(&Quaternion::identity(), &samples, dt, MagnusOrder::Second);
+        let fourth = propagate_magnus(&Quaternion::identity(), &samples, dt, MagnusOrder::Fourth);
+        assert!(fourth.angle_to(&reference) < 2.0e-4);
+        assert!(fourth.angle_to(&reference) < second.angle_to(&reference) / 10.0);
+    }
+
+    #[test]
+    fn predict_with_acceleration() {
+        let q0 = Quaternion::from_angle_axis(0.3, &Vector3d::unit_y());
+        let omega = Vector3d::new([0.5, 0.0, 1.0]);
+        let alpha = Vector3d::new([0.0, 4.0, 0.0]);
+        let reference = propagate_rk4(&q0, |t| omega + alpha.scalar_multiple(t), 0.0, 0.05, 100);
+
+        let constant = predict(&q0, &omega, 0.05, None);
+        let accelerating = predict(&q0, &omega, 0.05, Some(&alpha));
+        assert_quat_approx_eq!(q0.integrate(&omega, 0.05), constant, 1.0e-12);
+        assert!(accelerating.angle_to(&reference) < 1.0e-7);
+        assert!(constant.angle_to(&reference) > 1.0e-3);
+    }
+}
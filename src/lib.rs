@@ -3,16 +3,133 @@
 
 //! A library for Rotations and Orientations.
 
+pub use error::OrientationError;
+pub use units::{Radians, Degrees};
 pub use vector3d::Vector3d;
-pub use rotation::Rotation;
+pub use rotation::{Rotation, compose_all};
 pub use orientation::Orientation;
-pub use quaternion::Quaternion;
+pub use quaternion::{Quaternion, ErrorVectorMap};
+pub use euler::EulerSequence;
 pub use rotation_matrix::RotationMatrix;
+pub use any_rotation::AnyRotation;
+pub use compiled_rotation::CompiledRotation;
+pub use cached_orientation::CachedOrientation;
+pub use accumulator::{RotationAccumulator, DriftStatistics};
+pub use matrix3::Matrix3;
+pub use attitude_filter::AttitudeFilter;
+pub use complementary_filter::ComplementaryFilter;
+pub use mekf::Mekf;
+pub use orientation_covariance::{OrientationCovariance, propagate_monte_carlo};
+pub use integration::{propagate_rk4, propagate_rk4_samples, propagate_second_order,
+                      MagnusOrder, propagate_magnus, predict};
+pub use imu::{GyroBiasEstimate, estimate_gyro_bias, StillnessDetector, CoarseAlignment, coarse_alignment};
+pub use sequence::{angular_velocity_between, angular_velocities,
+                   make_sign_continuous, sign_continuous, SignContinuous};
+pub use smoothing::{SavitzkyGolay, SmoothedSample, LowPassFilter};
+pub use buffer::{OrientationBuffer, Extrapolation};
+pub use resample::{Resampler, Resampled, Interpolation};
+pub use spline::PiecewiseGeodesic;
+pub use evaluation::{RotationErrors, absolute_rotation_error, relative_rotation_error};
+pub use time_alignment::{TimeOffset, estimate_time_offset};
+pub use slew::{EigenaxisSlew, SlewState};
+pub use control::{AttitudeError, attitude_error};
+pub use dynamics::{RigidBodyState, propagate_torque_free, propagate_rigid_body, inertial_derivative,
+                   body_derivative};
+pub use orbital::OrbitalFrame;
+pub use pointing::{Pointing, align_axes, nadir_pointing, target_pointing};
+pub use transform_graph::TransformGraph;
+pub use conventions::{ned_to_enu, enu_to_ned, frd_to_flu, flu_to_frd, ned_frd_to_enu_flu, enu_flu_to_ned_frd,
+                      CoordinateSystem};
+pub use earth::{LocalLevel, ecef_to_local, EARTH_ROTATION_RATE, earth_rotation_angle, eci_to_ecef, ecef_to_eci};
+pub use camera::{opencv_to_opengl, opengl_to_opencv, Extrinsics};
+pub use symmetry::{SymmetryGroup, symmetric_representative, symmetric_distance};
+pub use crystal::{CrystalSymmetry, misorientation, reduce_to_fundamental_zone};
+pub use clustering::{Clustering, cluster_rotations};
+pub use histogram::OrientationHistogram;
+pub use matrix_fisher::MatrixFisher;
+pub use statistics::{RotationStatistics, rotation_statistics, RobustMean, robust_mean_rotation};
+#[cfg(feature = "tracing")]
+pub use trace::trace_rotation;
+
+// Test helpers; declared first so the macros are visible below.
+#[cfg(test)]
+#[macro_use]
+mod test_macros;
+
+// Instrumentation macros; also declared ahead of their users.
+#[macro_use]
+mod trace;
 
 // Modules
 mod constants;
+mod error;
+mod units;
 mod vector3d;
 mod rotation;
 mod orientation;
 mod quaternion;
+mod euler;
 mod rotation_matrix;
+mod any_rotation;
+mod compiled_rotation;
+mod cached_orientation;
+mod accumulator;
+mod matrix3;
+mod attitude_filter;
+mod complementary_filter;
+mod mekf;
+mod orientation_covariance;
+mod random;
+mod integration;
+mod imu;
+mod sequence;
+mod smoothing;
+mod buffer;
+mod resample;
+mod spline;
+mod evaluation;
+mod time_alignment;
+mod slew;
+mod control;
+mod dynamics;
+mod orbital;
+mod pointing;
+mod transform_graph;
+mod conventions;
+mod earth;
+mod camera;
+mod symmetry;
+mod crystal;
+mod clustering;
+mod histogram;
+mod matrix_fisher;
+mod statistics;
+mod parse;
+mod checked;
+mod trig;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "arbitrary")]
+mod fuzzing;
+#[cfg(feature = "approx")]
+mod approx_traits;
+
+// Plain-angle helpers, kept in their own namespace.
+pub mod angles;
+
+// Opt-in frame-tagged wrappers; not re-exported, since their names
+// mirror the untagged types.
+pub mod frames;
+
+// Property-testing strategies, behind the `proptest` feature.
+#[cfg(feature = "proptest")]
+pub mod strategies;
+
+// Serde impls and the lenient wrapper, behind the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod serialization;
+
+// Parallel batch operations, behind the `rayon` feature; their names
+// mirror the sequential functions.
+#[cfg(feature = "rayon")]
+pub mod parallel;
@@ -0,0 +1,445 @@
+use std::ops::{Add, Sub};
+use std::fmt;
+use crate::vector3d::Vector3d;
+use crate::constants::DBL_EPSILON;
+use crate::error::OrientationError;
+
+/// The most sweeps of Jacobi rotations used to diagonalize a symmetric
+/// matrix; convergence is quadratic, so a handful normally suffice.
+const MAX_JACOBI_SWEEPS: usize = 50;
+
+/// A general 3x3 matrix, used for covariances, Jacobians and tensors.
+#[derive(Copy, Clone, PartialEq)]
+pub struct Matrix3 {
+    /// The entries, stored row-major.
+    pub data: [[f64; 3]; 3]
+}
+
+impl Matrix3 {
+    /// The zero matrix.
+    pub const ZERO: Self = Self::new([[0.0; 3]; 3]);
+
+    /// The identity matrix.
+    pub const IDENTITY: Self = Self::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+
+    /// Create a new Matrix3 from row-major entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Matrix3;
+    /// let m = Matrix3::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+    /// ```
+    #[must_use]
+    pub const fn new(data: [[f64; 3]; 3]) -> Self {
+        Self { data }
+    }
+
+    /// Create a new Matrix3 from rows.
+    #[must_use]
+    pub const fn from_rows(rows: [Vector3d; 3]) -> Self {
+        Self::new([rows[0].data, rows[1].data, rows[2].data])
+    }
+
+    /// Create a new Matrix3 from columns.
+    #[must_use]
+    pub fn from_columns(columns: [Vector3d; 3]) -> Self {
+        Self::from_rows(columns).transpose()
+    }
+
+    /// Returns the zero matrix.
+    #[must_use]
+    pub const fn zero() -> Self {
+        Self::ZERO
+    }
+
+    /// Returns the identity matrix.
+    #[must_use]
+    pub const fn identity() -> Self {
+        Self::IDENTITY
+    }
+
+    /// Create a diagonal matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let m = Matrix3::from_diagonal(&Vector3d::new([1.0, 2.0, 3.0]));
+    /// assert_eq!(6.0, m.trace());
+    /// ```
+    #[must_use]
+    pub fn from_diagonal(diagonal: &Vector3d) -> Self {
+        let d = diagonal.data;
+        Self::new([
+            [d[0], 0.0, 0.0],
+            [0.0, d[1], 0.0],
+            [0.0, 0.0, d[2]]
+        ])
+    }
+
+    /// Create the skew-symmetric matrix `[v]x` such that
+    /// `[v]x * u = v.cross(&u)` for every vector `u`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let m = Matrix3::skew(&Vector3d::unit_x());
+    /// assert_eq!(Vector3d::unit_z(), m.multiply_vector(&Vector3d::unit_y()));
+    /// ```
+    #[must_use]
+    pub fn skew(v: &Vector3d) -> Self {
+        let [x, y, z] = v.data;
+        Self::new([
+            [0.0, -z, y],
+            [z, 0.0, -x],
+            [-y, x, 0.0]
+        ])
+    }
+
+    /// Get the rows.
+    #[must_use]
+    pub fn rows(&self) -> [Vector3d; 3] {
+        [
+            Vector3d::new(self.data[0]),
+            Vector3d::new(self.data[1]),
+            Vector3d::new(self.data[2])
+        ]
+    }
+
+    /// Get the columns.
+    #[must_use]
+    pub fn columns(&self) -> [Vector3d; 3] {
+        self.transpose().rows()
+    }
+
+    /// Transpose.
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let mut data = [[0.0; 3]; 3];
+        for (i, row) in data.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = self.data[j][i];
+            }
+        }
+        Self::new(data)
+    }
+
+    /// Sum of the diagonal entries.
+    #[must_use]
+    pub fn trace(&self) -> f64 {
+        self.data[0][0] + self.data[1][1] + self.data[2][2]
+    }
+
+    /// Determinant.
+    #[must_use]
+    pub fn determinant(&self) -> f64 {
+        let rows = self.rows();
+        rows[0].dot(&rows[1].cross(&rows[2]))
+    }
+
+    /// Whether every entry is finite, neither infinite nor NaN.
+    #[must_use]
+    pub fn is_finite(&self) -> bool {
+        self.data.iter().flatten().all(|x| x.is_finite())
+    }
+
+    /// Matrix product `self * other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let m = Matrix3::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+    /// assert_eq!(m, m.multiply(&Matrix3::identity()));
+    /// ```
+    #[must_use]
+    pub fn multiply(&self, other: &Self) -> Self {
+        let rows = self.rows();
+        let cols = other.columns();
+        let mut data = [[0.0; 3]; 3];
+        for (i, row) in data.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = rows[i].dot(&cols[j]);
+            }
+        }
+        Self::new(data)
+    }
+
+    /// Matrix-vector product `self * v`.
+    #[must_use]
+    pub fn multiply_vector(&self, v: &Vector3d) -> Vector3d {
+        let rows = self.rows();
+        Vector3d::new([rows[0].dot(v), rows[1].dot(v), rows[2].dot(v)])
+    }
+
+    /// Computes the scalar multiple of a matrix.
+    #[must_use]
+    pub fn scalar_multiple(&self, alpha: f64) -> Self {
+        let mut data = self.data;
+        for entry in data.iter_mut().flatten() {
+            *entry *= alpha;
+        }
+        Self::new(data)
+    }
+
+    /// The inverse of a matrix.
+    ///
+    /// # Errors
+    /// If the matrix is singular (determinant close to zero relative to
+    /// the lengths of its rows), the result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let m = Matrix3::from_diagonal(&Vector3d::new([1.0, 2.0, 4.0]));
+    /// let expected = Matrix3::from_diagonal(&Vector3d::new([1.0, 0.5, 0.25]));
+    /// assert_eq!(expected, m.inverse().unwrap());
+    /// ```
+    pub fn inverse(&self) -> Result<Self, OrientationError> {
+        // The determinant is at most the product of the row lengths, so
+        // comparing against that does not depend on the matrix's scale.
+        let rows = self.rows();
+        let det = self.determinant();
+        if det.abs() <= DBL_EPSILON * rows[0].norm() * rows[1].norm() * rows[2].norm() {
+            return Err(OrientationError::Singular)
+        }
+
+        // The columns of the inverse are the cross products of the
+        // rows, divided by the determinant.
+        let c1 = rows[1].cross(&rows[2]);
+        let c2 = rows[2].cross(&rows[0]);
+        let c3 = rows[0].cross(&rows[1]);
+        Ok(Self::from_columns([c1, c2, c3]).scalar_multiple(1.0 / det))
+    }
+
+    /// The Cholesky factor of a symmetric positive semi-definite
+    /// matrix: the lower-triangular `L` such that `L * L^T = self`.
+    /// Only the lower triangle of `self` is read.
+    ///
+    /// # Errors
+    /// If the matrix is not positive semi-definite, the result will be
+    /// an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let m = Matrix3::from_diagonal(&Vector3d::new([4.0, 9.0, 0.0]));
+    /// let expected = Matrix3::from_diagonal(&Vector3d::new([2.0, 3.0, 0.0]));
+    /// assert_eq!(expected, m.cholesky().unwrap());
+    /// ```
+    pub fn cholesky(&self) -> Result<Self, OrientationError> {
+        let a = self.data;
+        let mut l = [[0.0; 3]; 3];
+        for i in 0..3 {
+            for j in 0..=i {
+                let sum: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+                if i == j {
+                    let d = a[i][i] - sum;
+                    // Tolerate rounding in semi-definite matrices.
+                    if d < -DBL_EPSILON * a[i][i].abs().max(1.0) {
+                        return Err(OrientationError::NotPositiveSemiDefinite)
+                    }
+                    l[i][i] = d.max(0.0).sqrt();
+                } else if l[j][j] > 0.0 {
+                    l[i][j] = (a[i][j] - sum) / l[j][j];
+                } else if (a[i][j] - sum).abs() > DBL_EPSILON * a[i][i].abs().max(a[j][j].abs()).max(1.0) {
+                    // A zero pivot leaves nothing to cancel the rest of
+                    // its column, as in [[0, 1], [1, 0]].
+                    return Err(OrientationError::NotPositiveSemiDefinite)
+                }
+            }
+        }
+        Ok(Self::new(l))
+    }
+
+    /// The eigendecomposition of a symmetric matrix, found by cyclic
+    /// Jacobi rotations: the eigenvalues in decreasing order, and a
+    /// rotation whose columns are the corresponding unit eigenvectors.
+    /// Only the upper triangle of `self` is read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let m = Matrix3::new([[2.0, 1.0, 0.0], [1.0, 2.0, 0.0], [0.0, 0.0, 5.0]]);
+    /// let (values, vectors) = m.symmetric_eigen();
+    /// assert!((values - Vector3d::new([5.0, 3.0, 1.0])).norm() < 1.0e-12);
+    /// assert!((vectors.determinant() - 1.0).abs() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn symmetric_eigen(&self) -> (Vector3d, Self) {
+        let mut matrix = *self;
+        matrix.data[1][0] = matrix.data[0][1];
+        matrix.data[2][0] = matrix.data[0][2];
+        matrix.data[2][1] = matrix.data[1][2];
+        let mut vectors = Self::identity();
+        for _ in 0..MAX_JACOBI_SWEEPS {
+            let [[d0, a01, a02], [_, d1, a12], [_, _, d2]] = matrix.data;
+            let off = a01.abs() + a02.abs() + a12.abs();
+            if off == 0.0 || off <= DBL_EPSILON * (d0.abs() + d1.abs() + d2.abs()) {
+                break
+            }
+            for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+                let apq = matrix.data[p][q];
+                if apq == 0.0 {
+                    continue
+                }
+                // The rotation zeroing the (p, q) entry, computed stably
+                // from the tangent of its angle.
+                let theta = (matrix.data[q][q] - matrix.data[p][p]) / (2.0 * apq);
+                let tan = theta.signum() / (theta.abs() + theta.hypot(1.0));
+                let cos = 1.0 / tan.hypot(1.0);
+                let sin = tan * cos;
+                // For symmetric A, J^T A J is (A J)^T J.
+                matrix.rotate_columns(p, q, cos, sin);
+                matrix = matrix.transpose();
+                matrix.rotate_columns(p, q, cos, sin);
+                vectors.rotate_columns(p, q, cos, sin);
+            }
+        }
+
+        let values = [matrix.data[0][0], matrix.data[1][1], matrix.data[2][2]];
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| values[j].total_cmp(&values[i]));
+        let columns = vectors.columns();
+        let mut sorted = order.map(|i| columns[i]);
+        if sorted[0].cross(&sorted[1]).dot(&sorted[2]) < 0.0 {
+            sorted[2] = sorted[2].negate();
+        }
+        (Vector3d::new(order.map(|i| values[i])), Self::from_columns(sorted))
+    }
+
+    /// Replace columns `p` and `q` by their rotation through the angle
+    /// with the given cosine and sine.
+    fn rotate_columns(&mut self, p: usize, q: usize, cos: f64, sin: f64) {
+        for row in &mut self.data {
+            let (first, second) = (row[p], row[q]);
+            row[p] = cos * first - sin * second;
+            row[q] = sin * first + cos * second;
+        }
+    }
+}
+
+impl Add for Matrix3 {
+    type Output = Self;
+
+    /// Add two matrices.
+    fn add(self, other: Self) -> Self {
+        let mut data = self.data;
+        for (row, other_row) in data.iter_mut().zip(other.data.iter()) {
+            for (entry, other_entry) in row.iter_mut().zip(other_row.iter()) {
+                *entry += other_entry;
+            }
+        }
+        Self::new(data)
+    }
+}
+
+impl Sub for Matrix3 {
+    type Output = Self;
+
+    /// Subtract a matrix from another.
+    fn sub(self, other: Self) -> Self {
+        self + other.scalar_multiple(-1.0)
+    }
+}
+
+impl fmt::Debug for Matrix3 {
+    /// Pretty-print a matrix.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [r1, r2, r3] = self.data;
+        write!(f, "[[{} {} {}][{} {} {}][{} {} {}]]",
+               r1[0], r1[1], r1[2],
+               r2[0], r2[1], r2[2],
+               r3[0], r3[1], r3[2])
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transpose() {
+        let m = Matrix3::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        let expected = Matrix3::new([[1.0, 4.0, 7.0], [2.0, 5.0, 8.0], [3.0, 6.0, 9.0]]);
+        assert_eq!(expected, m.transpose());
+    }
+
+    #[test]
+    fn multiply() {
+        let a = Matrix3::new([[1.0, 2.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 2.0]]);
+        let b = Matrix3::new([[1.0, 0.0, 0.0], [3.0, 1.0, 0.0], [0.0, 1.0, 1.0]]);
+        let expected = Matrix3::new([[7.0, 2.0, 0.0], [3.0, 1.0, 0.0], [0.0, 2.0, 2.0]]);
+        assert_eq!(expected, a.multiply(&b));
+    }
+
+    #[test]
+    fn skew_matches_cross() {
+        let v = Vector3d::new([1.0, 2.0, 3.0]);
+        let u = Vector3d::new([4.0, 5.0, 6.0]);
+        assert_eq!(v.cross(&u), Matrix3::skew(&v).multiply_vector(&u));
+    }
+
+    #[test]
+    fn determinant() {
+        let m = Matrix3::new([[2.0, 0.0, 1.0], [1.0, 3.0, 2.0], [1.0, 1.0, 2.0]]);
+        assert_eq!(6.0, m.determinant());
+    }
+
+    #[test]
+    fn inverse() {
+        let m = Matrix3::new([[2.0, 0.0, 1.0], [1.0, 3.0, 2.0], [1.0, 1.0, 2.0]]);
+        assert_matrix_approx_eq!(Matrix3::identity(), m.multiply(&m.inverse().unwrap()));
+    }
+
+    #[test]
+    fn cholesky() {
+        let m = Matrix3::new([[4.0, 2.0, 0.4], [2.0, 5.0, 1.0], [0.4, 1.0, 3.0]]);
+        let l = m.cholesky().unwrap();
+        assert_eq!(0.0, l.data[0][1]);
+        assert_matrix_approx_eq!(m, l.multiply(&l.transpose()), 1.0e-12);
+    }
+
+    #[test]
+    fn symmetric_eigen() {
+        let m = Matrix3::new([[4.0, 2.0, 0.4], [2.0, -5.0, 1.0], [0.4, 1.0, 3.0]]);
+        let (values, vectors) = m.symmetric_eigen();
+        assert!(values.data[0] >= values.data[1] && values.data[1] >= values.data[2]);
+        assert_matrix_approx_eq!(Matrix3::identity(), vectors.transpose().multiply(&vectors), 1.0e-12);
+        let reconstructed = vectors.multiply(&Matrix3::from_diagonal(&values)).multiply(&vectors.transpose());
+        assert_matrix_approx_eq!(m, reconstructed, 1.0e-12);
+    }
+
+    #[test]
+    fn indefinite_cholesky() {
+        let m = Matrix3::from_diagonal(&Vector3d::new([1.0, -1.0, 1.0]));
+        assert!(m.cholesky().is_err(), "Should not be able to factor an indefinite matrix");
+        // The zero diagonal hides the negative eigenvalue from the pivots.
+        let m = Matrix3::new([[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert!(m.cholesky().is_err(), "Should not be able to factor an indefinite matrix");
+        // A zero pivot with a zero column is still semi-definite.
+        let m = Matrix3::new([[0.0, 0.0, 0.0], [0.0, 4.0, 2.0], [0.0, 2.0, 2.0]]);
+        let l = m.cholesky().unwrap();
+        assert_matrix_approx_eq!(m, l.multiply(&l.transpose()), 1.0e-12);
+    }
+
+    #[test]
+    fn singular_inverse() {
+        let m = Matrix3::new([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [1.0, 1.0, 1.0]]);
+        assert!(m.inverse().is_err(), "Should not be able to invert a singular matrix");
+        assert!(Matrix3::zero().inverse().is_err(), "Should not be able to invert a singular matrix");
+    }
+
+    #[test]
+    fn small_inverse() {
+        let m = Matrix3::new([[2.0, 0.0, 1.0], [1.0, 3.0, 2.0], [1.0, 1.0, 2.0]]).scalar_multiple(1.0e-7);
+        assert_matrix_approx_eq!(Matrix3::identity(), m.multiply(&m.inverse().unwrap()), 1.0e-12);
+    }
+}
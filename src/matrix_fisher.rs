@@ -0,0 +1,453 @@
+use rand::Rng;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::matrix3::Matrix3;
+use crate::random::standard_normal;
+use crate::error::OrientationError;
+
+/// The number of Simpson intervals used for the normalizing constant.
+const QUADRATURE_INTERVALS: usize = 2000;
+
+/// Beyond this many multiples of the width of the integrand's peak,
+/// the integrand of the normalizing constant is negligible.
+const QUADRATURE_SPAN: f64 = 40.0;
+
+/// The largest number of Newton iterations used when fitting.
+const MAX_FIT_ITERATIONS: usize = 100;
+
+/// The matrix Fisher distribution on rotations, with density
+/// `exp(tr(F^T R)) / c(F)` relative to the uniform distribution, where
+/// `R` is the rotation matrix and `F` the parameter matrix.
+///
+/// Writing `F = U S V^T` with `U` and `V` rotations and `S` diagonal,
+/// the mode is `U V^T`, and the entries of `S` set how concentrated the
+/// distribution is about each axis: for large values it is close to a
+/// Gaussian in the body-frame tangent space, with variance
+/// `1 / (s_j + s_k)` about axis `i` of `V`. Unlike a tangent-space
+/// Gaussian it is defined on the whole of SO(3), so it also describes
+/// broad and multimodal-looking uncertainty consistently, and its
+/// parameters can be estimated in closed form up to a three-variable
+/// root-find.
+///
+/// The normalizing constant is evaluated with the one-dimensional
+/// integral of Wood (1993) and Lee (2018), using polynomial
+/// approximations to the Bessel functions with a relative accuracy of
+/// about `1e-7`.
+#[derive(Copy, Clone, Debug)]
+pub struct MatrixFisher {
+    parameter: Matrix3,
+    left: Quaternion,
+    concentration: Vector3d,
+    right: Quaternion,
+    log_normalizer: f64
+}
+
+impl MatrixFisher {
+    /// Create a distribution from its parameter matrix `F`.
+    #[must_use]
+    pub fn new(parameter: Matrix3) -> Self {
+        let (left, concentration, right) = proper_svd(&parameter);
+        let [s1, s2, s3] = concentration.data;
+        Self {
+            parameter,
+            left: left.as_quaternion(),
+            concentration,
+            right: right.as_quaternion(),
+            log_normalizer: s1 + s2 + s3 + normalizing_integrals(&concentration)[0].ln()
+        }
+    }
+
+    /// The distribution with the given mode and concentrations about
+    /// the body axes, `F = mode * diag(concentration)`. The
+    /// concentrations should be non-negative; larger is tighter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mode = Quaternion::from_angle_axis(0.5, &Vector3d::unit_z());
+    /// let distribution = MatrixFisher::from_mode(&mode, &Vector3d::new([50.0, 50.0, 50.0]));
+    /// assert!(distribution.mode().angle_to(&mode) < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn from_mode(mode: &Quaternion, concentration: &Vector3d) -> Self {
+        let parameter = mode.as_rotation_matrix().as_matrix3().multiply(&Matrix3::from_diagonal(concentration));
+        Self::new(parameter)
+    }
+
+    /// Estimate the distribution from samples by maximum likelihood.
+    /// The mode is the rotation nearest the sample mean of the rotation
+    /// matrices, and the concentrations are those whose expected
+    /// rotation matrix matches the sample mean.
+    ///
+    /// # Errors
+    /// Returns an error if there are no samples, or if they are so
+    /// tightly clustered that the concentrations cannot be resolved, as
+    /// when every sample is the same rotation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let truth = MatrixFisher::from_mode(&Quaternion::identity(), &Vector3d::new([20.0, 10.0, 5.0]));
+    /// let samples = truth.samples(&mut rand::thread_rng(), 5000);
+    /// let fitted = MatrixFisher::fit(&samples).unwrap();
+    /// assert!(fitted.mode().angle_to(&Quaternion::identity()) < 0.05);
+    /// ```
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fit(samples: &[Quaternion]) -> Result<Self, OrientationError> {
+        if samples.is_empty() {
+            return Err(OrientationError::InsufficientData("cannot fit a distribution to no samples"))
+        }
+        enter_span!(DEBUG, "matrix_fisher_fit", count = samples.len());
+        let n = samples.len() as f64;
+        let mean = samples.iter()
+            .fold(Matrix3::zero(), |sum, q| sum + q.as_rotation_matrix().as_matrix3())
+            .scalar_multiple(1.0 / n);
+        let (left, moments, right) = proper_svd(&mean);
+        if moments.data.iter().any(|d| 1.0 - d < 1.0e-9) {
+            return Err(OrientationError::InsufficientData("samples are too concentrated to estimate a distribution"))
+        }
+        let concentration = solve_concentration(&moments)?;
+        let parameter = left.as_matrix3()
+            .multiply(&Matrix3::from_diagonal(&concentration))
+            .multiply(&right.as_matrix3().transpose());
+        Ok(Self::new(parameter))
+    }
+
+    /// The parameter matrix `F`.
+    #[must_use]
+    pub fn parameter(&self) -> Matrix3 {
+        self.parameter
+    }
+
+    /// The proper singular values of `F`: non-negative except possibly
+    /// the last, which is smallest in magnitude.
+    #[must_use]
+    pub fn concentration(&self) -> Vector3d {
+        self.concentration
+    }
+
+    /// The most probable rotation.
+    #[must_use]
+    pub fn mode(&self) -> Quaternion {
+        self.left.multiply(&self.right.inverse_unchecked())
+    }
+
+    /// The natural logarithm of the density at a rotation, relative to
+    /// the uniform distribution on rotations.
+    #[must_use]
+    pub fn log_density(&self, rotation: &Quaternion) -> f64 {
+        let r = rotation.as_rotation_matrix().as_matrix3();
+        let exponent: f64 = self.parameter.data.iter()
+            .flatten()
+            .zip(r.data.iter().flatten())
+            .map(|(f, r)| f * r)
+            .sum();
+        exponent - self.log_normalizer
+    }
+
+    /// The density at a rotation, relative to the uniform distribution
+    /// on rotations, so the uniform distribution has density one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let uniform = MatrixFisher::new(Matrix3::zero());
+    /// assert!((uniform.density(&Quaternion::identity()) - 1.0).abs() < 1.0e-6);
+    /// ```
+    #[must_use]
+    pub fn density(&self, rotation: &Quaternion) -> f64 {
+        self.log_density(rotation).exp()
+    }
+
+    /// The expected rotation matrix.
+    #[must_use]
+    pub fn mean_matrix(&self) -> Matrix3 {
+        let moments = expected_diagonal(&self.concentration);
+        self.left.as_rotation_matrix().as_matrix3()
+            .multiply(&Matrix3::from_diagonal(&moments))
+            .multiply(&self.right.as_rotation_matrix().as_matrix3().transpose())
+    }
+
+    /// Draw a random rotation.
+    ///
+    /// The distribution is a Bingham distribution on unit quaternions,
+    /// sampled exactly by rejection from an angular central Gaussian
+    /// envelope (Kent, Ganeiber and Mardia, 2013), whose acceptance rate
+    /// does not degrade as the distribution concentrates.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Quaternion {
+        let [s1, s2, s3] = self.concentration.data;
+        // exp(tr(S R(q))) is exp(q^T B q) for a diagonal B; shifting by
+        // its largest entry gives exp(-q^T A q) with A >= 0.
+        let a = [0.0, 2.0 * (s2 + s3), 2.0 * (s1 + s3), 2.0 * (s1 + s2)].map(|x: f64| x.max(0.0));
+        let b = envelope_parameter(&a);
+        let omega = a.map(|x| 1.0 + 2.0 * x / b);
+        let log_bound = -(4.0 - b) / 2.0 + 2.0 * (4.0 / b).ln();
+        let x = loop {
+            let y = omega.map(|w| standard_normal(rng) / w.sqrt());
+            let norm = y.iter().map(|v| v * v).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue
+            }
+            let x = y.map(|v| v / norm);
+            let quadratic = |m: &[f64; 4]| m.iter().zip(x.iter()).map(|(m, v)| m * v * v).sum::<f64>();
+            let log_ratio = -quadratic(&a) + 2.0 * quadratic(&omega).ln() - log_bound;
+            if rng.gen::<f64>().ln() <= log_ratio {
+                break x
+            }
+        };
+        let q = Quaternion::new(x[0], Vector3d::new([x[1], x[2], x[3]]));
+        self.left.multiply(&q).multiply(&self.right.inverse_unchecked())
+    }
+
+    /// Draw `n` random rotations as in `sample`.
+    pub fn samples<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<Quaternion> {
+        (0..n).map(|_| self.sample(rng)).collect()
+    }
+}
+
+/// The proper singular value decomposition `m = U diag(s) V^T`, with
+/// `U` and `V` rotations and `s1 >= s2 >= |s3|`.
+pub(crate) fn proper_svd(m: &Matrix3) -> (RotationMatrix, Vector3d, RotationMatrix) {
+    let (_, v) = m.transpose().multiply(m).symmetric_eigen();
+    let [v1, v2, v3] = v.columns();
+    let u1 = m.multiply_vector(&v1).normalized().unwrap_or_else(|_| Vector3d::unit_x());
+    let u2 = {
+        let w = m.multiply_vector(&v2);
+        let w = w - u1.scalar_multiple(u1.dot(&w));
+        w.normalized().unwrap_or_else(|_| u1.any_orthonormal_pair().map_or(Vector3d::unit_y(), |(u, _)| u))
+    };
+    let u3 = u1.cross(&u2);
+    let s = Vector3d::new([
+        u1.dot(&m.multiply_vector(&v1)),
+        u2.dot(&m.multiply_vector(&v2)),
+        u3.dot(&m.multiply_vector(&v3))
+    ]);
+    (RotationMatrix::from_columns([u1, u2, u3]), s, RotationMatrix::from_columns([v1, v2, v3]))
+}
+
+/// The scaled normalizing constant `c(S) exp(-(s1 + s2 + s3))`, followed
+/// by its derivatives with respect to each concentration, for proper
+/// singular values `S`.
+///
+/// With `t = 1 - u`, the constant is the integral over `u` in `[-1, 1]`
+/// of `I0((s1 - s2)(1 - u) / 2) I0((s1 + s2)(1 + u) / 2) exp(s3 u) / 2`.
+/// After scaling, the integrand decays as `exp(-(s2 + s3) t)`, and it
+/// is integrated only where it is not negligible.
+fn normalizing_integrals(s: &Vector3d) -> [f64; 4] {
+    let [s1, s2, s3] = s.data;
+    let decay = (s2 + s3).max(0.0);
+    let span = if decay * 2.0 > QUADRATURE_SPAN { QUADRATURE_SPAN / decay } else { 2.0 };
+    let integrand = |t: f64| {
+        let (a, b) = ((s1 - s2) * t / 2.0, (s1 + s2) * (2.0 - t) / 2.0);
+        let (i0a, i1a, i0b, i1b) = (bessel_i0e(a), bessel_i1e(a), bessel_i0e(b), bessel_i1e(b));
+        let w = 0.5 * (-decay * t).exp();
+        let (da, db) = (i1a * i0b * t / 2.0, i0a * i1b * (2.0 - t) / 2.0);
+        [w * i0a * i0b, w * (da + db), w * (db - da), w * i0a * i0b * (1.0 - t)]
+    };
+    simpson(integrand, span)
+}
+
+/// The composite Simpson rule over `[0, span]` for a vector integrand.
+#[allow(clippy::cast_precision_loss)]
+fn simpson<F: Fn(f64) -> [f64; 4]>(f: F, span: f64) -> [f64; 4] {
+    let h = span / QUADRATURE_INTERVALS as f64;
+    let mut sum = [0.0; 4];
+    for i in 0..=QUADRATURE_INTERVALS {
+        let weight = if i == 0 || i == QUADRATURE_INTERVALS { 1.0 } else if i % 2 == 1 { 4.0 } else { 2.0 };
+        for (total, value) in sum.iter_mut().zip(f(i as f64 * h).iter()) {
+            *total += weight * value;
+        }
+    }
+    sum.map(|total| total * h / 3.0)
+}
+
+/// The diagonal of the expected rotation matrix for proper singular
+/// values `S`: the gradient of `ln c(S)`.
+fn expected_diagonal(s: &Vector3d) -> Vector3d {
+    let [c, d1, d2, d3] = normalizing_integrals(s);
+    Vector3d::new([d1 / c, d2 / c, d3 / c])
+}
+
+/// The proper singular values whose expected rotation matrix has the
+/// given diagonal, found by damped Newton iteration.
+fn solve_concentration(moments: &Vector3d) -> Result<Vector3d, OrientationError> {
+    // Start from the concentrated approximation, in which 1 - d_i is
+    // about 1 / (2 s) for equal concentrations.
+    let mut s = moments.data.map(|d| 0.5 / (1.0 - d).max(1.0e-9));
+    s[2] = s[2].min(s[1]);
+    let residual = |s: &[f64; 3]| expected_diagonal(&Vector3d::new(*s)) - *moments;
+    let mut r = residual(&s);
+    for _ in 0..MAX_FIT_ITERATIONS {
+        emit_event!(TRACE, residual = r.norm(), "concentration solver iteration");
+        if r.norm() < 1.0e-10 {
+            break
+        }
+        let mut columns = [Vector3d::zero(); 3];
+        for (i, column) in columns.iter_mut().enumerate() {
+            let h = 1.0e-4 * (1.0 + s[i].abs());
+            let mut shifted = s;
+            shifted[i] += h;
+            *column = (residual(&shifted) - r).scalar_multiple(1.0 / h);
+        }
+        // The moments flatten out as the concentrations grow; scale the
+        // Jacobian to keep it invertible.
+        let jacobian = Matrix3::from_columns(columns);
+        let scale = jacobian.data.iter().flatten().fold(0.0, |m: f64, x| m.max(x.abs()));
+        if scale == 0.0 {
+            return Err(OrientationError::InsufficientData("samples do not determine the concentrations"))
+        }
+        let step = jacobian.scalar_multiple(1.0 / scale).inverse()?.multiply_vector(&r.scalar_multiple(1.0 / scale));
+        // Halve the step until it reduces the residual.
+        let mut scale = 1.0;
+        loop {
+            let candidate = [0, 1, 2].map(|i| s[i] - scale * step.data[i]);
+            let candidate_r = residual(&candidate);
+            if candidate_r.norm() < r.norm() || scale < 1.0e-6 {
+                s = candidate;
+                r = candidate_r;
+                break
+            }
+            scale /= 2.0;
+        }
+    }
+    Ok(Vector3d::new(s))
+}
+
+/// The exponentially scaled modified Bessel function `I0(x) exp(-x)`,
+/// for `x >= 0` (Abramowitz and Stegun 9.8.1 and 9.8.2).
+fn bessel_i0e(x: f64) -> f64 {
+    if x < 3.75 {
+        let t = (x / 3.75).powi(2);
+        let i0 = 1.0 + t * (3.515_622_9 + t * (3.089_942_4 + t * (1.206_749_2
+            + t * (0.265_973_2 + t * (0.036_076_8 + t * 0.004_581_3)))));
+        i0 * (-x).exp()
+    } else {
+        let t = 3.75 / x;
+        (0.398_942_28 + t * (0.013_285_92 + t * (0.002_253_19 + t * (-0.001_575_65
+            + t * (0.009_162_81 + t * (-0.020_577_06 + t * (0.026_355_37
+            + t * (-0.016_476_33 + t * 0.003_923_77)))))))) / x.sqrt()
+    }
+}
+
+/// The exponentially scaled modified Bessel function `I1(x) exp(-x)`,
+/// for `x >= 0` (Abramowitz and Stegun 9.8.3 and 9.8.4).
+fn bessel_i1e(x: f64) -> f64 {
+    if x < 3.75 {
+        let t = (x / 3.75).powi(2);
+        let i1 = x * (0.5 + t * (0.878_905_94 + t * (0.514_988_69 + t * (0.150_849_34
+            + t * (0.026_587_33 + t * (0.003_015_32 + t * 0.000_324_11))))));
+        i1 * (-x).exp()
+    } else {
+        let t = 3.75 / x;
+        (0.398_942_28 + t * (-0.039_880_24 + t * (-0.003_620_18 + t * (0.001_638_01
+            + t * (-0.010_315_55 + t * (0.022_829_67 + t * (-0.028_953_12
+            + t * (0.017_876_54 - t * 0.004_200_59)))))))) / x.sqrt()
+    }
+}
+
+/// The parameter `b` of the angular central Gaussian envelope for the
+/// Bingham density `exp(-x^T A x)` on the 3-sphere: the root of
+/// `sum 1 / (b + 2 a_i) = 1` in `(0, 4]`, found by bisection.
+fn envelope_parameter(a: &[f64; 4]) -> f64 {
+    let f = |b: f64| a.iter().map(|x| 1.0 / (b + 2.0 * x)).sum::<f64>() - 1.0;
+    let (mut low, mut high) = (0.0, 4.0);
+    for _ in 0..100 {
+        let mid = 0.5 * (low + high);
+        if f(mid) > 0.0 { low = mid } else { high = mid }
+    }
+    high
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn uniform_limit() {
+        let integrals = normalizing_integrals(&Vector3d::zero());
+        assert_float_approx_eq!(1.0, integrals[0], 1.0e-9);
+        for d in &integrals[1..] {
+            assert_float_approx_eq!(0.0, *d, 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn proper_svd_reconstructs() {
+        let m = Matrix3::new([[0.3, -1.0, 0.2], [0.5, 0.1, -0.7], [-0.2, 0.4, -0.1]]);
+        let (u, s, v) = proper_svd(&m);
+        let reconstructed = u.as_matrix3()
+            .multiply(&Matrix3::from_diagonal(&s))
+            .multiply(&v.as_matrix3().transpose());
+        assert_matrix_approx_eq!(m, reconstructed, 1.0e-12);
+        assert!(s.data[0] >= s.data[1] && s.data[1] >= s.data[2].abs());
+        // This matrix has a negative determinant.
+        assert!(s.data[2] < 0.0);
+    }
+
+    #[test]
+    fn density_integrates_to_one() {
+        let mut rng = StdRng::seed_from_u64(14);
+        let mode = Quaternion::from_angle_axis(0.8, &Vector3d::new([1.0, 0.2, -0.5]));
+        let distribution = MatrixFisher::from_mode(&mode, &Vector3d::new([3.0, 1.5, 0.5]));
+        let n = 40000;
+        let mut mean = 0.0;
+        for _ in 0..n {
+            let q = Quaternion::random_within_angle(&mut rng, &mode, std::f64::consts::PI).unwrap();
+            mean += distribution.density(&q) / f64::from(n);
+        }
+        assert_float_approx_eq!(1.0, mean, 0.03);
+        assert!(distribution.density(&mode) > distribution.density(&Quaternion::identity()));
+    }
+
+    #[test]
+    fn samples_match_moments_and_fit() {
+        let mut rng = StdRng::seed_from_u64(15);
+        let mode = Quaternion::from_angle_axis(-1.2, &Vector3d::new([0.3, 1.0, 0.4]));
+        let concentration = Vector3d::new([12.0, 6.0, 2.0]);
+        let distribution = MatrixFisher::from_mode(&mode, &concentration);
+        let samples = distribution.samples(&mut rng, 20000);
+
+        let mean = samples.iter()
+            .fold(Matrix3::zero(), |sum, q| sum + q.as_rotation_matrix().as_matrix3())
+            .scalar_multiple(1.0 / 20000.0);
+        assert_matrix_approx_eq!(distribution.mean_matrix(), mean, 0.01);
+
+        let fitted = MatrixFisher::fit(&samples).unwrap();
+        assert!(fitted.mode().angle_to(&mode) < 0.02);
+        for (estimate, truth) in fitted.concentration().data.iter().zip(concentration.data.iter()) {
+            assert!((estimate - truth).abs() < 0.1 * truth);
+        }
+    }
+
+    #[test]
+    fn concentrated_is_nearly_gaussian() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let s = 500.0;
+        let distribution = MatrixFisher::from_mode(&Quaternion::identity(), &Vector3d::new([s, s, s]));
+        let n = 10000;
+        let mut mean_square = 0.0;
+        for q in distribution.samples(&mut rng, n) {
+            mean_square += q.rotation_vector().norm_squared() / 10000.0;
+        }
+        // Each tangent component has variance 1 / (2 s).
+        assert_float_approx_eq!(3.0 / (2.0 * s), mean_square, 1.0e-4);
+        let fitted = MatrixFisher::fit(&distribution.samples(&mut rng, n)).unwrap();
+        for estimate in &fitted.concentration().data {
+            assert!((estimate - s).abs() < 0.05 * s);
+        }
+    }
+
+    #[test]
+    fn degenerate_fits() {
+        assert!(MatrixFisher::fit(&[]).is_err());
+        assert!(MatrixFisher::fit(&[Quaternion::identity(); 3]).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::matrix3::Matrix3;
+use crate::attitude_filter::AttitudeFilter;
+use crate::error::OrientationError;
+
+/// The manifold-specific parts of a multiplicative extended Kalman
+/// filter (MEKF) for attitude.
+///
+/// The filter tracks a reference attitude `q` together with the
+/// covariance of a three-dimensional error state `delta`. The error
+/// state is a rotation vector expressed in the body frame, so that the
+/// true attitude is `q * exp(delta)`, i.e. `delta` is applied before
+/// `q`. Between measurements the error state is zero: each correction
+/// is injected into `q` and the error state reset.
+///
+/// Attitudes rotate body-frame vectors into the world frame, and
+/// angular velocities are expressed in the body frame.
+#[derive(Copy, Clone, Debug)]
+pub struct Mekf {
+    attitude: Quaternion,
+    covariance: Matrix3,
+    gyro_noise_density: f64
+}
+
+impl Mekf {
+    /// Create a new filter from an initial attitude and error-state
+    /// covariance (in radians squared).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let p = Matrix3::identity().scalar_multiple(0.01);
+    /// let filter = Mekf::new(Quaternion::identity(), p);
+    /// ```
+    #[must_use]
+    pub fn new(attitude: Quaternion, covariance: Matrix3) -> Self {
+        Self {
+            attitude,
+            covariance,
+            gyro_noise_density: 0.0
+        }
+    }
+
+    /// Set the gyro noise density used by `AttitudeFilter::predict`, in
+    /// radians squared per second. The default is zero.
+    #[must_use]
+    pub fn with_gyro_noise_density(mut self, gyro_noise_density: f64) -> Self {
+        self.gyro_noise_density = gyro_noise_density;
+        self
+    }
+
+    /// The current attitude estimate.
+    #[must_use]
+    pub fn attitude(&self) -> Quaternion {
+        self.attitude
+    }
+
+    /// The current error-state covariance.
+    #[must_use]
+    pub fn covariance(&self) -> Matrix3 {
+        self.covariance
+    }
+
+    /// The error-state transition matrix over a step of `dt` seconds
+    /// with constant body-frame angular velocity `gyro`. This is the
+    /// transpose of the rotation matrix of `exp(gyro * dt)`.
+    #[must_use]
+    pub fn transition_matrix(gyro: &Vector3d, dt: f64) -> Matrix3 {
+        Quaternion::from_rotation_vector(&gyro.scalar_multiple(-dt))
+            .as_rotation_matrix()
+            .as_matrix3()
+    }
+
+    /// Propagate the attitude and covariance with a body-frame angular
+    /// velocity (radians per second) held constant over `dt` seconds.
+    /// `gyro_noise_density` is the variance of the gyro white noise, in
+    /// radians squared per second.
+    pub fn propagate(&mut self, gyro: &Vector3d, gyro_noise_density: f64, dt: f64) {
+        self.attitude = self.attitude.integrate(gyro, dt);
+        if let Ok(q) = self.attitude.normalized() {
+            self.attitude = q;
+        }
+
+        let phi = Self::transition_matrix(gyro, dt);
+        let q = Matrix3::identity().scalar_multiple(gyro_noise_density * dt);
+        self.covariance = phi.multiply(&self.covariance).multiply(&phi.transpose()) + q;
+    }
+
+    /// For a known world-frame reference direction, the body-frame
+    /// observation predicted by the current attitude, together with the
+    /// Jacobian of the observation with respect to the error state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let filter = Mekf::new(Quaternion::identity(), Matrix3::identity());
+    /// let (predicted, h) = filter.vector_measurement(&Vector3d::unit_z());
+    /// assert_eq!(Vector3d::unit_z(), predicted);
+    /// assert_eq!(Matrix3::skew(&Vector3d::unit_z()), h);
+    /// ```
+    #[must_use]
+    pub fn vector_measurement(&self, reference: &Vector3d) -> (Vector3d, Matrix3) {
+        let predicted = self.attitude.rotate_vector_inverse(reference);
+        (predicted, Matrix3::skew(&predicted))
+    }
+
+    /// Correct the attitude with a body-frame observation of a known
+    /// world-frame reference direction, with isotropic measurement
+    /// noise variance `noise_variance`. The resulting error state is
+    /// injected into the attitude and reset.
+    ///
+    /// # Errors
+    /// Returns an error if the innovation covariance is singular, for
+    /// example when both the covariance and the measurement noise are
+    /// zero.
+    pub fn correct_vector(&mut self, observed: &Vector3d, reference: &Vector3d,
+                          noise_variance: f64) -> Result<(), OrientationError> {
+        enter_span!(TRACE, "mekf_correct_vector", noise_variance);
+        let (predicted, h) = self.vector_measurement(reference);
+        let p = self.covariance;
+        let s = h.multiply(&p).multiply(&h.transpose())
+            + Matrix3::identity().scalar_multiple(noise_variance);
+        let gain = p.multiply(&h.transpose()).multiply(&s.inverse()?);
+
+        let delta = gain.multiply_vector(&(*observed - predicted));
+        emit_event!(TRACE, innovation = (*observed - predicted).norm(), correction = delta.norm(), "mekf correction");
+        let covariance = (Matrix3::identity() - gain.multiply(&h)).multiply(&p);
+
+        // Keep the covariance symmetric in the face of rounding.
+        self.covariance = (covariance + covariance.transpose()).scalar_multiple(0.5);
+        self.inject(&delta);
+        Ok(())
+    }
+
+    /// Inject an error-state estimate into the attitude and reset the
+    /// error state to zero, transforming the covariance to match.
+    pub fn inject(&mut self, delta: &Vector3d) {
+        let correction = Quaternion::from_rotation_vector(delta);
+        self.attitude = correction.before(&self.attitude);
+        if let Ok(q) = self.attitude.normalized() {
+            self.attitude = q;
+        }
+
+        // First-order reset Jacobian for a body-frame error state.
+        let g = Matrix3::identity() - Matrix3::skew(&delta.scalar_multiple(0.5));
+        self.covariance = g.multiply(&self.covariance).multiply(&g.transpose());
+    }
+}
+
+impl AttitudeFilter for Mekf {
+    fn predict(&mut self, gyro: &Vector3d, dt: f64) {
+        self.propagate(gyro, self.gyro_noise_density, dt);
+    }
+
+    fn correct_vector(&mut self, observed: &Vector3d, reference: &Vector3d,
+                      noise_variance: f64) -> Result<(), OrientationError> {
+        Mekf::correct_vector(self, observed, reference, noise_variance)
+    }
+
+    fn estimate(&self) -> Quaternion {
+        self.attitude
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn propagate_integrates_attitude() {
+        let mut filter = Mekf::new(Quaternion::identity(), Matrix3::zero());
+        let gyro = Vector3d::unit_x().scalar_multiple(PI / 2.0);
+        for _ in 0..100 {
+            filter.propagate(&gyro, 0.0, 0.01);
+        }
+        let expected = Quaternion::from_angle_axis(PI / 2.0, &Vector3d::unit_x());
+        assert_quat_approx_eq!(expected, filter.attitude());
+    }
+
+    #[test]
+    fn propagate_rotates_covariance() {
+        let p = Matrix3::from_diagonal(&Vector3d::new([1.0, 2.0, 3.0]));
+        let mut filter = Mekf::new(Quaternion::identity(), p);
+        filter.propagate(&Vector3d::unit_z(), 0.0, PI / 2.0);
+
+        // A quarter turn about z swaps the x and y variances.
+        let expected = Matrix3::from_diagonal(&Vector3d::new([2.0, 1.0, 3.0]));
+        assert_matrix_approx_eq!(expected, filter.covariance());
+    }
+
+    #[test]
+    fn propagate_adds_process_noise() {
+        let mut filter = Mekf::new(Quaternion::identity(), Matrix3::zero());
+        filter.propagate(&Vector3d::zero(), 0.5, 2.0);
+        assert_matrix_approx_eq!(Matrix3::identity(), filter.covariance());
+    }
+
+    #[test]
+    fn vector_measurement_jacobian() {
+        let q = Quaternion::from_angle_axis(0.4, &Vector3d::new([1.0, -2.0, 0.5]));
+        let filter = Mekf::new(q, Matrix3::identity());
+        let reference = Vector3d::new([0.3, 0.2, 0.9]);
+        let (predicted, h) = filter.vector_measurement(&reference);
+
+        // Compare against a finite difference of the measurement model.
+        let delta = Vector3d::new([1.0e-6, -2.0e-6, 1.5e-6]);
+        let perturbed = Quaternion::from_rotation_vector(&delta).before(&q);
+        let observed = perturbed.inverse_unchecked().rotate_vector(&reference);
+        assert_vector_approx_eq!(observed - predicted, h.multiply_vector(&delta), 1.0e-11);
+    }
+
+    #[test]
+    fn gravity_corrects_tilt_only() {
+        let truth = Quaternion::from_angle_axis(0.02, &Vector3d::unit_x());
+        let p = Matrix3::identity().scalar_multiple(0.1);
+        let mut filter = Mekf::new(Quaternion::identity(), p);
+        let down = Vector3d::unit_z().negate();
+        let observed = truth.inverse_unchecked().rotate_vector(&down);
+        for _ in 0..50 {
+            filter.correct_vector(&observed, &down, 1.0e-4).unwrap();
+        }
+
+        assert_quat_approx_eq!(truth, filter.attitude(), 1.0e-4);
+        // Rotation about the observed direction is unobservable, so its
+        // variance stays put while the total collapses to it.
+        let c = filter.covariance();
+        let unobservable = observed.dot(&c.multiply_vector(&observed));
+        assert!(unobservable > 0.09, "Yaw is unobservable from gravity alone");
+        assert!(c.trace() - unobservable < 1.0e-3);
+    }
+
+    #[test]
+    fn small_covariances() {
+        // An accurate filter has a tiny innovation covariance, which is
+        // still far from singular.
+        let truth = Quaternion::from_angle_axis(1.0e-3, &Vector3d::unit_x());
+        let p = Matrix3::identity().scalar_multiple(1.0e-6);
+        let mut filter = Mekf::new(Quaternion::identity(), p);
+        let down = Vector3d::unit_z().negate();
+        let observed = truth.inverse_unchecked().rotate_vector(&down);
+        filter.correct_vector(&observed, &down, 1.0e-6).unwrap();
+        assert_quat_approx_eq!(Quaternion::from_angle_axis(5.0e-4, &Vector3d::unit_x()), filter.attitude(), 1.0e-6);
+    }
+
+    #[test]
+    fn predict_uses_gyro_noise_density() {
+        let mut filter = Mekf::new(Quaternion::identity(), Matrix3::zero())
+            .with_gyro_noise_density(0.5);
+        filter.predict(&Vector3d::zero(), 2.0);
+        assert_matrix_approx_eq!(Matrix3::identity(), filter.covariance());
+    }
+}
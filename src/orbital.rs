@@ -0,0 +1,113 @@
+use crate::vector3d::Vector3d;
+use crate::rotation_matrix::RotationMatrix;
+use crate::error::OrientationError;
+
+/// Axis conventions for a frame attached to an orbiting body, defined
+/// by its position and velocity relative to the central body.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OrbitalFrame {
+    /// Local vertical, local horizontal as used for spacecraft attitude:
+    /// `z` points to nadir (towards the central body), `y` opposite the
+    /// orbit normal and `x` completes the frame, along the velocity for
+    /// a circular orbit.
+    Lvlh,
+    /// Radial, along-track, cross-track (also known as RIC or RTN): `x`
+    /// points radially outward, `z` along the orbit normal and `y`
+    /// completes the frame, along the velocity for a circular orbit.
+    Rsw,
+    /// Velocity, normal, co-normal: `x` points along the velocity, `y`
+    /// along the orbit normal and `z` completes the frame.
+    Vnc
+}
+
+impl RotationMatrix {
+    /// The orbital frame of a body at `position` moving with `velocity`,
+    /// both expressed in an inertial frame. The result rotates
+    /// orbital-frame vectors into the inertial frame, so its columns are
+    /// the orbital axes in inertial coordinates.
+    ///
+    /// # Errors
+    /// Returns an error if the position or velocity is zero, or if they
+    /// are parallel, since the orbit plane is then undefined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// // A circular equatorial orbit, currently on the x axis.
+    /// let r = Vector3d::unit_x().scalar_multiple(7000.0);
+    /// let v = Vector3d::unit_y().scalar_multiple(7.5);
+    /// let lvlh = RotationMatrix::from_orbital_frame(&r, &v, OrbitalFrame::Lvlh).unwrap();
+    /// // The LVLH z axis points at the Earth.
+    /// assert!((lvlh.rotate_vector(&Vector3d::unit_z()) + Vector3d::unit_x()).norm() < 1.0e-12);
+    /// ```
+    pub fn from_orbital_frame(position: &Vector3d, velocity: &Vector3d,
+                              convention: OrbitalFrame) -> Result<Self, OrientationError> {
+        let invalid = |_| OrientationError::Parallel;
+        let radial = position.normalized().map_err(invalid)?;
+        let along = velocity.normalized().map_err(invalid)?;
+        let normal = position.cross(velocity).normalized().map_err(invalid)?;
+
+        let columns = match convention {
+            OrbitalFrame::Lvlh => {
+                let (y, z) = (normal.negate(), radial.negate());
+                [y.cross(&z), y, z]
+            },
+            OrbitalFrame::Rsw => [radial, normal.cross(&radial), normal],
+            OrbitalFrame::Vnc => [along, normal, along.cross(&normal)]
+        };
+        Ok(Self::from_columns(columns))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotation::Rotation;
+    use crate::matrix3::Matrix3;
+
+    fn state() -> (Vector3d, Vector3d) {
+        // An eccentric, inclined orbit away from periapsis, so the
+        // velocity is not perpendicular to the position.
+        (Vector3d::new([5000.0, 3000.0, 2000.0]), Vector3d::new([-3.0, 6.0, 2.5]))
+    }
+
+    #[test]
+    fn frames_are_proper_rotations() {
+        let (r, v) = state();
+        for convention in [OrbitalFrame::Lvlh, OrbitalFrame::Rsw, OrbitalFrame::Vnc] {
+            let m = RotationMatrix::from_orbital_frame(&r, &v, convention).unwrap().as_matrix3();
+            assert_matrix_approx_eq!(Matrix3::identity(), m.multiply(&m.transpose()), 1.0e-12);
+            assert_float_approx_eq!(1.0, m.determinant(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn axis_conventions() {
+        let (r, v) = state();
+        let h = r.cross(&v).normalized().unwrap();
+        let r_hat = r.normalized().unwrap();
+        let v_hat = v.normalized().unwrap();
+
+        let lvlh = RotationMatrix::from_orbital_frame(&r, &v, OrbitalFrame::Lvlh).unwrap();
+        assert_vector_approx_eq!(r_hat.negate(), lvlh.rotate_vector(&Vector3d::unit_z()), 1.0e-12);
+        assert_vector_approx_eq!(h.negate(), lvlh.rotate_vector(&Vector3d::unit_y()), 1.0e-12);
+        assert!(lvlh.rotate_vector(&Vector3d::unit_x()).dot(&v) > 0.0);
+
+        let rsw = RotationMatrix::from_orbital_frame(&r, &v, OrbitalFrame::Rsw).unwrap();
+        assert_vector_approx_eq!(r_hat, rsw.rotate_vector(&Vector3d::unit_x()), 1.0e-12);
+        assert_vector_approx_eq!(h, rsw.rotate_vector(&Vector3d::unit_z()), 1.0e-12);
+
+        let vnc = RotationMatrix::from_orbital_frame(&r, &v, OrbitalFrame::Vnc).unwrap();
+        assert_vector_approx_eq!(v_hat, vnc.rotate_vector(&Vector3d::unit_x()), 1.0e-12);
+        assert_vector_approx_eq!(h, vnc.rotate_vector(&Vector3d::unit_y()), 1.0e-12);
+    }
+
+    #[test]
+    fn degenerate_state() {
+        let r = Vector3d::unit_x();
+        assert!(RotationMatrix::from_orbital_frame(&r, &r.scalar_multiple(2.0), OrbitalFrame::Lvlh).is_err());
+        assert!(RotationMatrix::from_orbital_frame(&r, &Vector3d::zero(), OrbitalFrame::Rsw).is_err());
+    }
+}
@@ -1,3 +1,95 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+
 /// Orientation trait
-pub trait Orientation {
+///
+/// An orientation is a rotation taking vectors expressed in a body
+/// frame into a world frame whose z-axis points up, away from gravity.
+pub trait Orientation: Rotation {
+    /// The direction of gravity (world down, `-z`) expressed in the
+    /// body frame, as a unit vector. This is what an ideal
+    /// accelerometer at rest measures, negated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// // Pitched nose-down by a quarter turn about the body y-axis.
+    /// let angle = std::f64::consts::PI / 2.0;
+    /// let q = Quaternion::from_angle_axis(angle, &Vector3d::unit_y());
+    /// let down = q.gravity_direction();
+    /// assert!((down - Vector3d::unit_x()).norm() < 1.0e-12);
+    /// ```
+    fn gravity_direction(&self) -> Vector3d {
+        self.rotate_vector_inverse(&Vector3d::unit_z().negate())
+    }
+
+    /// The heading: the angle of rotation about the world vertical, in
+    /// radians between -pi and pi, positive counterclockwise when seen
+    /// from above. For a compass bearing measured clockwise from a
+    /// north-pointing y-axis, use `pi / 2 - heading`.
+    ///
+    /// Rather than extracting an Euler angle, this takes the twist of
+    /// the orientation about the vertical (its swing-twist
+    /// decomposition), so it stays stable at large roll and pitch. It
+    /// is only undefined when the body is turned exactly upside down
+    /// about a horizontal axis, in which case zero is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let yaw = Quaternion::from_angle_axis(0.5, &Vector3d::unit_z());
+    /// let roll = Quaternion::from_angle_axis(1.4, &Vector3d::unit_x());
+    /// let q = roll.before(&yaw);
+    /// assert!((q.heading() - 0.5).abs() < 1.0e-12);
+    /// ```
+    fn heading(&self) -> f64 {
+        let twist = self.as_quaternion().twist(&Vector3d::unit_z());
+        twist.rotation_vector().data[2]
+    }
+}
+
+impl Orientation for Quaternion {}
+
+impl Orientation for RotationMatrix {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_gravity_points_down() {
+        assert_vector_approx_eq!(Vector3d::unit_z().negate(), Quaternion::identity().gravity_direction());
+    }
+
+    #[test]
+    fn representations_agree() {
+        let q = Quaternion::from_angle_axis(0.7, &Vector3d::new([1.0, 2.0, 0.5]));
+        let r = q.as_rotation_matrix();
+        assert_vector_approx_eq!(q.gravity_direction(), r.gravity_direction());
+    }
+
+    #[test]
+    fn heading_ignores_tilt() {
+        let yaw = Quaternion::from_angle_axis(-2.5, &Vector3d::unit_z());
+        let tilt = Quaternion::from_angle_axis(1.5, &Vector3d::new([1.0, 1.0, 0.0]));
+        assert_float_approx_eq!(-2.5, tilt.before(&yaw).heading());
+        assert_float_approx_eq!(-2.5, tilt.before(&yaw).as_rotation_matrix().heading());
+    }
+
+    #[test]
+    fn heading_wraps() {
+        let q = Quaternion::from_angle_axis(3.5, &Vector3d::unit_z());
+        assert_float_approx_eq!(3.5 - 2.0 * std::f64::consts::PI, q.heading());
+    }
+
+    #[test]
+    fn heading_upside_down_is_zero() {
+        let q = Quaternion::from_angle_axis(std::f64::consts::PI, &Vector3d::unit_x());
+        assert_float_approx_eq!(0.0, q.heading());
+    }
 }
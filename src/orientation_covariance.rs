@@ -0,0 +1,339 @@
+use rand::Rng;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::matrix3::Matrix3;
+use crate::random::standard_normal_vector;
+use crate::statistics::rotation_statistics;
+use crate::error::OrientationError;
+
+/// A rotation together with the covariance of its uncertainty.
+///
+/// The covariance is a 3x3 matrix in the tangent space, expressed in
+/// the body frame: the true rotation is `q * exp(delta)`, where `delta`
+/// is a zero-mean rotation vector with the given covariance. This is
+/// the same error convention used by `Mekf`.
+#[derive(Copy, Clone, Debug)]
+pub struct OrientationCovariance {
+    rotation: Quaternion,
+    covariance: Matrix3
+}
+
+impl OrientationCovariance {
+    /// Create a new rotation with covariance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_z());
+    /// let c = OrientationCovariance::new(q, Matrix3::identity().scalar_multiple(1.0e-4));
+    /// ```
+    #[must_use]
+    pub fn new(rotation: Quaternion, covariance: Matrix3) -> Self {
+        Self {
+            rotation,
+            covariance
+        }
+    }
+
+    /// The mean rotation.
+    #[must_use]
+    pub fn rotation(&self) -> Quaternion {
+        self.rotation
+    }
+
+    /// The tangent-space covariance, in radians squared.
+    #[must_use]
+    pub fn covariance(&self) -> Matrix3 {
+        self.covariance
+    }
+
+    /// Compose two uncertain rotations, assuming their errors are
+    /// independent. As with `Rotation::before`, the result rotates
+    /// first by `self` then by `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let p = Matrix3::identity().scalar_multiple(1.0e-4);
+    /// let a = OrientationCovariance::new(Quaternion::identity(), p);
+    /// let b = OrientationCovariance::new(Quaternion::identity(), p);
+    /// assert_eq!(p.scalar_multiple(2.0), a.before(&b).covariance());
+    /// ```
+    #[must_use]
+    pub fn before(&self, other: &Self) -> Self {
+        // other * exp(d2) * self * exp(d1)
+        //   = other * self * exp(R1^T d2) * exp(d1),
+        // so other's error is carried into self's body frame.
+        let r = rotation_matrix(&self.rotation);
+        let carried = r.transpose().multiply(&other.covariance).multiply(&r);
+        Self::new(self.rotation.before(&other.rotation), self.covariance + carried)
+    }
+
+    /// Compose two uncertain rotations, assuming their errors are
+    /// independent. As with `Rotation::after`, the result rotates
+    /// first by `other` then by `self`.
+    #[must_use]
+    pub fn after(&self, other: &Self) -> Self {
+        other.before(self)
+    }
+
+    /// The inverse rotation, with its covariance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(std::f64::consts::PI / 2.0, &Vector3d::unit_z());
+    /// let p = Matrix3::from_diagonal(&Vector3d::new([1.0, 2.0, 3.0]));
+    /// let c = OrientationCovariance::new(q, p).inverse();
+    /// // The x and y variances swap in the inverse's body frame.
+    /// let d = c.covariance();
+    /// assert!((d.data[0][0] - 2.0).abs() < 1.0e-12);
+    /// assert!((d.data[1][1] - 1.0).abs() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        // (q * exp(d))^-1 = exp(-d) * q^-1 = q^-1 * exp(-R d)
+        let r = rotation_matrix(&self.rotation);
+        let covariance = r.multiply(&self.covariance).multiply(&r.transpose());
+        Self::new(self.rotation.inverse_unchecked(), covariance)
+    }
+
+    /// Rotate an uncertain vector, returning the rotated vector and its
+    /// covariance. Pass `Matrix3::zero()` as the vector covariance if
+    /// the vector is known exactly. The vector error is assumed
+    /// independent of the rotation error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let p = Matrix3::identity();
+    /// let c = OrientationCovariance::new(Quaternion::identity(), p);
+    /// let (v, cov) = c.rotate_vector(&Vector3d::unit_x(), &Matrix3::zero());
+    /// // Rotation errors cannot move a vector along itself.
+    /// assert_eq!(0.0, cov.data[0][0]);
+    /// ```
+    #[must_use]
+    pub fn rotate_vector(&self, v: &Vector3d, v_covariance: &Matrix3) -> (Vector3d, Matrix3) {
+        // q * exp(d) * v ~= R (v + d x v) = R v - R [v]x d
+        let r = rotation_matrix(&self.rotation);
+        let jacobian = r.multiply(&Matrix3::skew(v)).scalar_multiple(-1.0);
+        let from_rotation = jacobian.multiply(&self.covariance).multiply(&jacobian.transpose());
+        let from_vector = r.multiply(v_covariance).multiply(&r.transpose());
+        (self.rotation.rotate_vector(v), from_rotation + from_vector)
+    }
+
+    /// Draw a random rotation `q * exp(delta)`, where `delta` is drawn
+    /// from a zero-mean normal distribution with this covariance.
+    ///
+    /// # Errors
+    /// If the covariance is not positive semi-definite, the result will
+    /// be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let p = Matrix3::identity().scalar_multiple(1.0e-4);
+    /// let c = OrientationCovariance::new(Quaternion::identity(), p);
+    /// let q = c.sample(&mut rand::thread_rng()).unwrap();
+    /// ```
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Quaternion, OrientationError> {
+        Ok(self.samples(rng, 1)?[0])
+    }
+
+    /// Draw `n` random rotations as in `sample`, factoring the
+    /// covariance only once.
+    ///
+    /// # Errors
+    /// If the covariance is not positive semi-definite, the result will
+    /// be an Error.
+    pub fn samples<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Result<Vec<Quaternion>, OrientationError> {
+        let l = self.covariance.cholesky()?;
+        let samples = (0..n)
+            .map(|_| {
+                let delta = l.multiply_vector(&standard_normal_vector(rng));
+                Quaternion::from_rotation_vector(&delta).before(&self.rotation)
+            })
+            .collect();
+        Ok(samples)
+    }
+}
+
+/// Propagate uncertainty through an arbitrary function of rotations by
+/// Monte Carlo: draw `n` joint samples of the inputs, assumed
+/// independent, evaluate `f` on each, and summarize the outputs by
+/// their intrinsic mean and body-frame tangent covariance. Useful for
+/// checking analytic propagation such as `before` and `rotate_vector`,
+/// and for functions with no Jacobian at hand.
+///
+/// `f` receives one sampled rotation per input, in the order given.
+///
+/// # Errors
+/// Returns an error if `n` is less than two or any input covariance is
+/// not positive semi-definite.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let p = Matrix3::identity().scalar_multiple(1.0e-4);
+/// let a = OrientationCovariance::new(Quaternion::from_angle_axis(0.5, &Vector3d::unit_x()), p);
+/// let b = OrientationCovariance::new(Quaternion::from_angle_axis(1.0, &Vector3d::unit_y()), p);
+/// let sampled = propagate_monte_carlo(&[a, b], |q| q[0].before(&q[1]), 10000,
+///                                     &mut rand::thread_rng()).unwrap();
+/// let analytic = a.before(&b);
+/// assert!(sampled.rotation().angle_to(&analytic.rotation()) < 1.0e-3);
+/// ```
+pub fn propagate_monte_carlo<F, R>(inputs: &[OrientationCovariance], f: F, n: usize,
+                                   rng: &mut R) -> Result<OrientationCovariance, OrientationError>
+where
+    F: Fn(&[Quaternion]) -> Quaternion,
+    R: Rng + ?Sized
+{
+    if n < 2 {
+        return Err(OrientationError::InsufficientData("at least two samples are needed to estimate a covariance"))
+    }
+    let factors = inputs.iter()
+        .map(|input| input.covariance.cholesky())
+        .collect::<Result<Vec<Matrix3>, OrientationError>>()?;
+
+    let mut draw = Vec::with_capacity(inputs.len());
+    let outputs: Vec<Quaternion> = (0..n)
+        .map(|_| {
+            draw.clear();
+            for (input, l) in inputs.iter().zip(factors.iter()) {
+                let delta = l.multiply_vector(&standard_normal_vector(rng));
+                draw.push(Quaternion::from_rotation_vector(&delta).before(&input.rotation));
+            }
+            f(&draw)
+        })
+        .collect();
+    Ok(rotation_statistics(&outputs)?.orientation_covariance())
+}
+
+/// The rotation matrix of a quaternion, as a general matrix.
+fn rotation_matrix(q: &Quaternion) -> Matrix3 {
+    q.as_rotation_matrix().as_matrix3()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn example() -> OrientationCovariance {
+        let q = Quaternion::from_angle_axis(0.7, &Vector3d::new([1.0, 2.0, -1.0]));
+        let p = Matrix3::new([
+            [4.0e-4, 1.0e-4, 0.0],
+            [1.0e-4, 2.0e-4, -5.0e-5],
+            [0.0, -5.0e-5, 1.0e-4]
+        ]);
+        OrientationCovariance::new(q, p)
+    }
+
+    #[test]
+    fn double_inverse() {
+        let c = example();
+        let d = c.inverse().inverse();
+        assert_quat_approx_eq!(c.rotation(), d.rotation());
+        assert_matrix_approx_eq!(c.covariance(), d.covariance(), 1.0e-12);
+    }
+
+    #[test]
+    fn compose_with_exact_identity() {
+        let c = example();
+        let identity = OrientationCovariance::new(Quaternion::identity(), Matrix3::zero());
+        assert_matrix_approx_eq!(c.covariance(), c.before(&identity).covariance(), 1.0e-12);
+        assert_matrix_approx_eq!(c.covariance(), c.after(&identity).covariance(), 1.0e-12);
+    }
+
+    #[test]
+    fn compose_then_inverse_is_consistent() {
+        // (a then b)^-1 = (b^-1 then a^-1)
+        let a = example();
+        let b = OrientationCovariance::new(
+            Quaternion::from_angle_axis(1.2, &Vector3d::unit_y()),
+            Matrix3::from_diagonal(&Vector3d::new([1.0e-4, 3.0e-4, 2.0e-4])));
+        let left = a.before(&b).inverse();
+        let right = b.inverse().before(&a.inverse());
+        assert_matrix_approx_eq!(left.covariance(), right.covariance(), 1.0e-12);
+    }
+
+    #[test]
+    fn rotate_vector_jacobian() {
+        let c = example();
+        let v = Vector3d::new([0.5, -1.0, 2.0]);
+        let (_, cov) = c.rotate_vector(&v, &Matrix3::zero());
+
+        // The same covariance, from finite differences of the rotation.
+        let r = c.rotation();
+        let h = 1.0e-6;
+        let columns = [Vector3d::unit_x(), Vector3d::unit_y(), Vector3d::unit_z()].map(|e| {
+            let perturbed = Quaternion::from_rotation_vector(&e.scalar_multiple(h)).before(&r);
+            (perturbed.rotate_vector(&v) - r.rotate_vector(&v)).scalar_multiple(1.0 / h)
+        });
+        let jacobian = Matrix3::from_columns(columns);
+        let expected = jacobian.multiply(&c.covariance()).multiply(&jacobian.transpose());
+        assert_matrix_approx_eq!(expected, cov, 1.0e-8);
+    }
+
+    #[test]
+    fn samples_match_covariance() {
+        let c = example();
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples = c.samples(&mut rng, 20000).unwrap();
+
+        let inverse = c.rotation().inverse_unchecked();
+        let mut sum = Matrix3::zero();
+        for q in &samples {
+            let delta = q.before(&inverse).rotation_vector();
+            let outer = Matrix3::from_columns([
+                delta.scalar_multiple(delta.data[0]),
+                delta.scalar_multiple(delta.data[1]),
+                delta.scalar_multiple(delta.data[2])
+            ]);
+            sum = sum + outer;
+        }
+        let estimate = sum.scalar_multiple(1.0 / 20000.0);
+        assert_matrix_approx_eq!(c.covariance(), estimate, 2.0e-5);
+    }
+
+    #[test]
+    fn monte_carlo_matches_analytic_propagation() {
+        let a = example();
+        let b = OrientationCovariance::new(
+            Quaternion::from_angle_axis(-0.8, &Vector3d::new([0.2, 1.0, 0.5])),
+            Matrix3::from_diagonal(&Vector3d::new([1.0e-4, 3.0e-4, 2.0e-4])));
+        let mut rng = StdRng::seed_from_u64(19);
+        let sampled = propagate_monte_carlo(&[a, b], |q| q[0].before(&q[1]), 20000, &mut rng).unwrap();
+        let analytic = a.before(&b);
+        assert!(sampled.rotation().angle_to(&analytic.rotation()) < 1.0e-3);
+        assert_matrix_approx_eq!(analytic.covariance(), sampled.covariance(), 3.0e-5);
+
+        let inverted = propagate_monte_carlo(&[a], |q| q[0].inverse_unchecked(), 20000, &mut rng).unwrap();
+        assert_matrix_approx_eq!(a.inverse().covariance(), inverted.covariance(), 3.0e-5);
+    }
+
+    #[test]
+    fn monte_carlo_rejects_bad_input() {
+        let mut rng = StdRng::seed_from_u64(20);
+        assert!(propagate_monte_carlo(&[example()], |q| q[0], 1, &mut rng).is_err());
+        let p = Matrix3::from_diagonal(&Vector3d::new([1.0, -1.0, 1.0]));
+        let bad = OrientationCovariance::new(Quaternion::identity(), p);
+        assert!(propagate_monte_carlo(&[bad], |q| q[0], 10, &mut rng).is_err());
+    }
+
+    #[test]
+    fn sample_indefinite_covariance() {
+        let p = Matrix3::from_diagonal(&Vector3d::new([1.0, -1.0, 1.0]));
+        let c = OrientationCovariance::new(Quaternion::identity(), p);
+        assert!(c.sample(&mut StdRng::seed_from_u64(0)).is_err());
+    }
+}
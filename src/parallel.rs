@@ -0,0 +1,162 @@
+//! Parallel versions of the batch operations, behind the `rayon`
+//! feature. Each splits its input across rayon's global thread pool and
+//! returns the same result as its sequential counterpart, up to the
+//! rounding of sums taken in a different order.
+
+use rayon::prelude::*;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::evaluation::{RotationErrors, check_lengths};
+use crate::statistics::iterate_mean;
+use crate::error::OrientationError;
+
+/// How many elements each task handles. Large enough that the cost of
+/// scheduling a task, and of converting a quaternion to a matrix in
+/// `rotate_vectors_into`, is negligible.
+const CHUNK_SIZE: usize = 4096;
+
+/// Rotate each of a batch of vectors in parallel. See
+/// `Rotation::rotate_vectors`.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_z());
+/// let vs = vec![Vector3d::unit_x(); 10_000];
+/// let rotated = parallel::rotate_vectors(&q, &vs);
+/// assert!((rotated[9_999] - q.rotate_vector(&vs[9_999])).norm() < 1.0e-15);
+/// ```
+pub fn rotate_vectors<R: Rotation + Sync>(rotation: &R, vs: &[Vector3d]) -> Vec<Vector3d> {
+    let mut rotated = vec![Vector3d::ZERO; vs.len()];
+    rotate_vectors_into(rotation, vs, &mut rotated);
+    rotated
+}
+
+/// Rotate each of a batch of vectors in parallel into a caller-provided
+/// slice. See `Rotation::rotate_vectors_into`.
+///
+/// # Panics
+/// Panics if `vs` and `rotated` differ in length.
+pub fn rotate_vectors_into<R: Rotation + Sync>(rotation: &R, vs: &[Vector3d], rotated: &mut [Vector3d]) {
+    assert_eq!(vs.len(), rotated.len(), "Input and output lengths differ");
+    vs.par_chunks(CHUNK_SIZE)
+        .zip(rotated.par_chunks_mut(CHUNK_SIZE))
+        .for_each(|(v, out)| rotation.rotate_vectors_into(v, out));
+}
+
+/// The rotation minimizing the sum of squared geodesic distances to a
+/// set of rotations, the mean used by `rotation_statistics`, with each
+/// averaging step summed in parallel.
+///
+/// # Errors
+/// Returns an error if there are no rotations.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let rotations: Vec<Quaternion> = (0..999)
+///     .map(|i| Quaternion::from_angle_axis(0.001 * f64::from(i % 3) - 0.001, &Vector3d::unit_x()))
+///     .collect();
+/// let mean = parallel::mean_rotation(&rotations).unwrap();
+/// assert!(mean.angle_to(&Quaternion::identity()) < 1.0e-12);
+/// ```
+pub fn mean_rotation(rotations: &[Quaternion]) -> Result<Quaternion, OrientationError> {
+    if rotations.is_empty() {
+        return Err(OrientationError::InsufficientData("cannot average an empty set of rotations"))
+    }
+    Ok(iterate_mean(rotations, |inverse| {
+        rotations.par_iter().with_min_len(CHUNK_SIZE).map(|q| inverse.multiply(q).rotation_vector()).sum()
+    }))
+}
+
+/// `absolute_rotation_error`, computed in parallel.
+///
+/// # Errors
+/// Returns an error if the trajectories are empty or differ in length.
+pub fn absolute_rotation_error(estimated: &[Quaternion], truth: &[Quaternion],
+                               align: bool) -> Result<RotationErrors, OrientationError> {
+    check_lengths(estimated, truth)?;
+
+    let alignment = if align {
+        let offsets: Vec<Quaternion> = estimated.par_iter()
+            .zip(truth.par_iter())
+            .map(|(e, t)| e.inverse_unchecked().before(t))
+            .collect();
+        mean_rotation(&offsets)?
+    } else {
+        Quaternion::identity()
+    };
+
+    let errors = estimated.par_iter()
+        .zip(truth.par_iter())
+        .map(|(e, t)| e.before(&alignment).angle_to(t))
+        .collect();
+    Ok(RotationErrors::new(errors, alignment))
+}
+
+/// `relative_rotation_error`, computed in parallel.
+///
+/// # Errors
+/// Returns an error if the trajectories differ in length, if `delta` is
+/// zero, or if there are not more than `delta` samples.
+pub fn relative_rotation_error(estimated: &[Quaternion], truth: &[Quaternion],
+                               delta: usize) -> Result<RotationErrors, OrientationError> {
+    check_lengths(estimated, truth)?;
+    if delta == 0 || estimated.len() <= delta {
+        return Err(OrientationError::InvalidParameter("delta must be positive and shorter than the trajectories"))
+    }
+
+    let relative = |q: &[Quaternion], i: usize| q[i].inverse_unchecked().multiply(&q[i + delta]);
+    let errors = (0..estimated.len() - delta)
+        .into_par_iter()
+        .map(|i| relative(estimated, i).angle_to(&relative(truth, i)))
+        .collect();
+    Ok(RotationErrors::new(errors, Quaternion::identity()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation;
+    use crate::statistics::rotation_statistics;
+
+    fn trajectory(n: u32, rate: f64) -> Vec<Quaternion> {
+        (0..n)
+            .map(|i| Quaternion::from_angle_axis(rate * f64::from(i), &Vector3d::new([1.0, -0.5, 2.0])))
+            .collect()
+    }
+
+    #[test]
+    fn matches_sequential() {
+        let q = Quaternion::from_angle_axis(1.2, &Vector3d::new([0.3, 1.0, -1.0]));
+        let vs: Vec<Vector3d> = (0..10_000).map(|i| Vector3d::new([f64::from(i), 1.0, -2.0])).collect();
+        for (v, w) in vs.iter().zip(&rotate_vectors(&q, &vs)) {
+            assert_vector_approx_eq!(q.rotate_vector(v), *w, 1.0e-9);
+        }
+
+        let truth = trajectory(9_000, 1.0e-3);
+        let estimated = trajectory(9_000, 1.1e-3);
+        let expected = rotation_statistics(&estimated).unwrap().mean;
+        assert!(mean_rotation(&estimated).unwrap().angle_to(&expected) < 1.0e-12);
+
+        let sequential = evaluation::absolute_rotation_error(&estimated, &truth, true).unwrap();
+        let parallel = absolute_rotation_error(&estimated, &truth, true).unwrap();
+        assert!(parallel.alignment.angle_to(&sequential.alignment) < 1.0e-12);
+        assert_float_approx_eq!(sequential.rmse, parallel.rmse, 1.0e-12);
+
+        let sequential = evaluation::relative_rotation_error(&estimated, &truth, 10).unwrap();
+        let parallel = relative_rotation_error(&estimated, &truth, 10).unwrap();
+        assert_eq!(sequential.errors, parallel.errors);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(mean_rotation(&[]).is_err());
+        assert!(absolute_rotation_error(&[], &[], false).is_err());
+        assert!(relative_rotation_error(&trajectory(5, 0.1), &trajectory(5, 0.1), 5).is_err());
+    }
+}
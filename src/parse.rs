@@ -0,0 +1,175 @@
+use std::str::FromStr;
+use crate::vector3d::Vector3d;
+use crate::quaternion::Quaternion;
+use crate::error::OrientationError;
+
+impl FromStr for Vector3d {
+    type Err = OrientationError;
+
+    /// Parse three components separated by commas or whitespace,
+    /// optionally enclosed in brackets or parentheses, as in the
+    /// `Debug` output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let v: Vector3d = "1.0, 2.0, 3.0".parse().unwrap();
+    /// assert_eq!(Vector3d::new([1.0, 2.0, 3.0]), v);
+    /// assert_eq!(Ok(v), "[1 2 3]".parse());
+    /// assert!("1.0, 2.0".parse::<Vector3d>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let components = parse_list(s)?;
+        match components[..] {
+            [x, y, z] => Ok(Self::new([x, y, z])),
+            _ => Err(OrientationError::InvalidFormat("expected three components"))
+        }
+    }
+}
+
+impl FromStr for Quaternion {
+    type Err = OrientationError;
+
+    /// Parse a quaternion either as four components `w x y z`, separated
+    /// by commas or whitespace and optionally bracketed, or as a sum of
+    /// terms such as `0.707 + 0.707i + 0j + 0k`. In the second form,
+    /// terms may come in any order and missing terms are zero; the
+    /// `Debug` output is accepted too. The result is not normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q: Quaternion = "0.6 + 0.8k".parse().unwrap();
+    /// assert_eq!(Quaternion::new(0.6, Vector3d::new([0.0, 0.0, 0.8])), q);
+    /// assert_eq!(Ok(q), "0.6 0 0 0.8".parse());
+    /// assert_eq!(Ok(q), "[0.6, 0, 0, 0.8]".parse());
+    /// ```
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let text = text.strip_prefix("Quaternion").unwrap_or(text);
+        let terms = split_terms(text);
+        if terms.iter().any(|term| term.ends_with(['i', 'j', 'k'])) {
+            return parse_algebraic(&terms)
+        }
+        let components = parse_list(text)?;
+        match components[..] {
+            [w, x, y, z] => Ok(Self::new(w, Vector3d::new([x, y, z]))),
+            _ => Err(OrientationError::InvalidFormat("expected four components"))
+        }
+    }
+}
+
+/// Parse numbers separated by commas or whitespace, with optional
+/// enclosing brackets or parentheses.
+fn parse_list(s: &str) -> Result<Vec<f64>, OrientationError> {
+    let s = s.trim();
+    let s = s.strip_prefix('[').and_then(|s| s.strip_suffix(']'))
+        .or_else(|| s.strip_prefix('(').and_then(|s| s.strip_suffix(')')))
+        .unwrap_or(s);
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(parse_number)
+        .collect()
+}
+
+/// Split a sum such as `1 - 2.5e-1i + 3j` into signed terms with the
+/// whitespace removed: `["1", "-2.5e-1i", "+3j"]`. A sign directly after
+/// an exponent marker or another sign belongs to the number, so
+/// `1 + -2i` is read as `1` and `-2i`.
+fn split_terms(s: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        let continues = current.is_empty() || current.ends_with(['e', 'E']) || current == "+" || current == "-";
+        if (c == '+' || c == '-') && !continues {
+            terms.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    terms
+}
+
+/// Parse signed terms, each a real number or an `i`, `j` or `k`
+/// multiple, into a quaternion. A bare unit such as `-j` has
+/// coefficient one.
+fn parse_algebraic(terms: &[String]) -> Result<Quaternion, OrientationError> {
+    let mut components: [Option<f64>; 4] = [None; 4];
+    for term in terms {
+        let (coefficient, index) = match term.chars().last() {
+            Some('i') => (&term[..term.len() - 1], 1),
+            Some('j') => (&term[..term.len() - 1], 2),
+            Some('k') => (&term[..term.len() - 1], 3),
+            _ => (term.as_str(), 0)
+        };
+        let value = match coefficient {
+            "" | "+" if index > 0 => 1.0,
+            "-" if index > 0 => -1.0,
+            _ => parse_number(coefficient.strip_prefix('+').unwrap_or(coefficient))?
+        };
+        if components[index].replace(value).is_some() {
+            return Err(OrientationError::InvalidFormat("quaternion component given more than once"))
+        }
+    }
+    let [w, x, y, z] = components.map(|c| c.unwrap_or(0.0));
+    Ok(Quaternion::new(w, Vector3d::new([x, y, z])))
+}
+
+/// Parse one number, reporting failure as an `InvalidFormat` error.
+fn parse_number(token: &str) -> Result<f64, OrientationError> {
+    token.parse().map_err(|_| OrientationError::InvalidFormat("invalid number"))
+}
+
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::rotation::Rotation;
+
+    #[test]
+    fn vector_formats() {
+        let expected = Vector3d::new([1.0, -2.5, 3.0e-3]);
+        for s in &["1.0, -2.5, 3e-3", "  1 -2.5 3.0E-3 ", "[1, -2.5, 0.003]", "(1,-2.5,0.003)"] {
+            assert_eq!(Ok(expected), s.parse(), "{s}");
+        }
+        assert_eq!(Ok(expected), format!("{expected:?}").parse());
+    }
+
+    #[test]
+    fn vector_errors() {
+        assert_eq!(Err(OrientationError::InvalidFormat("expected three components")),
+                   "1, 2, 3, 4".parse::<Vector3d>());
+        assert_eq!(Err(OrientationError::InvalidFormat("invalid number")), "1, two, 3".parse::<Vector3d>());
+        assert!("".parse::<Vector3d>().is_err());
+        assert!("[1, 2, 3".parse::<Vector3d>().is_err());
+    }
+
+    #[test]
+    fn quaternion_formats() {
+        let expected = Quaternion::new(0.5, Vector3d::new([-0.5, 0.25, 1.0e-3]));
+        for s in &[
+            "0.5 -0.5 0.25 1e-3", "[0.5, -0.5, 0.25, 0.001]",
+            "0.5 - 0.5i + 0.25j + 1e-3k", "1e-3k+0.25j-0.5i+0.5", "0.5 + -0.5i + 0.25j + 0.001k"
+        ] {
+            assert_eq!(Ok(expected), s.parse(), "{s}");
+        }
+        assert_eq!(Ok(expected), format!("{expected:?}").parse());
+        assert_eq!(Ok(Quaternion::new(0.0, Vector3d::new([1.0, 0.0, -1.0]))), "i - k".parse());
+        assert_eq!(Ok(Quaternion::identity()), "1 + 0i".parse());
+    }
+
+    #[test]
+    fn quaternion_errors() {
+        assert_eq!(Err(OrientationError::InvalidFormat("expected four components")),
+                   "1 0 0".parse::<Quaternion>());
+        assert_eq!(Err(OrientationError::InvalidFormat("quaternion component given more than once")),
+                   "1 + 2i + 3i".parse::<Quaternion>());
+        assert!("1 + 2x + 3j".parse::<Quaternion>().is_err());
+        assert!("1 + ik".parse::<Quaternion>().is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::error::OrientationError;
+
+/// An attitude that points a primary body axis exactly at a target
+/// while best aligning a secondary body axis with a reference.
+#[derive(Copy, Clone, Debug)]
+pub struct Pointing {
+    /// The attitude, rotating body-frame vectors into the reference
+    /// frame the directions were given in.
+    pub attitude: Quaternion,
+    /// The angle in radians between the secondary body axis and its
+    /// reference direction. It is zero when the angle between the
+    /// target and the reference matches the angle between the two body
+    /// axes, and grows as the constraints conflict.
+    pub secondary_error: f64
+}
+
+/// Point `primary_axis` of the body at nadir, the direction from
+/// `position` towards the origin, while turning `secondary_axis` as
+/// close as possible to the direction of `velocity`. Both body axes are
+/// in body-frame coordinates; position and velocity are in the
+/// inertial frame.
+///
+/// # Errors
+/// Returns an error if either pair of directions is zero or parallel.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let r = Vector3d::unit_x().scalar_multiple(7000.0);
+/// let v = Vector3d::unit_y().scalar_multiple(7.5);
+/// // Camera boresight along body +z, body +x along track.
+/// let pointing = nadir_pointing(&r, &v, &Vector3d::unit_z(), &Vector3d::unit_x()).unwrap();
+/// assert!((pointing.attitude.rotate_vector(&Vector3d::unit_z()) + Vector3d::unit_x()).norm() < 1.0e-12);
+/// assert!(pointing.secondary_error < 1.0e-12);
+/// ```
+pub fn nadir_pointing(position: &Vector3d, velocity: &Vector3d, primary_axis: &Vector3d,
+                      secondary_axis: &Vector3d) -> Result<Pointing, OrientationError> {
+    align_axes(primary_axis, &position.negate(), secondary_axis, velocity)
+}
+
+/// Point `primary_axis` of the body from `position` towards `target`,
+/// such as a ground station, while turning `secondary_axis` as close as
+/// possible to `secondary_reference`, such as the sun direction. Body
+/// axes are in body-frame coordinates and everything else is in the
+/// inertial frame. To point at a direction rather than a location,
+/// such as the sun, use a zero position.
+///
+/// # Errors
+/// Returns an error if either pair of directions is zero or parallel,
+/// including when the target coincides with the position.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let sun = Vector3d::new([1.0, 1.0, 0.0]);
+/// // Solar panel normal (+x) at the sun, body +z as near to +z as possible.
+/// let pointing = target_pointing(&Vector3d::zero(), &sun, &Vector3d::unit_x(), &Vector3d::unit_z(), &Vector3d::unit_z()).unwrap();
+/// let normal = pointing.attitude.rotate_vector(&Vector3d::unit_x());
+/// assert!((normal - sun.normalized().unwrap()).norm() < 1.0e-12);
+/// ```
+pub fn target_pointing(position: &Vector3d, target: &Vector3d, primary_axis: &Vector3d,
+                       secondary_axis: &Vector3d, secondary_reference: &Vector3d) -> Result<Pointing, OrientationError> {
+    align_axes(primary_axis, &(*target - *position), secondary_axis, secondary_reference)
+}
+
+/// Find the attitude carrying `primary_axis` of the body exactly onto
+/// `primary_target`, while bringing `secondary_axis` as close as
+/// possible to `secondary_reference`. Body axes are in body-frame
+/// coordinates and the target and reference in the reference frame;
+/// none need be unit length.
+///
+/// This is the two-vector (TRIAD) construction: an orthonormal triad is
+/// built from each pair, with the primary direction first and the
+/// normal to the pair second, and the attitude maps one triad onto the
+/// other. The remaining secondary error is exactly the difference
+/// between the angle separating the body axes and the angle separating
+/// the target from the reference.
+///
+/// # Errors
+/// Returns an error if any direction is zero, or if either secondary
+/// direction is parallel to its primary, which leaves the rotation
+/// about the primary undetermined.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// // Look along world +y with the camera's up (body -y, as in OpenCV)
+/// // as near world +z as possible.
+/// let pointing = align_axes(&Vector3d::unit_z(), &Vector3d::unit_y(), &Vector3d::unit_y().negate(), &Vector3d::unit_z()).unwrap();
+/// let up = pointing.attitude.rotate_vector(&Vector3d::unit_y().negate());
+/// assert!((up - Vector3d::unit_z()).norm() < 1.0e-12);
+/// ```
+pub fn align_axes(primary_axis: &Vector3d, primary_target: &Vector3d, secondary_axis: &Vector3d,
+                  secondary_reference: &Vector3d) -> Result<Pointing, OrientationError> {
+    let triad = |primary: &Vector3d, secondary: &Vector3d| -> Result<[Vector3d; 3], OrientationError> {
+        let first = primary.normalized()?;
+        let second = primary.cross(secondary)
+            .normalized()
+            .map_err(|_| OrientationError::Parallel)?;
+        Ok([first, second, first.cross(&second)])
+    };
+    let body = RotationMatrix::from_columns(triad(primary_axis, secondary_axis)?);
+    let reference = RotationMatrix::from_columns(triad(primary_target, secondary_reference)?);
+    let attitude = body.inverse_unchecked().before(&reference).as_quaternion();
+
+    let achieved = attitude.rotate_vector(&secondary_axis.normalized()?);
+    let desired = secondary_reference.normalized()?;
+    let secondary_error = achieved.angle_between(&desired);
+    Ok(Pointing {
+        attitude,
+        secondary_error
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primary_is_exact_and_secondary_is_closest() {
+        let primary = Vector3d::new([0.2, 0.1, 1.0]);
+        let secondary = Vector3d::new([1.0, 0.3, 0.0]);
+        let target = Vector3d::new([-1.0, 2.0, 0.5]);
+        let reference = Vector3d::new([0.4, 0.0, 1.0]);
+        let pointing = target_pointing(&Vector3d::zero(), &target, &primary, &secondary, &reference).unwrap();
+        let q = pointing.attitude;
+        assert_vector_approx_eq!(target.normalized().unwrap(),
+                                 q.rotate_vector(&primary.normalized().unwrap()), 1.0e-12);
+
+        // Spinning about the primary axis can only make the secondary
+        // error worse.
+        let target_hat = target.normalized().unwrap();
+        let reference_hat = reference.normalized().unwrap();
+        let secondary_hat = secondary.normalized().unwrap();
+        for i in 1..36 {
+            let spun = q.before(&Quaternion::from_angle_axis(0.17 * f64::from(i), &target_hat));
+            let angle = spun.rotate_vector(&secondary_hat).dot(&reference_hat).acos();
+            assert!(angle >= pointing.secondary_error - 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn secondary_error_measures_conflict() {
+        // Body axes are perpendicular but the references are 60 degrees
+        // apart, so the best achievable secondary error is 30 degrees.
+        let reference = Vector3d::new([0.5, 0.75_f64.sqrt(), 0.0]);
+        let pointing = target_pointing(&Vector3d::zero(), &Vector3d::unit_x(), &Vector3d::unit_x(),
+                                       &Vector3d::unit_y(), &reference).unwrap();
+        assert_float_approx_eq!(std::f64::consts::FRAC_PI_6, pointing.secondary_error, 1.0e-12);
+    }
+
+    #[test]
+    fn secondary_error_is_difference_of_separations() {
+        let separation = |a: &Vector3d, b: &Vector3d| a.cross(b).norm().atan2(a.dot(b));
+        let cases = [
+            (Vector3d::new([1.0, 2.0, 3.0]), Vector3d::new([-1.0, 0.5, 0.2]),
+             Vector3d::new([0.0, 0.0, 2.0]), Vector3d::new([3.0, 1.0, 1.0])),
+            (Vector3d::new([0.0, -1.0, 0.1]), Vector3d::new([0.3, 0.3, 0.3]),
+             Vector3d::new([5.0, 0.0, 0.0]), Vector3d::new([-1.0, 0.1, 0.0])),
+        ];
+        for (primary, target, secondary, reference) in &cases {
+            let pointing = align_axes(primary, target, secondary, reference).unwrap();
+            let expected = (separation(primary, secondary) - separation(target, reference)).abs();
+            assert_float_approx_eq!(expected, pointing.secondary_error, 1.0e-12);
+            assert_vector_approx_eq!(target.normalized().unwrap(),
+                                     pointing.attitude.rotate_vector(&primary.normalized().unwrap()), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn consistent_pairs_are_matched_exactly() {
+        let q = Quaternion::from_angle_axis(2.2, &Vector3d::new([-0.4, 1.0, 0.3]));
+        let (primary, secondary) = (Vector3d::new([0.2, 0.9, -0.1]), Vector3d::new([1.0, 0.0, 0.4]));
+        let pointing = align_axes(&primary, &q.rotate_vector(&primary),
+                                  &secondary, &q.rotate_vector(&secondary)).unwrap();
+        assert!(pointing.attitude.angle_to(&q) < 1.0e-12);
+        assert!(pointing.secondary_error < 1.0e-12);
+    }
+
+    #[test]
+    fn target_relative_to_position() {
+        let position = Vector3d::new([1.0, 1.0, 0.0]);
+        let station = Vector3d::new([1.0, 3.0, 0.0]);
+        let pointing = target_pointing(&position, &station, &Vector3d::unit_z(), &Vector3d::unit_x(), &Vector3d::unit_z()).unwrap();
+        assert_vector_approx_eq!(Vector3d::unit_y(), pointing.attitude.rotate_vector(&Vector3d::unit_z()), 1.0e-12);
+    }
+
+    #[test]
+    fn degenerate_directions() {
+        let r = Vector3d::unit_x();
+        assert!(nadir_pointing(&r, &r, &Vector3d::unit_z(), &Vector3d::unit_x()).is_err());
+        assert!(nadir_pointing(&r, &Vector3d::unit_y(), &Vector3d::unit_z(), &Vector3d::unit_z()).is_err());
+        assert!(target_pointing(&r, &r, &Vector3d::unit_z(), &Vector3d::unit_x(), &Vector3d::unit_y()).is_err());
+    }
+}
@@ -1,8 +1,36 @@
 use std::fmt;
+use std::iter::Product;
 use crate::vector3d::Vector3d;
-use crate::rotation::Rotation;
+use crate::rotation::{Rotation, compose_all};
 use crate::rotation_matrix::RotationMatrix;
 use crate::constants::DBL_EPSILON;
+use crate::error::OrientationError;
+use crate::checked;
+use crate::trig;
+use crate::units::Radians;
+
+/// Below this value of 1 - cos(theta), slerp falls back to a
+/// normalized linear interpolation.
+const SLERP_LINEAR_THRESHOLD: f64 = 1.0e-9;
+
+/// Batches at least this long are rotated through a rotation matrix:
+/// building it costs about as much as rotating two vectors by the
+/// quaternion, and each vector after that costs a third as much.
+const BATCH_MATRIX_THRESHOLD: usize = 4;
+
+/// How a rotation is mapped to a three-component error vector, as used
+/// by error-state filters and attitude controllers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ErrorVectorMap {
+    /// Twice the imaginary part of the quaternion, `2 sin(angle / 2)`
+    /// along the axis. Cheap, but its magnitude falls short of the
+    /// angle by a relative `angle^2 / 24`: about 0.1% at 9 degrees and
+    /// 1% at 28 degrees. It can only represent rotations up to a half
+    /// turn, at magnitude 2.
+    TwiceImaginary,
+    /// The rotation vector (the logarithm map), exact for any angle.
+    RotationVector
+}
 
 /// A quaternion
 #[derive(Copy, Clone, PartialEq)]
@@ -12,6 +40,18 @@ pub struct Quaternion {
 }
 
 impl Quaternion {
+    /// The identity rotation, usable in constant expressions where the
+    /// `identity` method of `Rotation` is not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// static REST: [Quaternion; 2] = [Quaternion::IDENTITY, Quaternion::IDENTITY];
+    /// assert_eq!(Quaternion::identity(), REST[1]);
+    /// ```
+    pub const IDENTITY: Self = Self::new(1.0, Vector3d::ZERO);
+
     /// Create a new Quaternion.
     ///
     /// # Examples
@@ -21,14 +61,54 @@ impl Quaternion {
     /// let imaginary_part = orientations::Vector3d::zero();
     /// let q = orientations::Quaternion::new(real_part, imaginary_part);
     /// ```
-    pub fn new(real_part: f64, imaginary_part: Vector3d) -> Self {
+    #[must_use]
+    pub const fn new(real_part: f64, imaginary_part: Vector3d) -> Self {
         Self {
             real_part,
             imaginary_part
         }
     }
 
-    /// Create a quaternion from the corresponding angle and axis of rotation.
+    /// Create a quaternion from its scalar (real) and vector
+    /// (imaginary) parts; the same as `new`, named to pair with `split`.
+    #[must_use]
+    pub const fn from_parts(scalar: f64, vector: Vector3d) -> Self {
+        Self::new(scalar, vector)
+    }
+
+    /// Create the pure quaternion `(0, v)`, as used to rotate a vector
+    /// by conjugation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let v = Vector3d::new([1.0, 2.0, 3.0]);
+    /// assert_eq!((0.0, v), Quaternion::pure(v).split());
+    /// ```
+    #[must_use]
+    pub const fn pure(v: Vector3d) -> Self {
+        Self::new(0.0, v)
+    }
+
+    /// The scalar (real) and vector (imaginary) parts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(std::f64::consts::PI, &Vector3d::unit_z());
+    /// let (scalar, vector) = q.split();
+    /// assert!(scalar.abs() < 1.0e-12);
+    /// assert!((vector - Vector3d::unit_z()).norm() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub const fn split(&self) -> (f64, Vector3d) {
+        (self.real_part, self.imaginary_part)
+    }
+
+    /// Create a quaternion from the corresponding angle and axis of
+    /// rotation. The angle is in radians, or given as `Degrees`.
     ///
     /// # Panics
     /// Panics if axis has norm close to zero.
@@ -38,18 +118,565 @@ impl Quaternion {
     /// ```
     /// use orientations::*;
     /// let angle = std::f64::consts::PI / 2.0;
-    /// let q = Quaternion::from_angle_axis(angle, &Vector3d::x());
+    /// let q = Quaternion::from_angle_axis(angle, &Vector3d::unit_x());
+    /// let r = Quaternion::from_angle_axis(Degrees(90.0), &Vector3d::unit_x());
+    /// assert!(q.angle_to(&r) < 1.0e-12);
     /// ```
-    pub fn from_angle_axis(angle: f64, axis: &Vector3d) -> Self {
+    #[must_use]
+    pub fn from_angle_axis<A: Into<Radians>>(angle: A, axis: &Vector3d) -> Self {
+        let angle = angle.into().0;
         let axis_norm = axis.norm();
-        if axis_norm < DBL_EPSILON {
-            panic!("Axis has zero norm")
-        }
+        assert!(axis_norm >= DBL_EPSILON, "Axis has zero norm");
 
         let half_angle = angle / 2.0;
-        let real_part = half_angle.cos();
-        let imaginary_part = axis.scalar_multiple(half_angle.sin() / axis_norm);
-        Self::new(real_part, imaginary_part)
+        let (sin, cos) = trig::sin_cos(half_angle);
+        let real_part = cos;
+        let imaginary_part = axis.scalar_multiple(sin / axis_norm);
+        checked::unit_quaternion("result of Quaternion::from_angle_axis", Self::new(real_part, imaginary_part))
+    }
+
+    /// Create the shortest-arc rotation taking the direction of `from`
+    /// to the direction of `to`. If the directions are opposite, the
+    /// result is a half turn about an arbitrary perpendicular axis.
+    ///
+    /// # Errors
+    /// If either vector has norm close to zero, the result will be an
+    /// Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_two_vectors(&Vector3d::unit_x(), &Vector3d::unit_y()).unwrap();
+    /// let v = q.rotate_vector(&Vector3d::unit_x());
+    /// assert!((v - Vector3d::unit_y()).norm() < 1.0e-12);
+    /// ```
+    pub fn from_two_vectors(from: &Vector3d, to: &Vector3d) -> Result<Self, OrientationError> {
+        let a = from.normalized()?;
+        let b = to.normalized()?;
+        let cos_angle = a.dot(&b);
+
+        if 1.0 + cos_angle < DBL_EPSILON {
+            // Any axis perpendicular to a will do.
+            let (axis, _) = a.any_orthonormal_pair()?;
+            return Ok(Self::new(0.0, axis))
+        }
+
+        // (1 + cos, sin * axis) is the half-angle quaternion scaled by
+        // 2 cos(angle / 2).
+        Self::new(1.0 + cos_angle, a.cross(&b)).normalized()
+    }
+
+    /// The attitude of a gimbal pointing the body axis `boresight` at an
+    /// azimuth and elevation, in radians, in the convention of
+    /// `Vector3d::from_azimuth_elevation` but with azimuth measured from
+    /// `zero_azimuth` instead of the y-axis. World z is up, and
+    /// `zero_azimuth` is projected onto the horizontal plane.
+    ///
+    /// At zero azimuth and elevation, the body is turned by the shortest
+    /// rotation taking `boresight` onto the zero-azimuth direction. It is
+    /// then raised by the elevation about the horizontal axis
+    /// perpendicular to that direction, and then turned by the azimuth
+    /// about the vertical, as an azimuth-over-elevation mount would.
+    ///
+    /// # Errors
+    /// Returns an error if the boresight is zero or if `zero_azimuth` is
+    /// vertical or zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// // Point a dish, whose boresight is body +z, 30 degrees above
+    /// // the horizon and due east in an east-north-up frame.
+    /// let (azimuth, elevation) = (std::f64::consts::FRAC_PI_2, 30.0_f64.to_radians());
+    /// let q = Quaternion::from_azimuth_elevation(azimuth, elevation, &Vector3d::unit_z(), &Vector3d::unit_y()).unwrap();
+    /// let direction = q.rotate_vector(&Vector3d::unit_z());
+    /// assert!((direction - Vector3d::from_azimuth_elevation(azimuth, elevation)).norm() < 1.0e-12);
+    /// ```
+    pub fn from_azimuth_elevation<A: Into<Radians>, E: Into<Radians>>(
+        azimuth: A, elevation: E, boresight: &Vector3d, zero_azimuth: &Vector3d
+    ) -> Result<Self, OrientationError> {
+        let (azimuth, elevation) = (azimuth.into().0, elevation.into().0);
+        let zero = Vector3d::new([zero_azimuth.data[0], zero_azimuth.data[1], 0.0])
+            .normalized()
+            .map_err(|_| OrientationError::Parallel)?;
+        let rest = Self::from_two_vectors(boresight, &zero)?;
+        let raise = Self::from_angle_axis(elevation, &zero.cross(&Vector3d::unit_z()));
+        let turn = Self::from_angle_axis(-azimuth, &Vector3d::unit_z());
+        Ok(rest.before(&raise).before(&turn))
+    }
+
+    /// Create a quaternion from a rotation vector, that is, a vector
+    /// whose direction is the axis of rotation and whose magnitude is
+    /// the angle of rotation (the exponential map). Unlike
+    /// `from_angle_axis`, a zero vector is allowed and yields the
+    /// identity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_rotation_vector(&Vector3d::zero());
+    /// assert_eq!(Quaternion::identity(), q);
+    /// ```
+    #[must_use]
+    pub fn from_rotation_vector(v: &Vector3d) -> Self {
+        let angle = v.norm();
+        let (sin, cos) = trig::sin_cos(angle / 2.0);
+
+        // sin(angle / 2) / angle tends to 1/2 as the angle goes to zero.
+        let scale = if angle < DBL_EPSILON {
+            0.5
+        } else {
+            sin / angle
+        };
+
+        checked::unit_quaternion("result of Quaternion::from_rotation_vector",
+                                 Self::new(cos, v.scalar_multiple(scale)))
+    }
+
+    /// The rotation vector of a rotation (the logarithm map): a vector
+    /// whose direction is the axis of rotation and whose magnitude is
+    /// the angle of rotation, between 0 and pi. This is the inverse of
+    /// `from_rotation_vector`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let v = Vector3d::new([0.1, -0.2, 0.3]);
+    /// let q = Quaternion::from_rotation_vector(&v);
+    /// assert!((q.rotation_vector() - v).norm() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn rotation_vector(&self) -> Vector3d {
+        // q and -q are the same rotation; pick the one with a
+        // non-negative real part so the angle is at most pi.
+        let (w, v) = if self.real_part < 0.0 {
+            (-self.real_part, self.imaginary_part.negate())
+        } else {
+            (self.real_part, self.imaginary_part)
+        };
+
+        let sin_half_angle = v.norm();
+        if sin_half_angle < DBL_EPSILON {
+            // angle / sin(angle / 2) tends to 2 / w as the angle goes
+            // to zero.
+            return if w < DBL_EPSILON { Vector3d::zero() } else { v.scalar_multiple(2.0 / w) }
+        }
+
+        let angle = 2.0 * sin_half_angle.atan2(w);
+        v.scalar_multiple(angle / sin_half_angle)
+    }
+
+    /// The error vector of a rotation under the chosen map, taking the
+    /// shorter way round whatever the sign of the quaternion. The
+    /// inverse of `from_error_vector`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.01, &Vector3d::unit_x());
+    /// let small = q.to_error_vector(ErrorVectorMap::TwiceImaginary);
+    /// let exact = q.to_error_vector(ErrorVectorMap::RotationVector);
+    /// assert!((small - exact).norm() < 1.0e-7);
+    /// ```
+    #[must_use]
+    pub fn to_error_vector(&self, map: ErrorVectorMap) -> Vector3d {
+        match map {
+            ErrorVectorMap::TwiceImaginary => {
+                let sign = if self.real_part < 0.0 { -2.0 } else { 2.0 };
+                self.imaginary_part.scalar_multiple(sign)
+            },
+            ErrorVectorMap::RotationVector => self.rotation_vector()
+        }
+    }
+
+    /// The unit quaternion with the given error vector under the chosen
+    /// map.
+    ///
+    /// # Errors
+    /// Under `ErrorVectorMap::TwiceImaginary`, vectors longer than 2
+    /// correspond to no rotation and the result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let v = Vector3d::new([0.2, -0.1, 0.05]);
+    /// let q = Quaternion::from_error_vector(&v, ErrorVectorMap::TwiceImaginary).unwrap();
+    /// assert!((q.to_error_vector(ErrorVectorMap::TwiceImaginary) - v).norm() < 1.0e-12);
+    /// assert!(Quaternion::from_error_vector(&Vector3d::unit_x().scalar_multiple(3.0),
+    ///                                       ErrorVectorMap::TwiceImaginary).is_err());
+    /// ```
+    pub fn from_error_vector(v: &Vector3d, map: ErrorVectorMap) -> Result<Self, OrientationError> {
+        match map {
+            ErrorVectorMap::TwiceImaginary => {
+                let half = v.scalar_multiple(0.5);
+                let real_squared = 1.0 - half.norm_squared();
+                if real_squared < -DBL_EPSILON {
+                    return Err(OrientationError::InvalidParameter("error vector is too long to represent a rotation"))
+                }
+                Ok(Self::new(real_squared.max(0.0).sqrt(), half))
+            },
+            ErrorVectorMap::RotationVector => Ok(Self::from_rotation_vector(v))
+        }
+    }
+
+    /// Return a quaternion representing the same rotation but with
+    /// unit norm.
+    ///
+    /// # Errors
+    /// If the quaternion has norm close to zero, the result will be an
+    /// Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::new(2.0, Vector3d::zero());
+    /// assert_eq!(Quaternion::identity(), q.normalized().unwrap());
+    /// ```
+    pub fn normalized(&self) -> Result<Self, OrientationError> {
+        let n = self.norm();
+        if n < DBL_EPSILON {
+            Err(OrientationError::ZeroNorm)
+        } else {
+            Ok(checked::unit_quaternion("result of Quaternion::normalized", self.scale(1.0 / n)))
+        }
+    }
+
+    /// Scale the quaternion to unit norm in place, as when correcting
+    /// the drift of an integrated attitude every step.
+    ///
+    /// # Errors
+    /// If the quaternion has norm close to zero, the result will be an
+    /// Error and the quaternion is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mut q = Quaternion::new(2.0, Vector3d::zero());
+    /// q.normalize_in_place().unwrap();
+    /// assert_eq!(Quaternion::identity(), q);
+    /// ```
+    pub fn normalize_in_place(&mut self) -> Result<(), OrientationError> {
+        let n = self.norm();
+        if n < DBL_EPSILON {
+            Err(OrientationError::ZeroNorm)
+        } else {
+            self.real_part /= n;
+            self.imaginary_part /= n;
+            checked::unit_quaternion("result of Quaternion::normalize_in_place", *self);
+            Ok(())
+        }
+    }
+
+    /// Normalize each of a batch of quaternions in place. With the
+    /// `simd` feature, four are processed at a time.
+    ///
+    /// # Errors
+    /// If any quaternion has norm close to zero, the result will be an
+    /// Error; those quaternions are left unchanged and the rest are
+    /// still normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mut qs = vec![Quaternion::new(2.0, Vector3d::zero()); 5];
+    /// Quaternion::normalize_each(&mut qs).unwrap();
+    /// assert!(qs.iter().all(|q| *q == Quaternion::identity()));
+    /// ```
+    pub fn normalize_each(qs: &mut [Self]) -> Result<(), OrientationError> {
+        #[cfg(feature = "simd")]
+        let (done, mut result) = crate::simd::normalize(qs);
+        #[cfg(not(feature = "simd"))]
+        let (done, mut result) = (0, Ok(()));
+        for q in &mut qs[done..] {
+            if let Err(e) = q.normalize_in_place() {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    /// Compose pairs of rotations: `out[i] = lhs[i].after(&rhs[i])`.
+    /// With the `simd` feature, four pairs are composed at a time.
+    ///
+    /// # Panics
+    /// Panics if the three slices differ in length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let lhs = vec![Quaternion::from_angle_axis(0.2, &Vector3d::unit_z()); 6];
+    /// let rhs = vec![Quaternion::from_angle_axis(0.3, &Vector3d::unit_z()); 6];
+    /// let mut out = vec![Quaternion::identity(); 6];
+    /// Quaternion::after_each(&lhs, &rhs, &mut out);
+    /// assert!(out[5].angle_to(&Quaternion::from_angle_axis(0.5, &Vector3d::unit_z())) < 1.0e-12);
+    /// ```
+    pub fn after_each(lhs: &[Self], rhs: &[Self], out: &mut [Self]) {
+        assert!(lhs.len() == rhs.len() && lhs.len() == out.len(), "Input and output lengths differ");
+        #[cfg(feature = "simd")]
+        let done = crate::simd::multiply(lhs, rhs, out);
+        #[cfg(not(feature = "simd"))]
+        let done = 0;
+        for ((a, b), product) in lhs[done..].iter().zip(&rhs[done..]).zip(&mut out[done..]) {
+            *product = a.multiply(b);
+        }
+    }
+
+    /// Whether every component is finite, neither infinite nor NaN.
+    #[must_use]
+    pub fn is_finite(&self) -> bool {
+        self.real_part.is_finite() && self.imaginary_part.is_finite()
+    }
+
+    /// Whether the quaternion has unit norm to within `tolerance`, as a
+    /// rotation should. Non-finite quaternions are not normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x());
+    /// assert!(q.is_normalized(1.0e-12));
+    /// assert!(!Quaternion::new(2.0, Vector3d::zero()).is_normalized(1.0e-12));
+    /// ```
+    #[must_use]
+    pub fn is_normalized(&self, tolerance: f64) -> bool {
+        (self.norm() - 1.0).abs() <= tolerance
+    }
+
+    /// Spherical linear interpolation between two rotations. When `t`
+    /// is 0 the result is `self`; when `t` is 1 the result represents
+    /// the same rotation as `other`. Since `q` and `-q` represent the
+    /// same rotation, the interpolation always follows the shorter of
+    /// the two possible arcs. Both quaternions are assumed to have
+    /// unit norm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let angle = std::f64::consts::PI / 2.0;
+    /// let q = Quaternion::identity();
+    /// let r = Quaternion::from_angle_axis(angle, &Vector3d::unit_z());
+    /// let (half, _axis) = q.slerp(&r, 0.5).angle_axis();
+    /// assert!((half - angle / 2.0).abs() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        let mut cos_theta = self.dot(other);
+        let mut end = *other;
+        if cos_theta < 0.0 {
+            // Flip to the same hemisphere as self to take the short way
+            // around.
+            end = end.scale(-1.0);
+            cos_theta = -cos_theta;
+        }
+
+        if cos_theta > 1.0 - SLERP_LINEAR_THRESHOLD {
+            // The rotations are nearly identical; sin(theta) is too
+            // small to divide by, so interpolate linearly instead.
+            let q = self.scale(1.0 - t).add(&end.scale(t));
+            return q.normalized().unwrap_or(*self);
+        }
+
+        let theta = cos_theta.acos();
+        let inv_sin_theta = 1.0 / trig::sin(theta);
+        let a = trig::sin((1.0 - t) * theta) * inv_sin_theta;
+        let b = trig::sin(t * theta) * inv_sin_theta;
+        checked::unit_quaternion("result of Quaternion::slerp", self.scale(a).add(&end.scale(b)))
+    }
+
+    /// Split a rotation into a twist about `axis` followed by a swing
+    /// about an axis perpendicular to it, returned as `(swing, twist)`.
+    /// That is, `twist.before(&swing)` is the original rotation. This
+    /// is the usual tool for joint limits in skeletal animation and for
+    /// separating heading from tilt.
+    ///
+    /// If the rotation is a half turn about an axis perpendicular to
+    /// `axis`, the twist is undefined; the identity is used and the
+    /// whole rotation is returned as the swing.
+    ///
+    /// # Errors
+    /// If the axis has norm close to zero, the result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let twist = Quaternion::from_angle_axis(0.4, &Vector3d::unit_z());
+    /// let swing = Quaternion::from_angle_axis(0.7, &Vector3d::unit_x());
+    /// let (s, t) = twist.before(&swing).swing_twist(&Vector3d::unit_z()).unwrap();
+    /// assert!((s.rotation_vector() - swing.rotation_vector()).norm() < 1.0e-12);
+    /// assert!((t.rotation_vector() - twist.rotation_vector()).norm() < 1.0e-12);
+    /// ```
+    pub fn swing_twist(&self, axis: &Vector3d) -> Result<(Self, Self), OrientationError> {
+        let axis = axis.normalized()?;
+        let twist = self.twist(&axis);
+        let swing = self.multiply(&twist.conjugate());
+        Ok((swing, twist))
+    }
+
+    /// The rotation about `axis` nearest to `self` in geodesic
+    /// distance, returned with the residual rotation as
+    /// `(closest, residual)`, where `residual.before(&closest)` is the
+    /// original rotation. The nearest rotation is the twist of the
+    /// swing-twist decomposition, so it is undefined for a half turn
+    /// about a perpendicular axis; the identity is then returned.
+    ///
+    /// # Errors
+    /// If the axis has norm close to zero, the result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.3, &Vector3d::new([0.1, 0.0, 1.0]));
+    /// let (closest, residual) = q.closest_rotation_about(&Vector3d::unit_z()).unwrap();
+    /// let (_angle, axis) = closest.angle_axis();
+    /// assert!((axis - Vector3d::unit_z()).norm() < 1.0e-12);
+    /// assert!((residual.angle_to(&Quaternion::identity()) - q.angle_to(&closest)).abs() < 1.0e-12);
+    /// ```
+    pub fn closest_rotation_about(&self, axis: &Vector3d) -> Result<(Self, Self), OrientationError> {
+        let axis = axis.normalized()?;
+        let closest = self.twist(&axis);
+        let residual = closest.conjugate().multiply(self);
+        Ok((closest, residual))
+    }
+
+    /// The geodesic distance between two rotations: the angle, between
+    /// 0 and pi, of the rotation taking one to the other. Both
+    /// quaternions are assumed to have unit norm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.2, &Vector3d::unit_x());
+    /// let r = Quaternion::from_angle_axis(0.5, &Vector3d::unit_x());
+    /// assert!((q.angle_to(&r) - 0.3).abs() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn angle_to(&self, other: &Self) -> f64 {
+        // atan2 of the sine and cosine of the half angle is accurate
+        // for nearby rotations, where acos of the dot product is not.
+        let delta = self.conjugate().multiply(other);
+        2.0 * delta.imaginary_part.norm().atan2(delta.real_part.abs())
+    }
+
+    /// Whether two unit quaternions represent rotations within
+    /// `tolerance` radians of each other. Since `q` and `-q` are the same
+    /// rotation, they compare equal; use the `approx` traits for a
+    /// component-wise comparison.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::new(0.6, Vector3d::new([0.0, 0.8, 0.0]));
+    /// let negated = Quaternion::new(-0.6, Vector3d::new([0.0, -0.8, 0.0]));
+    /// assert!(q.approx_eq(&negated, 1.0e-12));
+    /// assert!(!q.approx_eq(&Quaternion::identity(), 0.1));
+    /// ```
+    #[must_use]
+    pub fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        self.angle_to(other) <= tolerance
+    }
+
+    /// The bit patterns of the components `w, x, y, z`, after choosing
+    /// the sign that makes the first nonzero component positive, turning
+    /// `-0.0` into `0.0` and replacing any NaN with the standard one.
+    /// Quaternions that are exactly equal up to sign, and so the same
+    /// rotation, get the same bits, making the result usable as a
+    /// `HashMap` key. Rotations that differ only by rounding get
+    /// different keys; round the components first if that matters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// use std::collections::HashMap;
+    /// let q = Quaternion::new(0.6, Vector3d::new([0.0, -0.8, 0.0]));
+    /// let negated = Quaternion::new(-0.6, Vector3d::new([-0.0, 0.8, 0.0]));
+    /// let mut cache = HashMap::new();
+    /// cache.insert(q.canonical_bits(), "expensive result");
+    /// assert_eq!(Some(&"expensive result"), cache.get(&negated.canonical_bits()));
+    /// ```
+    #[must_use]
+    pub fn canonical_bits(&self) -> [u64; 4] {
+        let mut components = [self.real_part, self.imaginary_part.data[0],
+                              self.imaginary_part.data[1], self.imaginary_part.data[2]];
+        let first = components.iter().find(|c| **c != 0.0 && !c.is_nan());
+        if first.is_some_and(|c| *c < 0.0) {
+            for c in &mut components {
+                *c = -*c;
+            }
+        }
+        components.map(|c| if c.is_nan() { f64::NAN.to_bits() } else { (c + 0.0).to_bits() })
+    }
+
+    /// The twist of a rotation about a unit axis: the rotation about
+    /// that axis closest to `self`, found by projecting the imaginary
+    /// part onto the axis. If the projection vanishes (the rotation is
+    /// a half turn about a perpendicular axis), the twist is undefined
+    /// and the identity is returned.
+    pub(crate) fn twist(&self, axis: &Vector3d) -> Self {
+        let projected = Self::new(self.real_part, axis.scalar_multiple(self.imaginary_part.dot(axis)));
+        projected.normalized().unwrap_or_else(|_| Self::identity())
+    }
+
+    /// The real (scalar) part.
+    pub(crate) fn real_part(&self) -> f64 {
+        self.real_part
+    }
+
+    /// The imaginary (vector) part.
+    pub(crate) fn imaginary_part(&self) -> Vector3d {
+        self.imaginary_part
+    }
+
+    /// Compute the dot product of two quaternions, treated as 4-d
+    /// vectors.
+    pub(crate) fn dot(&self, other: &Self) -> f64 {
+        self.real_part * other.real_part + self.imaginary_part.dot(&other.imaginary_part)
+    }
+
+    /// Multiply each component of a quaternion by a scalar.
+    pub(crate) fn scale(&self, alpha: f64) -> Self {
+        Self::new(alpha * self.real_part, self.imaginary_part.scalar_multiple(alpha))
+    }
+
+    /// The Hamilton product `self * other`, which need not be a
+    /// rotation; composition goes through `before` and `after`.
+    pub(crate) fn multiply(&self, other: &Self) -> Self {
+        // Written out component by component, 16 multiplications and
+        // 12 additions, rather than through dot and cross products and
+        // the intermediate vectors they create; composition is hot.
+        let (w1, [x1, y1, z1]) = (self.real_part, self.imaginary_part.data);
+        let (w2, [x2, y2, z2]) = (other.real_part, other.imaginary_part.data);
+        let product = Self::new(
+            w1 * w2 - x1 * x2 - y1 * y2 - z1 * z2,
+            Vector3d::new([
+                w1 * x2 + x1 * w2 + y1 * z2 - z1 * y2,
+                w1 * y2 - x1 * z2 + y1 * w2 + z1 * x2,
+                w1 * z2 + x1 * y2 - y1 * x2 + z1 * w2
+            ])
+        );
+        checked::quaternion("result of Quaternion::multiply", product)
+    }
+
+    /// Add two quaternions componentwise.
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        Self::new(self.real_part + other.real_part,
+                  self.imaginary_part + other.imaginary_part)
     }
 
     /// Compute the conjugate of a quaternion.
@@ -68,6 +695,45 @@ impl Quaternion {
     }
 }
 
+impl Default for Quaternion {
+    /// The identity rotation.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Product for Quaternion {
+    /// Compose left to right; see `compose_all`.
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::identity(), |total, r| total.after(&r))
+    }
+}
+
+impl<'a> Product<&'a Quaternion> for Quaternion {
+    /// Compose left to right; see `compose_all`.
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        compose_all(iter)
+    }
+}
+
+impl From<[f64; 4]> for Quaternion {
+    /// Build a quaternion from its components in the order
+    /// `[w, x, y, z]`, scalar part first, as in `Quaternion::new`.
+    /// Some libraries put the scalar last; reorder such arrays before
+    /// converting.
+    fn from([w, x, y, z]: [f64; 4]) -> Self {
+        Self::new(w, Vector3d::new([x, y, z]))
+    }
+}
+
+impl From<Quaternion> for [f64; 4] {
+    /// The components in the order `[w, x, y, z]`, scalar part first.
+    fn from(q: Quaternion) -> Self {
+        let [x, y, z] = q.imaginary_part.data;
+        [q.real_part, x, y, z]
+    }
+}
+
 impl fmt::Debug for Quaternion {
     /// Pretty-print a quaternion.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -84,7 +750,7 @@ impl fmt::Debug for Quaternion {
 }
 
 impl Rotation for Quaternion {
-    type R = Self;
+    type Output = Self;
 
     /// Return the identity Quaternion.
     ///
@@ -96,7 +762,7 @@ impl Rotation for Quaternion {
     /// assert_eq!(expected, Quaternion::identity());
     /// ```
     fn identity() -> Self {
-        Self::new(1.0, Vector3d::zero())
+        Self::IDENTITY
     }
 
     /// Calculate the inverse of a quaternion.
@@ -104,7 +770,7 @@ impl Rotation for Quaternion {
     /// # Errors
     ///
     /// Returns an error if the quaternion is close to zero. This can
-    /// happen if Quaternion::new() is misused (e.g. by instantiating
+    /// happen if `Quaternion::new()` is misused (e.g. by instantiating
     /// an all-zero quaternion), or through the accumulation of
     /// floating point errors.
     ///
@@ -113,15 +779,15 @@ impl Rotation for Quaternion {
     /// ```
     /// use orientations::*;
     /// let angle = std::f64::consts::PI / 2.0;
-    /// let q = Quaternion::from_angle_axis(angle, &Vector3d::x());
-    /// let expected = Quaternion::from_angle_axis(angle, &Vector3d::x().negate());
+    /// let q = Quaternion::from_angle_axis(angle, &Vector3d::unit_x());
+    /// let expected = Quaternion::from_angle_axis(angle, &Vector3d::unit_x().negate());
     /// assert_eq!(expected, q.inverse().unwrap());
     /// ```
-    fn inverse(&self) -> Result<Self, String> {
+    fn inverse(&self) -> Result<Self, OrientationError> {
         // Check that norm is > 0
         let norm_squared = self.norm_squared();
         if norm_squared < DBL_EPSILON {
-            return Err(String::from("Quaternion close to zero; cannot invert."))
+            return Err(OrientationError::ZeroNorm)
         }
 
         let inv_norm_squared = 1.0 / norm_squared;
@@ -138,7 +804,7 @@ impl Rotation for Quaternion {
         let c = self.conjugate();
         let real_part = c.real_part * inv_norm_squared;
         let imaginary_part = c.imaginary_part.scalar_multiple(inv_norm_squared);
-        Self::new(real_part, imaginary_part)
+        checked::quaternion("result of Quaternion::inverse_unchecked", Self::new(real_part, imaginary_part))
     }
 
     /// Get the quaternion representation of a rotation.
@@ -147,15 +813,36 @@ impl Rotation for Quaternion {
     ///
     /// ```
     /// use orientations::*;
-    /// let r = Quaternion::from_angle_axis(0.03, &Vector3d::x());
+    /// let r = Quaternion::from_angle_axis(0.03, &Vector3d::unit_x());
     /// assert_eq!(r, r.as_quaternion());
     /// ```
     fn as_quaternion(&self) -> Self {
-        self.clone()
+        *self
     }
 
+    /// Get the rotation matrix representation of a rotation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.3, &Vector3d::new([1.0, 2.0, 3.0]));
+    /// let v = Vector3d::new([4.0, 5.0, 6.0]);
+    /// let error = q.rotate_vector(&v) - q.as_rotation_matrix().rotate_vector(&v);
+    /// assert!(error.norm() < 1.0e-12);
+    /// ```
+    #[allow(clippy::many_single_char_names)]
     fn as_rotation_matrix(&self) -> RotationMatrix {
-        RotationMatrix::identity()
+        // Dividing by the squared norm makes this correct for
+        // quaternions that have drifted slightly from unit norm.
+        let s = 2.0 / self.norm_squared();
+        let w = self.real_part;
+        let [x, y, z] = self.imaginary_part.data;
+
+        let r1 = Vector3d::new([1.0 - s * (y * y + z * z), s * (x * y - w * z), s * (x * z + w * y)]);
+        let r2 = Vector3d::new([s * (x * y + w * z), 1.0 - s * (x * x + z * z), s * (y * z - w * x)]);
+        let r3 = Vector3d::new([s * (x * z - w * y), s * (y * z + w * x), 1.0 - s * (x * x + y * y)]);
+        checked::rotation_matrix("result of Quaternion::as_rotation_matrix", RotationMatrix::from_rows([r1, r2, r3]))
     }
 
     /// Get the angle and axis associated with a rotation. If the
@@ -169,7 +856,7 @@ impl Rotation for Quaternion {
     /// let q = Quaternion::identity();
     /// let (angle, axis) = q.angle_axis();
     /// assert_eq!(angle, 0.0);
-    /// assert_eq!(axis, Vector3d::z());
+    /// assert_eq!(axis, Vector3d::unit_z());
     /// ```
     fn angle_axis(&self) -> (f64, Vector3d) {
         let n = self.norm();
@@ -182,74 +869,40 @@ impl Rotation for Quaternion {
         let angle = (self.real_part / n).acos() * 2.0;
         let axis = match self.imaginary_part.normalized() {
             Ok(axis) => axis,
-            Err(_error) => Vector3d::z()
+            Err(_error) => Vector3d::unit_z()
         };
 
         (angle, axis)
     }
 
-    /// Compose two rotations.
+    /// Compose two rotations: `self` and then `r`.
     ///
     /// # Examples
     ///
     /// ```
     /// use orientations::*;
     /// let angle = std::f64::consts::PI / 2.0;
-    /// let r = Quaternion::from_angle_axis(angle, &Vector3d::x());
-    /// let q = Quaternion::from_angle_axis(angle, &Vector3d::x().negate());
-    /// assert_eq!(Quaternion::identity(), r.multiply(&q));
-    /// assert_eq!(Quaternion::identity(), q.multiply(&r));
-    /// ```
-    fn multiply<T: Rotation>(&self, r: &T) -> Self {
-        let rr = r.as_quaternion();
-        let real_part = self.real_part * rr.real_part - self.imaginary_part.dot(&rr.imaginary_part);
-        let imaginary_part = rr.imaginary_part.scalar_multiple(self.real_part)
-            + self.imaginary_part.scalar_multiple(rr.real_part)
-            + self.imaginary_part.cross(&rr.imaginary_part);
-
-        Self::new(real_part, imaginary_part)
-    }
-
-    /// Compose two rotations.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use orientations::*;
-    /// let q = Quaternion::identity();
-    /// let r = Quaternion::identity();
-    ///
-    /// // q.before(&r) is the rotation equivalent to rotating first
-    /// // by q then by r.
+    /// let r = Quaternion::from_angle_axis(angle, &Vector3d::unit_x());
+    /// let q = Quaternion::from_angle_axis(angle, &Vector3d::unit_x().negate());
     /// assert_eq!(Quaternion::identity(), q.before(&r));
     /// ```
-    fn before<T: Rotation<R = T>>(&self, r: &T) -> T {
-        r.multiply(self)
+    fn before<T: Rotation>(&self, r: &T) -> Self {
+        r.as_quaternion().multiply(self)
     }
 
-    /// Compose two rotations.
-    ///
-    /// # Panics
-    ///
-    /// This function *can* panic if the inverse of the operand is
-    /// poorly defined. See the comment in the example below for more
-    /// specific context. This doesn't happen in the `before` function
-    /// due to implementation details.
+    /// Compose two rotations: `r` and then `self`.
     ///
     /// # Examples
     ///
     /// ```
     /// use orientations::*;
-    /// let q = Quaternion::identity();
-    /// let r = Quaternion::identity();
-    ///
-    /// // q.after(&r) is the rotation equivalent to rotating first
-    /// // by r then by q. This will panic if r is close to zero,
-    /// // in which case it is not a valid rotation!
+    /// let angle = std::f64::consts::PI / 2.0;
+    /// let r = Quaternion::from_angle_axis(angle, &Vector3d::unit_x());
+    /// let q = Quaternion::from_angle_axis(angle, &Vector3d::unit_x().negate());
     /// assert_eq!(Quaternion::identity(), q.after(&r));
     /// ```
-    fn after<T: Rotation<R = T>>(&self, r: &T) -> T {
-        r.inverse_unchecked().multiply(&self.inverse_unchecked()).inverse_unchecked()
+    fn after<T: Rotation>(&self, r: &T) -> Self {
+        self.multiply(&r.as_quaternion())
     }
 
     /// Rotate a vector
@@ -258,164 +911,56 @@ impl Rotation for Quaternion {
     ///
     /// ```
     /// use orientations::*;
-    /// let axis = Vector3d::z();
+    /// let axis = Vector3d::unit_z();
     /// let angle = std::f64::consts::PI / 2.0;
     /// let q = Quaternion::from_angle_axis(angle, &axis);
-    /// let v = Vector3d::x();
+    /// let v = Vector3d::unit_x();
     /// let w = q.rotate_vector(&v);
     /// ```
     fn rotate_vector(&self, v: &Vector3d) -> Vector3d {
-        let vv = Quaternion::new(0.0, v.clone());
-        let ww = self.multiply(&vv).multiply(&self.inverse_unchecked());
-        let w = ww.imaginary_part;
-        w
+        checked::unit_quaternion("rotation passed to Quaternion::rotate_vector", *self);
+        let v = checked::vector("vector passed to Quaternion::rotate_vector", *v);
+        // q v q^-1 expanded for q = (w, u): with t = s (u x v), it is
+        // v + w t + u x t. For a unit quaternion s = 2; dividing by the
+        // squared norm, as in as_rotation_matrix, makes this correct
+        // for quaternions that have drifted slightly from unit norm.
+        let u = &self.imaginary_part;
+        let t = u.cross(&v).scalar_multiple(2.0 / self.norm_squared());
+        v + t.scalar_multiple(self.real_part) + u.cross(&t)
+    }
+
+    fn rotate_vector_inverse(&self, v: &Vector3d) -> Vector3d {
+        checked::unit_quaternion("rotation passed to Quaternion::rotate_vector_inverse", *self);
+        let v = checked::vector("vector passed to Quaternion::rotate_vector_inverse", *v);
+        // As in rotate_vector, with the conjugate (w, -u).
+        let u = &self.imaginary_part;
+        let t = u.cross(&v).scalar_multiple(2.0 / self.norm_squared());
+        v - t.scalar_multiple(self.real_part) + u.cross(&t)
+    }
+
+    fn rotate_vectors_into(&self, vs: &[Vector3d], rotated: &mut [Vector3d]) {
+        assert_eq!(vs.len(), rotated.len(), "Input and output lengths differ");
+        if vs.len() >= BATCH_MATRIX_THRESHOLD {
+            self.as_rotation_matrix().rotate_vectors_into(vs, rotated);
+        } else {
+            for (v, out) in vs.iter().zip(rotated.iter_mut()) {
+                *out = self.rotate_vector(v);
+            }
+        }
     }
 
 }
 
 
 #[cfg(test)]
+#[allow(clippy::float_cmp)]
 mod tests {
     use super::*;
     use std::f64::consts::PI;
-
-    /// Asserts that two vectors are approximately (~1.0e-6) equal to each other.
-    ///
-    /// On panic, this macro will print the values of the expressions with their
-    /// debug representations. You can optionally add an optional diff value. If you
-    /// don't supply a diff value as an argument, `1.0e-6` is the default used.
-    ///
-    /// Source: https://github.com/ashleygwilliams/assert_approx_eq
-    macro_rules! assert_vector_approx_eq {
-        ($a:expr, $b:expr) => {{
-            let eps = 1.0e-6;
-            let err = $a - $b;
-            assert!(
-                err.norm() < eps,
-                "assertion failed: `(left !== right)` \
-                 (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
-                &$a,
-                &$b,
-                eps,
-                err.norm()
-            );
-        }};
-        ($a:expr, $b:expr, $eps:expr) => {{
-            let eps = $eps;
-            let err = $a - $b;
-            assert!(
-                err.norm() < eps,
-                "assertion failed: `(left !== right)` \
-                 (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
-                &$a,
-                &$b,
-                eps,
-                err.norm()
-            );
-        }};
-    }
-
-    macro_rules! assert_float_approx_eq {
-        ($a:expr, $b:expr) => {{
-            let eps = 1.0e-6;
-            let err = $a - $b;
-            assert!(
-                err.abs() < eps,
-                "assertion failed: `(left !== right)` \
-                 (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
-                &$a,
-                &$b,
-                eps,
-                err.abs()
-            );
-        }};
-        ($a:expr, $b:expr, $eps:expr) => {{
-            let eps = $eps;
-            let err = $a - $b;
-            assert!(
-                err.abs() < eps,
-                "assertion failed: `(left !== right)` \
-                 (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
-                &$a,
-                &$b,
-                eps,
-                err.abs()
-            );
-        }};
-    }
-
-    macro_rules! assert_quat_approx_eq {
-        ($a:expr, $b:expr) => {{
-            let eps = 1.0e-6;
-
-            let (angle_a, axis_a) = $a.angle_axis();
-            let (angle_b, axis_b) = $b.angle_axis();
-
-            assert!(
-                (angle_a - angle_b).abs() < eps,
-                "assertion failed: `(left !== right)` \
-                 (left: `{:?}`, right: `{:?}`, \
-                 expect angle: `{:?}`, real angle: `{:?}`) \
-                 expect axis: `{:?}`, real axis: `{:?}`",
-                &$a,
-                &$b,
-                angle_a,
-                angle_b,
-                axis_a,
-                axis_b
-            );
-
-            assert!(
-                (axis_a - axis_b).norm() < eps,
-                "assertion failed: `(left !== right)` \
-                 (left: `{:?}`, right: `{:?}`, \
-                 expect angle: `{:?}`, real angle: `{:?}`) \
-                 expect axis: `{:?}`, real axis: `{:?}`",
-                &$a,
-                &$b,
-                angle_a,
-                angle_b,
-                axis_a,
-                axis_b
-            );
-        }};
-        ($a:expr, $b:expr, $eps:expr) => {{
-            let eps = $eps;
-
-            let (angle_a, axis_a) = $a.angle_axis();
-            let (angle_b, axis_b) = $b.angle_axis();
-
-            assert!(
-                (angle_a - angle_b).abs() < eps,
-                "assertion failed: `(left !== right)` \
-                 (left: `{:?}`, right: `{:?}`, \
-                 expect angle: `{:?}`, real angle: `{:?}`) \
-                 expect axis: `{:?}`, real axis: `{:?}`",
-                &$a,
-                &$b,
-                angle_a,
-                angle_b,
-                axis_a,
-                axis_b
-            );
-
-            assert!(
-                (axis_a - axis_b).norm() < eps,
-                "assertion failed: `(left !== right)` \
-                 (left: `{:?}`, right: `{:?}`, \
-                 expect angle: `{:?}`, real angle: `{:?}`) \
-                 expect axis: `{:?}`, real axis: `{:?}`",
-                &$a,
-                &$b,
-                angle_a,
-                angle_b,
-                axis_a,
-                axis_b
-            );
-        }};
-    }
+    use crate::matrix3::Matrix3;
 
     #[test]
+    #[allow(deprecated)]
     fn from_angle_axis() {
         let angle = PI / 2.0;
         let q = Quaternion::from_angle_axis(angle, &Vector3d::x());
@@ -426,10 +971,144 @@ mod tests {
 
     #[test]
     #[should_panic]
+    #[allow(unused_must_use, clippy::should_panic_without_expect)]
     fn from_angle_zero_axis() {
         Quaternion::from_angle_axis(0.0, &Vector3d::zero());
     }
 
+    #[test]
+    fn rotation_vector_round_trip() {
+        let v = Vector3d::new([1.0, -2.0, 0.5]);
+        let q = Quaternion::from_rotation_vector(&v);
+        assert_vector_approx_eq!(v, q.rotation_vector());
+        // Both constructors take their trig from the same place.
+        assert!(q.approx_eq(&Quaternion::from_angle_axis(v.norm(), &v), 1.0e-14));
+    }
+
+    #[test]
+    fn rotation_vector_takes_short_way() {
+        // 3/2 pi about z is the same as -1/2 pi about z.
+        let q = Quaternion::from_angle_axis(1.5 * PI, &Vector3d::unit_z());
+        assert_vector_approx_eq!(Vector3d::unit_z().scalar_multiple(-PI / 2.0), q.rotation_vector());
+    }
+
+    #[test]
+    fn from_azimuth_elevation() {
+        // A turret whose boresight, body x, rests along world x.
+        let q = Quaternion::from_azimuth_elevation(0.0, 0.0, &Vector3d::unit_x(), &Vector3d::unit_x()).unwrap();
+        assert_quat_approx_eq!(Quaternion::identity(), q);
+
+        let (azimuth, elevation) = (1.2, -0.3);
+        let zero = Vector3d::new([1.0, 0.0, 0.5]);
+        let q = Quaternion::from_azimuth_elevation(azimuth, elevation, &Vector3d::unit_x(), &zero).unwrap();
+        let direction = q.rotate_vector(&Vector3d::unit_x());
+        // Azimuth turns clockwise seen from above, from x towards -y.
+        let expected = Vector3d::new([elevation.cos() * azimuth.cos(), -elevation.cos() * azimuth.sin(),
+                                      elevation.sin()]);
+        assert_vector_approx_eq!(expected, direction, 1.0e-12);
+        // No roll: the body's horizontal axis stays horizontal.
+        assert_float_approx_eq!(0.0, q.rotate_vector(&Vector3d::unit_y()).data[2], 1.0e-12);
+
+        assert!(Quaternion::from_azimuth_elevation(0.0, 0.0, &Vector3d::unit_x(), &Vector3d::unit_z()).is_err());
+    }
+
+    #[test]
+    fn from_two_vectors() {
+        let a = Vector3d::new([1.0, 2.0, 3.0]);
+        let b = Vector3d::new([-2.0, 0.5, 1.0]);
+        let q = Quaternion::from_two_vectors(&a, &b).unwrap();
+        assert_vector_approx_eq!(b.normalized().unwrap(), q.rotate_vector(&a.normalized().unwrap()));
+    }
+
+    #[test]
+    fn from_opposite_vectors() {
+        let a = Vector3d::new([1.0, 2.0, 3.0]);
+        let q = Quaternion::from_two_vectors(&a, &a.negate()).unwrap();
+        assert_vector_approx_eq!(a.negate(), q.rotate_vector(&a));
+    }
+
+    #[test]
+    fn swing_twist() {
+        let q = Quaternion::from_angle_axis(2.0, &Vector3d::new([0.3, -1.0, 0.6]));
+        let axis = Vector3d::new([1.0, 1.0, 1.0]);
+        let (swing, twist) = q.swing_twist(&axis).unwrap();
+        assert_quat_approx_eq!(q, twist.before(&swing));
+
+        // The twist is about the axis, and the swing axis is
+        // perpendicular to it.
+        let unit = axis.normalized().unwrap();
+        assert_float_approx_eq!(0.0, twist.rotation_vector().cross(&unit).norm());
+        assert_float_approx_eq!(0.0, swing.rotation_vector().dot(&unit));
+    }
+
+    #[test]
+    fn swing_twist_zero_axis() {
+        assert!(Quaternion::identity().swing_twist(&Vector3d::zero()).is_err());
+    }
+
+    #[test]
+    fn closest_rotation_about() {
+        let q = Quaternion::from_angle_axis(1.0, &Vector3d::new([0.5, 0.2, 1.0]));
+        let axis = Vector3d::unit_z();
+        let (closest, residual) = q.closest_rotation_about(&axis).unwrap();
+        assert_quat_approx_eq!(q, residual.before(&closest));
+
+        // No other rotation about the axis is closer.
+        let distance = q.angle_to(&closest);
+        for i in -10..=10 {
+            let nearby = Quaternion::from_angle_axis(closest.rotation_vector().data[2] + 0.01 * f64::from(i), &axis);
+            assert!(q.angle_to(&nearby) >= distance - 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn angle_to_is_sign_invariant() {
+        let q = Quaternion::from_angle_axis(0.2, &Vector3d::unit_y());
+        let r = Quaternion::from_angle_axis(0.2 + 2.0 * PI, &Vector3d::unit_y());
+        assert_float_approx_eq!(0.0, q.angle_to(&r));
+    }
+
+    #[test]
+    fn approx_eq_agrees_across_representations() {
+        let q = Quaternion::from_angle_axis(2.9, &Vector3d::new([1.0, -1.0, 2.0]));
+        for &delta in &[1.0e-7, 1.0e-3, 0.5] {
+            let r = q.multiply(&Quaternion::from_angle_axis(delta, &Vector3d::unit_x()));
+            for &tolerance in &[0.5 * delta, 2.0 * delta] {
+                let expected = tolerance > delta;
+                assert_eq!(expected, q.approx_eq(&r, tolerance));
+                assert_eq!(expected, q.approx_eq(&r.scale(-1.0), tolerance));
+                assert_eq!(expected, q.as_rotation_matrix().approx_eq(&r.as_rotation_matrix(), tolerance));
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_bits() {
+        let q = Quaternion::new(-0.0, Vector3d::new([0.0, -0.6, 0.8]));
+        let negated = Quaternion::new(0.0, Vector3d::new([-0.0, 0.6, -0.8]));
+        assert_eq!(q.canonical_bits(), negated.canonical_bits());
+        assert_eq!([0, 0, 0.6_f64.to_bits(), (-0.8_f64).to_bits()], q.canonical_bits());
+        let r = Quaternion::from_angle_axis(0.3, &Vector3d::unit_z());
+        assert_ne!(Quaternion::identity().canonical_bits(), r.canonical_bits());
+        let nan = Quaternion::new(f64::NAN, Vector3d::new([-f64::NAN, 0.0, 0.0]));
+        assert_eq!(nan.canonical_bits(), nan.scale(-1.0).canonical_bits());
+    }
+
+    #[test]
+    fn integrate_is_exact() {
+        let omega = Vector3d::new([0.3, -0.2, 0.9]);
+        let stepped = (0..1000).fold(Quaternion::identity(), |q, _| q.integrate(&omega, 0.001));
+        let expected = Quaternion::from_rotation_vector(&omega);
+        assert_quat_approx_eq!(expected, stepped, 1.0e-9);
+        assert_float_approx_eq!(1.0, stepped.norm(), 1.0e-12);
+    }
+
+    #[test]
+    fn integrate_zero_rate() {
+        let q = Quaternion::from_angle_axis(0.4, &Vector3d::unit_x());
+        assert_eq!(q, q.integrate(&Vector3d::zero(), 0.01));
+    }
+
     #[test]
     fn conjugate() {
         let q = Quaternion::new(0.2, Vector3d::new([0.3, 0.4, 0.5]));
@@ -437,18 +1116,106 @@ mod tests {
         assert_eq!(expected, q.conjugate());
     }
 
+    #[test]
+    fn validity_checks() {
+        let q = Quaternion::from_angle_axis(2.0, &Vector3d::new([1.0, 2.0, -1.0]));
+        assert!(q.is_finite() && q.is_normalized(1.0e-12));
+        assert!(!q.scale(1.0 + 1.0e-6).is_normalized(1.0e-9));
+        assert!(q.scale(1.0 + 1.0e-6).is_normalized(1.0e-5));
+        let nan = Quaternion::new(f64::NAN, Vector3d::zero());
+        assert!(!nan.is_finite() && !nan.is_normalized(1.0));
+
+        let r = q.as_rotation_matrix();
+        assert!(r.is_finite() && r.is_special_orthogonal(1.0e-12));
+        assert!(r.as_matrix3().is_finite());
+        let skewed = r.as_matrix3() + Matrix3::skew(&Vector3d::new([0.0, 1.0e-6, 0.0]));
+        assert!(!RotationMatrix::from_rows(skewed.rows()).is_special_orthogonal(1.0e-9));
+        let infinite = RotationMatrix::from_rows([
+            Vector3d::new([f64::INFINITY, 0.0, 0.0]), Vector3d::unit_y(), Vector3d::unit_z()
+        ]);
+        assert!(!infinite.is_finite() && !infinite.is_special_orthogonal(1.0));
+    }
+
     #[test]
     fn norm_squared() {
         let q = Quaternion::new(0.2, Vector3d::new([0.3, 0.4, 0.5]));
         assert_eq!(0.54, q.norm_squared());
     }
 
+    #[test]
+    fn in_place_operations_match_by_value() {
+        let mut q = Quaternion::new(0.5, Vector3d::new([1.0, -2.0, 0.3]));
+        let expected = q.normalized().unwrap();
+        q.normalize_in_place().unwrap();
+        assert_quat_approx_eq!(expected, q, 1.0e-15);
+        let mut v = Vector3d::new([0.3, -1.0, 2.0]);
+        let rotated = q.rotate_vector(&v);
+        q.rotate_vector_in_place(&mut v);
+        assert_vector_approx_eq!(rotated, v, 1.0e-15);
+        let mut zero = Quaternion::new(0.0, Vector3d::zero());
+        assert!(zero.normalize_in_place().is_err());
+    }
+
+    #[test]
+    fn array_conversions_are_scalar_first() {
+        let q = Quaternion::new(0.5, Vector3d::new([-0.5, 0.5, 0.5]));
+        assert_eq!(q, Quaternion::from([0.5, -0.5, 0.5, 0.5]));
+        let components: [f64; 4] = q.into();
+        assert_eq!([0.5, -0.5, 0.5, 0.5], components);
+    }
+
+    #[test]
+    fn batch_kernels_match_scalar() {
+        let lhs: Vec<Quaternion> = (0..11).map(|k| Quaternion::from_angle_axis(0.3 * f64::from(k), &Vector3d::new([1.0, f64::from(k), -2.0]))).collect();
+        let rhs: Vec<Quaternion> = (0..11).map(|k| Quaternion::from_angle_axis(-0.7 * f64::from(k), &Vector3d::new([f64::from(k), 0.5, 1.0]))).collect();
+        let mut out = vec![Quaternion::identity(); 11];
+        Quaternion::after_each(&lhs, &rhs, &mut out);
+        for ((a, b), product) in lhs.iter().zip(&rhs).zip(&out) {
+            assert_quat_approx_eq!(a.after(b), *product, 1.0e-15);
+        }
+
+        let mut scaled: Vec<Quaternion> = lhs.iter().zip(1..).map(|(q, k)| q.scale(0.5 * f64::from(k))).collect();
+        scaled[6] = Quaternion::new(0.0, Vector3d::zero());
+        assert!(Quaternion::normalize_each(&mut scaled).is_err());
+        for (k, (q, original)) in scaled.iter().zip(&lhs).enumerate() {
+            if k == 6 {
+                assert_eq!(Quaternion::new(0.0, Vector3d::zero()), *q);
+            } else {
+                assert_quat_approx_eq!(*original, *q, 1.0e-12);
+            }
+        }
+
+        let r = lhs[3].as_rotation_matrix();
+        let vs: Vec<Vector3d> = (0..7).map(|k| Vector3d::new([f64::from(k), -1.0, 0.5])).collect();
+        for (v, w) in vs.iter().zip(&r.rotate_vectors(&vs)) {
+            assert_vector_approx_eq!(r.rotate_vector(v), *w, 1.0e-15);
+        }
+    }
+
+    #[test]
+    fn parts_round_trip() {
+        let v = Vector3d::new([0.3, 0.4, 0.5]);
+        let q = Quaternion::from_parts(0.2, v);
+        assert_eq!(Quaternion::new(0.2, v), q);
+        assert_eq!((0.2, v), q.split());
+        // Conjugating a pure quaternion rotates its vector part.
+        let r = Quaternion::from_angle_axis(0.7, &Vector3d::new([1.0, -1.0, 0.5]));
+        let (scalar, rotated) = r.multiply(&Quaternion::pure(v)).multiply(&r.conjugate()).split();
+        assert_float_approx_eq!(0.0, scalar, 1.0e-12);
+        assert_vector_approx_eq!(r.rotate_vector(&v), rotated, 1.0e-12);
+    }
+
     #[test]
     fn identity() {
         let expected = Quaternion::new(1.0, Vector3d::zero());
         assert_eq!(expected, Quaternion::identity());
     }
 
+    #[test]
+    fn default_is_identity() {
+        assert_eq!(Quaternion::identity(), Quaternion::default());
+    }
+
     #[test]
     fn angle_axis() {
         let theta = 0.03;
@@ -460,6 +1227,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn zero_angle_axis() {
         let q = Quaternion::new(0.0, Vector3d::zero());
         let (angle, axis) = q.angle_axis();
@@ -468,6 +1236,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::cast_lossless, clippy::unnecessary_cast)]
     fn inverse() {
         let sqrt2 = (2 as f64).sqrt() / 2.0;
         let q = Quaternion::new(sqrt2, Vector3d::new([sqrt2, 0.0, 0.0]));
@@ -476,6 +1245,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn zero_inverse() {
         let zero = Quaternion::new(0.0, Vector3d::new([0.0, 0.0, 0.0]));
         match zero.inverse() {
@@ -490,6 +1260,15 @@ mod tests {
         assert_eq!(Quaternion::identity(), r.as_quaternion());
     }
 
+    #[test]
+    fn as_rotation_matrix() {
+        let q = Quaternion::from_angle_axis(PI / 2.0, &Vector3d::unit_z());
+        let r = q.as_rotation_matrix();
+        assert_vector_approx_eq!(Vector3d::unit_y(), r.rotate_vector(&Vector3d::unit_x()));
+        assert_vector_approx_eq!(Vector3d::unit_x().negate(), r.rotate_vector(&Vector3d::unit_y()));
+        assert_vector_approx_eq!(Vector3d::unit_z(), r.rotate_vector(&Vector3d::unit_z()));
+    }
+
     #[test]
     fn multiply() {
         let q = Quaternion::identity();
@@ -498,6 +1277,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn before() {
         let angle = PI / 2.0;
         let q = Quaternion::from_angle_axis(angle, &Vector3d::x());
@@ -508,6 +1288,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn after() {
         let angle = PI / 2.0;
         let q = Quaternion::from_angle_axis(angle, &Vector3d::x());
@@ -518,6 +1299,55 @@ mod tests {
     }
 
     #[test]
+    fn after_does_not_invert() {
+        // Composing with a degenerate operand stays finite, as `before`
+        // does, instead of dividing by its norm.
+        let q = Quaternion::from_angle_axis(0.4, &Vector3d::unit_y());
+        let zero = Quaternion::new(0.0, Vector3d::zero());
+        assert_eq!(zero, q.after(&zero));
+        assert_eq!(zero, zero.after(&q));
+        let m = q.as_rotation_matrix();
+        assert!(q.after(&m).angle_to(&q.multiply(&q)) < 1.0e-12);
+    }
+
+    #[test]
+    fn rotate_vector_inverse_matches_inverse() {
+        let q = Quaternion::from_angle_axis(2.2, &Vector3d::new([-1.0, 0.5, 2.0]));
+        let v = Vector3d::new([3.0, -1.0, 0.25]);
+        let expected = q.inverse_unchecked().rotate_vector(&v);
+        assert_vector_approx_eq!(expected, q.rotate_vector_inverse(&v), 1.0e-12);
+        assert_vector_approx_eq!(expected, q.as_rotation_matrix().rotate_vector_inverse(&v), 1.0e-12);
+        if cfg!(not(feature = "checked")) {
+            // Slight drift from unit norm does not scale the result.
+            let drifted = q.scale(1.001);
+            assert_vector_approx_eq!(drifted.inverse_unchecked().rotate_vector(&v),
+                                     drifted.rotate_vector_inverse(&v), 1.0e-12);
+            assert_vector_approx_eq!(q.rotate_vector(&v), drifted.rotate_vector(&v), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn batch_rotation_matches_single() {
+        let q = Quaternion::from_angle_axis(1.7, &Vector3d::new([0.2, -1.0, 0.6]));
+        for n in 0..8 {
+            let vs: Vec<Vector3d> = (0..n).map(|k| Vector3d::new([f64::from(k), 1.0, -0.5 * f64::from(k)])).collect();
+            let rotated = q.rotate_vectors(&vs);
+            assert_eq!(vs.len(), rotated.len());
+            for (v, w) in vs.iter().zip(&rotated) {
+                assert_vector_approx_eq!(q.rotate_vector(v), *w, 1.0e-12);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Input and output lengths differ")]
+    fn batch_rotation_checks_lengths() {
+        let mut out = [Vector3d::ZERO; 2];
+        Quaternion::identity().rotate_vectors_into(&[Vector3d::unit_x()], &mut out);
+    }
+
+    #[test]
+    #[allow(deprecated)]
     fn rotate_x_90degrees_about_z_equals_y() {
         let angle = PI / 2.0;
         let q = Quaternion::from_angle_axis(angle, &Vector3d::z());
@@ -525,6 +1355,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn rotate_y_90degrees_about_z_equals_negative_x() {
         let angle = PI / 2.0;
         let q = Quaternion::from_angle_axis(angle, &Vector3d::z());
@@ -532,6 +1363,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn rotate_x_30degrees_about_z_equals_in_first_quadrant() {
         let angle = PI / 6.0;
         let q = Quaternion::from_angle_axis(angle, &Vector3d::z());
@@ -540,9 +1372,40 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn rotate_y_90degrees_about_x_equals_z() {
         let angle = PI / 2.0;
         let q = Quaternion::from_angle_axis(angle, &Vector3d::x());
         assert_vector_approx_eq!(Vector3d::z(), q.rotate_vector(&Vector3d::y()));
     }
+
+    #[test]
+    fn rotate_about_unit_axes() {
+        let angle = PI / 2.0;
+        let q = Quaternion::from_angle_axis(angle, &Vector3d::unit_z());
+        assert_vector_approx_eq!(Vector3d::unit_y(), q.rotate_vector(&Vector3d::unit_x()));
+        let q = Quaternion::from_angle_axis(angle, &Vector3d::unit_x());
+        assert_vector_approx_eq!(Vector3d::unit_z(), q.rotate_vector(&Vector3d::unit_y()));
+        let q = Quaternion::from_angle_axis(angle, &Vector3d::unit_y());
+        assert_vector_approx_eq!(Vector3d::unit_x(), q.rotate_vector(&Vector3d::unit_z()));
+    }
+
+    #[test]
+    fn error_vector_round_trips() {
+        let v = Vector3d::new([0.4, -0.9, 0.3]);
+        for &map in &[ErrorVectorMap::TwiceImaginary, ErrorVectorMap::RotationVector] {
+            let q = Quaternion::from_error_vector(&v, map).unwrap();
+            assert_vector_approx_eq!(v, q.to_error_vector(map), 1.0e-12);
+            assert_vector_approx_eq!(v, q.scale(-1.0).to_error_vector(map), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn error_vector_accuracy() {
+        // The small-angle map is 1% short at about 28 degrees.
+        let q = Quaternion::from_angle_axis(0.49, &Vector3d::unit_z());
+        let small = q.to_error_vector(ErrorVectorMap::TwiceImaginary).norm();
+        assert_float_approx_eq!(0.49, q.to_error_vector(ErrorVectorMap::RotationVector).norm(), 1.0e-12);
+        assert_float_approx_eq!(0.99, small / 0.49, 1.0e-3);
+    }
 }
@@ -0,0 +1,222 @@
+use std::f64::consts::PI;
+use rand::Rng;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::error::OrientationError;
+
+/// Draw a standard normal variate using the Box-Muller transform.
+pub(crate) fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    // gen() is in [0, 1), so 1 - u1 is in (0, 1] and the log is finite.
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+/// Draw a vector of independent standard normal variates.
+pub(crate) fn standard_normal_vector<R: Rng + ?Sized>(rng: &mut R) -> Vector3d {
+    Vector3d::new([standard_normal(rng), standard_normal(rng), standard_normal(rng)])
+}
+
+impl Vector3d {
+    /// Draw a unit vector uniformly distributed over the sphere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let v = Vector3d::random_unit(&mut rand::thread_rng());
+    /// assert!((v.norm() - 1.0).abs() < 1.0e-12);
+    /// ```
+    pub fn random_unit<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        cap_sample(rng, -1.0)
+    }
+
+    /// Draw a unit vector uniformly distributed over the spherical cap of
+    /// directions within `half_angle` radians of `axis`. A half angle of
+    /// `pi` covers the whole sphere.
+    ///
+    /// # Errors
+    /// Returns an error if `axis` has norm close to zero or `half_angle`
+    /// is not between 0 and `pi`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let axis = Vector3d::new([1.0, 1.0, 0.0]);
+    /// let v = Vector3d::random_in_cap(&mut rand::thread_rng(), &axis, 0.1).unwrap();
+    /// assert!(v.dot(&axis.normalized().unwrap()) >= 0.1_f64.cos() - 1.0e-12);
+    /// ```
+    pub fn random_in_cap<R: Rng + ?Sized>(rng: &mut R, axis: &Self, half_angle: f64) -> Result<Self, OrientationError> {
+        if !(0.0..=PI).contains(&half_angle) {
+            return Err(OrientationError::InvalidParameter("cap half angle must be between 0 and pi"))
+        }
+        let to_axis = Quaternion::from_two_vectors(&Self::unit_z(), axis)?;
+        Ok(to_axis.rotate_vector(&cap_sample(rng, half_angle.cos())))
+    }
+}
+
+impl Quaternion {
+    /// Draw a rotation perturbed from `mean` by a small random body-frame
+    /// rotation whose rotation vector has independent normal components,
+    /// each with standard deviation `std_dev` radians. This is the
+    /// isotropic case of `OrientationCovariance::sample`.
+    ///
+    /// # Errors
+    /// Returns an error if `std_dev` is negative or not finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let truth = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x());
+    /// let noisy = Quaternion::random_perturbation(&mut rand::thread_rng(), &truth, 0.01).unwrap();
+    /// ```
+    pub fn random_perturbation<R: Rng + ?Sized>(rng: &mut R, mean: &Self, std_dev: f64) -> Result<Self, OrientationError> {
+        if !(std_dev >= 0.0 && std_dev.is_finite()) {
+            return Err(OrientationError::InvalidParameter("standard deviation must be non-negative and finite"))
+        }
+        let delta = standard_normal_vector(rng).scalar_multiple(std_dev);
+        Ok(Self::from_rotation_vector(&delta).before(mean))
+    }
+
+    /// Draw a rotation uniformly distributed over the rotations within
+    /// `max_angle` radians of `mean`, uniform in the sense of the Haar
+    /// measure, so a maximum angle of `pi` draws from all rotations. The
+    /// perturbation is in the body frame.
+    ///
+    /// # Errors
+    /// Returns an error if `max_angle` is not between 0 and `pi`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mean = Quaternion::identity();
+    /// let q = Quaternion::random_within_angle(&mut rand::thread_rng(), &mean, 0.1).unwrap();
+    /// assert!(q.angle_to(&mean) <= 0.1 + 1.0e-12);
+    /// ```
+    pub fn random_within_angle<R: Rng + ?Sized>(rng: &mut R, mean: &Self, max_angle: f64) -> Result<Self, OrientationError> {
+        if !(0.0..=PI).contains(&max_angle) {
+            return Err(OrientationError::InvalidParameter("maximum angle must be between 0 and pi"))
+        }
+        // The Haar measure has density proportional to sin^2(angle / 2)
+        // in the rotation angle; draw it by rejection against the
+        // largest value on [0, max_angle].
+        let bound = (max_angle / 2.0).sin().powi(2);
+        let angle = loop {
+            let angle = max_angle * rng.gen::<f64>();
+            if bound * rng.gen::<f64>() <= (angle / 2.0).sin().powi(2) {
+                break angle
+            }
+        };
+        let axis = Vector3d::random_unit(rng);
+        Ok(Self::from_angle_axis(angle, &axis).before(mean))
+    }
+}
+
+/// Draw a unit vector uniformly from the cap about z with z-component at
+/// least `min_z`. By Archimedes' hat-box theorem, z is uniform on
+/// `[min_z, 1]` for a uniform distribution over the cap.
+fn cap_sample<R: Rng + ?Sized>(rng: &mut R, min_z: f64) -> Vector3d {
+    let z = 1.0 - (1.0 - min_z) * rng.gen::<f64>();
+    let azimuth = 2.0 * PI * rng.gen::<f64>();
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    Vector3d::new([r * azimuth.cos(), r * azimuth.sin(), z])
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn unit_vectors_are_uniform() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let n = 20000;
+        let samples: Vec<Vector3d> = (0..n).map(|_| Vector3d::random_unit(&mut rng)).collect();
+        let mut mean = Vector3d::zero();
+        let mut upper = 0;
+        for v in &samples {
+            assert_float_approx_eq!(1.0, v.norm(), 1.0e-12);
+            mean += v.scalar_multiple(1.0 / f64::from(n));
+            if v.data[2] > 0.5 {
+                upper += 1;
+            }
+        }
+        assert!(mean.norm() < 0.03);
+        // The cap above z = 0.5 is a quarter of the sphere's area.
+        assert_float_approx_eq!(0.25, f64::from(upper) / f64::from(n), 0.01);
+    }
+
+    #[test]
+    fn cap_samples_stay_in_cap() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let axis = Vector3d::new([-1.0, 2.0, 0.5]);
+        let unit_axis = axis.normalized().unwrap();
+        let half_angle = 0.4;
+        let n = 20000;
+        let mut mean_cos = 0.0;
+        for _ in 0..n {
+            let v = Vector3d::random_in_cap(&mut rng, &axis, half_angle).unwrap();
+            assert_float_approx_eq!(1.0, v.norm(), 1.0e-12);
+            let cos = v.dot(&unit_axis);
+            assert!(cos >= half_angle.cos() - 1.0e-12);
+            mean_cos += cos / f64::from(n);
+        }
+        // The cosine to the axis is uniform over [cos(half_angle), 1].
+        assert_float_approx_eq!(0.5 + 0.5 * half_angle.cos(), mean_cos, 1.0e-3);
+    }
+
+    #[test]
+    fn perturbation_spread() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let mean = Quaternion::from_angle_axis(1.2, &Vector3d::new([0.4, -1.0, 0.2]));
+        let std_dev = 0.05;
+        let n = 20000;
+        let mut mean_square = 0.0;
+        for _ in 0..n {
+            let q = Quaternion::random_perturbation(&mut rng, &mean, std_dev).unwrap();
+            mean_square += q.angle_to(&mean).powi(2) / f64::from(n);
+        }
+        // The squared angle sums three squared normal components.
+        assert_float_approx_eq!(3.0 * std_dev * std_dev, mean_square, 1.0e-4);
+        assert!(Quaternion::random_perturbation(&mut rng, &mean, 0.0).unwrap().angle_to(&mean) < 1.0e-12);
+        assert!(Quaternion::random_perturbation(&mut rng, &mean, -1.0).is_err());
+    }
+
+    #[test]
+    fn within_angle_matches_haar_measure() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mean = Quaternion::from_angle_axis(-0.7, &Vector3d::unit_y());
+        let n = 20000;
+        let mut small = 0;
+        for _ in 0..n {
+            let q = Quaternion::random_within_angle(&mut rng, &mean, PI).unwrap();
+            if q.angle_to(&mean) < PI / 2.0 {
+                small += 1;
+            }
+        }
+        // Under the Haar measure, the angle has CDF (t - sin t) / pi.
+        let expected = (PI / 2.0 - 1.0) / PI;
+        assert_float_approx_eq!(expected, f64::from(small) / f64::from(n), 0.01);
+
+        for _ in 0..100 {
+            let q = Quaternion::random_within_angle(&mut rng, &mean, 0.2).unwrap();
+            assert!(q.angle_to(&mean) <= 0.2 + 1.0e-12);
+        }
+        assert!(Quaternion::random_within_angle(&mut rng, &mean, 0.0).unwrap().angle_to(&mean) < 1.0e-12);
+        assert!(Quaternion::random_within_angle(&mut rng, &mean, 3.5).is_err());
+    }
+
+    #[test]
+    fn invalid_cap() {
+        let mut rng = StdRng::seed_from_u64(9);
+        assert!(Vector3d::random_in_cap(&mut rng, &Vector3d::zero(), 0.5).is_err());
+        assert!(Vector3d::random_in_cap(&mut rng, &Vector3d::unit_z(), -0.1).is_err());
+        assert!(Vector3d::random_in_cap(&mut rng, &Vector3d::unit_z(), 4.0).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::quaternion::Quaternion;
+use crate::error::OrientationError;
+
+/// How a resampler fills in orientations between input samples.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Take the nearer of the two neighboring samples.
+    Nearest,
+    /// Interpolate along the shorter arc between the two neighboring
+    /// samples.
+    Slerp
+}
+
+/// An orientation sequence resampled at a fixed rate.
+#[derive(Clone, Debug)]
+pub struct Resampled {
+    /// The resampled (time, orientation) pairs. Times falling inside a
+    /// gap are omitted.
+    pub samples: Vec<(f64, Quaternion)>,
+    /// The start and end times of every input interval longer than the
+    /// maximum gap.
+    pub gaps: Vec<(f64, f64)>
+}
+
+/// Resamples irregularly timestamped orientation sequences at a fixed
+/// rate, for example to synchronize camera and IMU logs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Resampler {
+    rate: f64,
+    max_gap: f64,
+    interpolation: Interpolation
+}
+
+impl Resampler {
+    /// Create a resampler emitting `rate` samples per second, using
+    /// slerp and bridging gaps of any length.
+    ///
+    /// # Panics
+    /// Panics if the rate is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let resampler = Resampler::new(100.0).with_max_gap(0.05);
+    /// ```
+    #[must_use]
+    pub fn new(rate: f64) -> Self {
+        assert!(rate > 0.0 && rate.is_finite(), "Rate must be positive and finite");
+        Self {
+            rate,
+            max_gap: f64::INFINITY,
+            interpolation: Interpolation::Slerp
+        }
+    }
+
+    /// Set the longest interval between input samples, in seconds, that
+    /// is interpolated across. Output times inside longer intervals are
+    /// dropped and the interval reported as a gap.
+    ///
+    /// # Panics
+    /// Panics if the maximum gap is negative or NaN; infinity bridges
+    /// every gap.
+    #[must_use]
+    pub fn with_max_gap(mut self, max_gap: f64) -> Self {
+        assert!(max_gap >= 0.0, "Maximum gap must be non-negative");
+        self.max_gap = max_gap;
+        self
+    }
+
+    /// Set the interpolation method.
+    #[must_use]
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Resample a sequence, starting at its first timestamp and ending
+    /// at or before its last.
+    ///
+    /// # Errors
+    /// Returns an error if the sequence is empty, or its timestamps are
+    /// not finite or not strictly increasing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let samples = [
+    ///     (0.0, Quaternion::identity()),
+    ///     (0.013, Quaternion::from_angle_axis(0.013, &Vector3d::unit_z())),
+    ///     (0.031, Quaternion::from_angle_axis(0.031, &Vector3d::unit_z())),
+    /// ];
+    /// let resampled = Resampler::new(100.0).resample(&samples).unwrap();
+    /// assert_eq!(4, resampled.samples.len());
+    /// assert!(resampled.gaps.is_empty());
+    /// ```
+    #[allow(clippy::cast_precision_loss)]
+    pub fn resample(&self, samples: &[(f64, Quaternion)]) -> Result<Resampled, OrientationError> {
+        let (first, last) = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return Err(OrientationError::InsufficientData("cannot resample an empty sequence"))
+        };
+        if samples.iter().any(|(time, _)| !time.is_finite()) {
+            return Err(OrientationError::NotFinite)
+        }
+        if samples.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(OrientationError::NotIncreasing)
+        }
+
+        let gaps = samples
+            .windows(2)
+            .filter(|pair| pair[1].0 - pair[0].0 > self.max_gap)
+            .map(|pair| (pair[0].0, pair[1].0))
+            .collect();
+
+        let mut output = Vec::new();
+        let mut index = 0;
+        for k in 0_u64.. {
+            // Compute each time afresh rather than accumulating, so
+            // rounding does not drift over long logs.
+            let time = first + k as f64 / self.rate;
+            if time > last {
+                break
+            }
+            while index + 1 < samples.len() && samples[index + 1].0 <= time {
+                index += 1;
+            }
+
+            let (t0, q0) = samples[index];
+            let (t1, q1) = samples.get(index + 1).copied().unwrap_or((t0, q0));
+            // Samples landing exactly on an input time are kept even at
+            // the edge of a gap.
+            if t1 - t0 > self.max_gap && time > t0 {
+                continue
+            }
+            let fraction = if t1 > t0 { (time - t0) / (t1 - t0) } else { 0.0 };
+            let q = match self.interpolation {
+                Interpolation::Nearest => if fraction < 0.5 { q0 } else { q1 },
+                Interpolation::Slerp => q0.slerp(&q1, fraction)
+            };
+            output.push((time, q));
+        }
+
+        Ok(Resampled {
+            samples: output,
+            gaps
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector3d::Vector3d;
+    use crate::rotation::Rotation;
+
+    fn spinning(t: f64) -> Quaternion {
+        Quaternion::from_angle_axis(2.0 * t, &Vector3d::new([0.0, 1.0, 1.0]))
+    }
+
+    #[test]
+    fn slerp_is_exact_for_constant_rate() {
+        let times = [0.0, 0.004, 0.011, 0.019, 0.02, 0.033, 0.05];
+        let samples: Vec<(f64, Quaternion)> = times.iter().map(|&t| (t, spinning(t))).collect();
+        let resampled = Resampler::new(200.0).resample(&samples).unwrap();
+
+        assert_eq!(11, resampled.samples.len());
+        for (t, q) in resampled.samples {
+            assert_quat_approx_eq!(spinning(t), q, 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn nearest_neighbor() {
+        let samples = [(0.0, spinning(0.0)), (1.0, spinning(1.0))];
+        let resampled = Resampler::new(4.0)
+            .with_interpolation(Interpolation::Nearest)
+            .resample(&samples)
+            .unwrap();
+        let picked: Vec<Quaternion> = resampled.samples.iter().map(|s| s.1).collect();
+        assert_eq!(vec![samples[0].1, samples[0].1, samples[1].1, samples[1].1, samples[1].1], picked);
+    }
+
+    #[test]
+    fn gaps_are_reported_and_skipped() {
+        let times = [0.0, 0.1, 0.2, 0.6, 0.7];
+        let samples: Vec<(f64, Quaternion)> = times.iter().map(|&t| (t, spinning(t))).collect();
+        let resampled = Resampler::new(10.0).with_max_gap(0.15).resample(&samples).unwrap();
+
+        assert_eq!(vec![(0.2, 0.6)], resampled.gaps);
+        let kept: Vec<f64> = resampled.samples.iter().map(|s| (s.0 * 10.0).round()).collect();
+        assert_eq!(vec![0.0, 1.0, 2.0, 6.0, 7.0], kept);
+    }
+
+    #[test]
+    fn invalid_sequences() {
+        let resampler = Resampler::new(10.0);
+        assert!(resampler.resample(&[]).is_err());
+        let q = Quaternion::identity();
+        assert!(resampler.resample(&[(1.0, q), (0.5, q)]).is_err());
+        // Non-finite times would otherwise never reach the end.
+        assert_eq!(OrientationError::NotFinite, resampler.resample(&[(0.0, q), (f64::NAN, q)]).unwrap_err());
+        assert_eq!(OrientationError::NotFinite, resampler.resample(&[(f64::NEG_INFINITY, q), (1.0, q)]).unwrap_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Maximum gap must be non-negative")]
+    fn nan_max_gap() {
+        let _ = Resampler::new(10.0).with_max_gap(f64::NAN);
+    }
+}
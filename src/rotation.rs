@@ -1,23 +1,41 @@
 use crate::vector3d::Vector3d;
 use crate::quaternion::Quaternion;
 use crate::rotation_matrix::RotationMatrix;
+use crate::matrix3::Matrix3;
+use crate::error::OrientationError;
+use crate::units::{Radians, Degrees};
 
 /// Rotation trait
+///
+/// A rotation takes vectors expressed in a body frame into a reference
+/// (world) frame. Rotations are combined with `before` and `after`:
+/// `a.before(&b)` rotates by `a` and then by `b`, and `a.after(&b)`
+/// rotates by `b` and then by `a`, so `a.before(&b)` and `b.after(&a)`
+/// are the same rotation.
+///
+/// Composition may mix representations: the other operand is converted
+/// as needed, and the result is always in the representation of the
+/// receiver, `Self::Output`. Put the representation you want on the
+/// left.
 pub trait Rotation {
-    /// The representation of the Rotation.
-    /// Every implementor must specify this type. Generally, it will
-    /// be the same type as the implementor. For example, a Quaternion
-    /// will specify type R = Quaternion.
-    type R: Rotation;
+    /// The representation produced by composing, inverting or
+    /// integrating this rotation. For each type in this crate it is
+    /// the type itself.
+    type Output: Rotation;
 
     /// The identity rotation equivalent to no rotation at all.
-    fn identity() -> Self::R;
+    fn identity() -> Self::Output;
 
     /// The inverse of a rotation.
-    fn inverse(&self) -> Result<Self::R, String>;
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rotation is degenerate and cannot be
+    /// inverted.
+    fn inverse(&self) -> Result<Self::Output, OrientationError>;
 
     /// The inverse of a rotation.
-    fn inverse_unchecked(&self) -> Self::R;
+    fn inverse_unchecked(&self) -> Self::Output;
 
     /// Get the quaternion representation of a rotation.
     fn as_quaternion(&self) -> Quaternion;
@@ -28,16 +46,180 @@ pub trait Rotation {
     /// Get the angle and axis associated with a rotation.
     fn angle_axis(&self) -> (f64, Vector3d);
 
-    /// Compose two rotations.
-    fn before<T: Rotation<R = T>>(&self, r: &T) -> T;
+    /// Get the angle, in degrees, and axis associated with a rotation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(Degrees(30.0), &Vector3d::unit_y());
+    /// let (angle, _) = q.angle_axis_deg();
+    /// assert!((angle.0 - 30.0).abs() < 1.0e-12);
+    /// ```
+    fn angle_axis_deg(&self) -> (Degrees, Vector3d) {
+        let (angle, axis) = self.angle_axis();
+        (Radians(angle).into(), axis)
+    }
 
-    /// Compose two rotations.
-    fn after<T: Rotation<R = T>>(&self, r: &T) -> T;
+    /// The rotation by `self` followed by the rotation by `r`. As a
+    /// product this is `r * self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let quarter = std::f64::consts::FRAC_PI_2;
+    /// let about_z = Quaternion::from_angle_axis(quarter, &Vector3d::unit_z());
+    /// let about_x = Quaternion::from_angle_axis(quarter, &Vector3d::unit_x()).as_rotation_matrix();
+    /// // x goes to y, which then goes to z. The result is a quaternion,
+    /// // like the receiver.
+    /// let q: Quaternion = about_z.before(&about_x);
+    /// assert!((q.rotate_vector(&Vector3d::unit_x()) - Vector3d::unit_z()).norm() < 1.0e-12);
+    /// ```
+    fn before<T: Rotation>(&self, r: &T) -> Self::Output;
 
-    /// Convenience function; should not be used.
-    fn multiply<T: Rotation>(&self, r: &T) -> Self::R;
+    /// The rotation by `r` followed by the rotation by `self`. As a
+    /// product this is `self * r`; in particular, composing a
+    /// body-to-world rotation with a body-frame increment is
+    /// `attitude.after(&increment)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let a = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x());
+    /// let b = Quaternion::from_angle_axis(0.5, &Vector3d::unit_y());
+    /// assert!(a.before(&b).angle_to(&b.after(&a)) < 1.0e-12);
+    /// ```
+    fn after<T: Rotation>(&self, r: &T) -> Self::Output;
 
     /// Rotate a vector
     fn rotate_vector(&self, v: &Vector3d) -> Vector3d;
+
+    /// Rotate a vector, overwriting it with the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(std::f64::consts::FRAC_PI_2, &Vector3d::unit_z());
+    /// let mut v = Vector3d::unit_x();
+    /// q.rotate_vector_in_place(&mut v);
+    /// assert!((v - Vector3d::unit_y()).norm() < 1.0e-12);
+    /// ```
+    fn rotate_vector_in_place(&self, v: &mut Vector3d) {
+        *v = self.rotate_vector(v);
+    }
+
+    /// Rotate a vector by the inverse rotation, taking world-frame
+    /// vectors into the body frame, without forming the inverse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.8, &Vector3d::new([1.0, 2.0, -1.0]));
+    /// let v = Vector3d::new([0.5, -1.0, 2.0]);
+    /// let expected = q.inverse_unchecked().rotate_vector(&v);
+    /// assert!((q.rotate_vector_inverse(&v) - expected).norm() < 1.0e-12);
+    /// ```
+    fn rotate_vector_inverse(&self, v: &Vector3d) -> Vector3d;
+
+    /// Rotate each of a batch of vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(std::f64::consts::FRAC_PI_2, &Vector3d::unit_z());
+    /// let rotated = q.rotate_vectors(&[Vector3d::unit_x(), Vector3d::unit_y()]);
+    /// assert!((rotated[0] - Vector3d::unit_y()).norm() < 1.0e-12);
+    /// assert!((rotated[1] + Vector3d::unit_x()).norm() < 1.0e-12);
+    /// ```
+    fn rotate_vectors(&self, vs: &[Vector3d]) -> Vec<Vector3d> {
+        let mut rotated = vec![Vector3d::ZERO; vs.len()];
+        self.rotate_vectors_into(vs, &mut rotated);
+        rotated
+    }
+
+    /// Rotate each of a batch of vectors into a caller-provided slice,
+    /// so repeated batches need no allocation.
+    ///
+    /// # Panics
+    /// Panics if `vs` and `rotated` differ in length.
+    fn rotate_vectors_into(&self, vs: &[Vector3d], rotated: &mut [Vector3d]) {
+        assert_eq!(vs.len(), rotated.len(), "Input and output lengths differ");
+        for (v, out) in vs.iter().zip(rotated.iter_mut()) {
+            *out = self.rotate_vector(v);
+        }
+    }
+
+    /// Integrate a constant body-frame angular velocity `omega` (in
+    /// radians per second) over `dt` seconds, returning the rotation
+    /// followed by the increment. The increment `exp(omega * dt)` is
+    /// applied exactly rather than as an Euler step on the components,
+    /// so unit norm is preserved and zero angular velocity needs no
+    /// special case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let omega = Vector3d::unit_z().scalar_multiple(std::f64::consts::PI);
+    /// let q = Quaternion::identity().integrate(&omega, 0.5);
+    /// let v = q.rotate_vector(&Vector3d::unit_x());
+    /// assert!((v - Vector3d::unit_y()).norm() < 1.0e-12);
+    /// ```
+    fn integrate(&self, omega: &Vector3d, dt: f64) -> Self::Output {
+        self.after(&Quaternion::from_rotation_vector(&omega.scalar_multiple(dt)))
+    }
+
+    /// Express a tensor given in the rotated (body) frame in the
+    /// reference (world) frame, `R * J * R^T`. This carries inertia
+    /// tensors and covariance matrices between frames; use the inverse
+    /// rotation to go the other way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// // A rod along the body x axis, turned a quarter turn about z.
+    /// let inertia = Matrix3::from_diagonal(&Vector3d::new([0.0, 1.0, 1.0]));
+    /// let q = Quaternion::from_angle_axis(std::f64::consts::FRAC_PI_2, &Vector3d::unit_z());
+    /// let world = q.rotate_tensor(&inertia);
+    /// assert!(world.data[1][1].abs() < 1.0e-12);
+    /// assert!((world.data[0][0] - 1.0).abs() < 1.0e-12);
+    /// ```
+    fn rotate_tensor(&self, tensor: &Matrix3) -> Matrix3 {
+        let r = self.as_rotation_matrix().as_matrix3();
+        r.multiply(tensor).multiply(&r.transpose())
+    }
 }
 
+/// Compose a sequence of rotations left to right: the result is the
+/// product `r1 * r2 * ... * rn`, so `rn` acts on a vector first and `r1`
+/// last. Each rotation is taken to be relative to the one before it, as
+/// the joint rotations along a kinematic chain from base to tip are, and
+/// the result takes the tip frame to the base frame. An empty sequence
+/// gives the identity.
+///
+/// The `Product` impls do the same fold, so
+/// `rotations.iter().product::<Quaternion>()` is equivalent.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let shoulder = Quaternion::from_angle_axis(0.5, &Vector3d::unit_z());
+/// let elbow = Quaternion::from_angle_axis(-0.2, &Vector3d::unit_y());
+/// let chain: Quaternion = compose_all(&[shoulder, elbow]);
+/// assert!(chain.angle_to(&shoulder.after(&elbow)) < 1.0e-12);
+/// assert!(chain.angle_to(&[shoulder, elbow].iter().product()) < 1.0e-12);
+/// ```
+pub fn compose_all<'a, R, I>(rotations: I) -> R
+where
+    R: Rotation<Output = R> + 'a,
+    I: IntoIterator<Item = &'a R>
+{
+    rotations.into_iter().fold(R::identity(), |total, r| total.after(r))
+}
@@ -1,7 +1,11 @@
 use std::fmt;
+use std::iter::Product;
 use crate::vector3d::Vector3d;
-use crate::rotation::Rotation;
+use crate::rotation::{Rotation, compose_all};
 use crate::quaternion::Quaternion;
+use crate::matrix3::Matrix3;
+use crate::error::OrientationError;
+use crate::checked;
 
 /// A rotation matrix
 #[derive(Copy, Clone, PartialEq)]
@@ -10,12 +14,17 @@ pub struct RotationMatrix {
 }
 
 impl RotationMatrix {
-    /// Create a new RotationMatrix from rows.
-    pub fn from_rows(rows: [Vector3d; 3]) -> Self {
+    /// The identity rotation, usable in constant expressions.
+    pub const IDENTITY: Self = Self::from_rows([Vector3d::X, Vector3d::Y, Vector3d::Z]);
+
+    /// Create a new `RotationMatrix` from rows.
+    #[must_use]
+    pub const fn from_rows(rows: [Vector3d; 3]) -> Self {
         Self {rows}
     }
 
-    /// Create a new RotationMatrix from columns.
+    /// Create a new `RotationMatrix` from columns.
+    #[must_use]
     pub fn from_columns(columns: [Vector3d; 3]) -> Self {
         let r11 = columns[0].data[0];
         let r21 = columns[0].data[1];
@@ -33,9 +42,88 @@ impl RotationMatrix {
         RotationMatrix::from_rows([r1, r2, r3])
     }
 
+    /// Get the general 3x3 matrix with the same entries.
+    #[must_use]
+    pub fn as_matrix3(&self) -> Matrix3 {
+        Matrix3::from_rows(self.rows)
+    }
+
+    /// Whether two rotation matrices represent rotations within
+    /// `tolerance` radians of each other, matching
+    /// `Quaternion::approx_eq`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.5, &Vector3d::unit_y());
+    /// let r = Quaternion::from_angle_axis(0.5 + 1.0e-9, &Vector3d::unit_y());
+    /// assert!(q.as_rotation_matrix().approx_eq(&r.as_rotation_matrix(), 1.0e-8));
+    /// ```
+    #[must_use]
+    pub fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        // The Frobenius distance between rotations an angle theta apart
+        // is 2 sqrt(2) sin(theta / 2), which stays accurate for small
+        // angles where the trace does not.
+        let difference = self.as_matrix3() - other.as_matrix3();
+        let distance = difference.rows().iter().map(Vector3d::norm_squared).sum::<f64>().sqrt();
+        2.0 * (distance / 8.0_f64.sqrt()).min(1.0).asin() <= tolerance
+    }
+
+    /// Whether every entry is finite, neither infinite nor NaN.
+    #[must_use]
+    pub fn is_finite(&self) -> bool {
+        self.rows.iter().all(Vector3d::is_finite)
+    }
+
+    /// Whether the matrix is a proper rotation to within `tolerance`:
+    /// every entry of `R^T R - I`, and the determinant less one, are at
+    /// most `tolerance` in magnitude. Non-finite matrices fail.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let r = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x()).as_rotation_matrix();
+    /// assert!(r.is_special_orthogonal(1.0e-12));
+    /// // A reflection is orthogonal but not special.
+    /// let flip = RotationMatrix::from_rows([Vector3d::unit_x(), Vector3d::unit_y(), -Vector3d::unit_z()]);
+    /// assert!(!flip.is_special_orthogonal(1.0e-12));
+    /// ```
+    #[must_use]
+    pub fn is_special_orthogonal(&self, tolerance: f64) -> bool {
+        let m = self.as_matrix3();
+        let gram = m.transpose().multiply(&m) - Matrix3::identity();
+        gram.data.iter().flatten().all(|x| x.abs() <= tolerance)
+            && (m.determinant() - 1.0).abs() <= tolerance
+    }
+
+    /// The matrix product `self * other`; composition goes through
+    /// `before` and `after`.
+    pub(crate) fn multiply(&self, other: &Self) -> Self {
+        let rows = self.rows();
+        let cols = other.columns();
+
+        let r11 = rows[0].dot(&cols[0]);
+        let r12 = rows[0].dot(&cols[1]);
+        let r13 = rows[0].dot(&cols[2]);
+        let r21 = rows[1].dot(&cols[0]);
+        let r22 = rows[1].dot(&cols[1]);
+        let r23 = rows[1].dot(&cols[2]);
+        let r31 = rows[2].dot(&cols[0]);
+        let r32 = rows[2].dot(&cols[1]);
+        let r33 = rows[2].dot(&cols[2]);
+
+        let r1 = Vector3d::new([r11, r12, r13]);
+        let r2 = Vector3d::new([r21, r22, r23]);
+        let r3 = Vector3d::new([r31, r32, r33]);
+
+        checked::rotation_matrix("result of RotationMatrix::multiply", RotationMatrix::from_rows([r1, r2, r3]))
+    }
+
     /// Get the rows.
     fn rows(&self) -> [Vector3d; 3] {
-        self.rows.clone()
+        self.rows
     }
 
     /// Get the columns.
@@ -62,6 +150,27 @@ impl RotationMatrix {
     }
 }
 
+impl Default for RotationMatrix {
+    /// The identity rotation.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Product for RotationMatrix {
+    /// Compose left to right; see `compose_all`.
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::identity(), |total, r| total.after(&r))
+    }
+}
+
+impl<'a> Product<&'a RotationMatrix> for RotationMatrix {
+    /// Compose left to right; see `compose_all`.
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        compose_all(iter)
+    }
+}
+
 impl fmt::Debug for RotationMatrix {
     /// Pretty-print a rotation matrix.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -74,22 +183,18 @@ impl fmt::Debug for RotationMatrix {
         let r31 = self.rows[2].data[0];
         let r32 = self.rows[2].data[1];
         let r33 = self.rows[2].data[2];
-        write!(f, "[[{} {} {}][{} {} {}][{} {} {}]",
-               r11, r12, r13,
-               r21, r22, r23,
-               r31, r32, r33
-               )
+        write!(f, "[[{r11} {r12} {r13}][{r21} {r22} {r23}][{r31} {r32} {r33}]")
     }
 }
 
 impl Rotation for RotationMatrix {
-    type R = Self;
+    type Output = Self;
 
     fn identity() -> Self {
-        Self::from_rows([Vector3d::x(), Vector3d::y(), Vector3d::z()])
+        Self::IDENTITY
     }
 
-    fn inverse(&self) -> Result<Self, String> {
+    fn inverse(&self) -> Result<Self, OrientationError> {
         Ok(self.transpose())
     }
 
@@ -97,55 +202,162 @@ impl Rotation for RotationMatrix {
         self.transpose()
     }
 
+    /// Get the quaternion representation of a rotation. The sign of
+    /// the result is chosen so the real part is non-negative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let q = Quaternion::from_angle_axis(0.3, &Vector3d::new([1.0, 2.0, 3.0]));
+    /// let (angle, _axis) = q.as_rotation_matrix().as_quaternion().angle_axis();
+    /// assert!((angle - 0.3).abs() < 1.0e-12);
+    /// ```
     fn as_quaternion(&self) -> Quaternion {
-        Quaternion::identity()
+        let [r11, r12, r13] = self.rows[0].data;
+        let [r21, r22, r23] = self.rows[1].data;
+        let [r31, r32, r33] = self.rows[2].data;
+
+        // Shepperd's method: divide by the largest of the four
+        // candidate denominators to stay numerically stable.
+        let trace = r11 + r22 + r33;
+        let (w, x, y, z) = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            (s / 4.0, (r32 - r23) / s, (r13 - r31) / s, (r21 - r12) / s)
+        } else if r11 > r22 && r11 > r33 {
+            let s = (1.0 + r11 - r22 - r33).sqrt() * 2.0;
+            ((r32 - r23) / s, s / 4.0, (r12 + r21) / s, (r13 + r31) / s)
+        } else if r22 > r33 {
+            let s = (1.0 + r22 - r11 - r33).sqrt() * 2.0;
+            ((r13 - r31) / s, (r12 + r21) / s, s / 4.0, (r23 + r32) / s)
+        } else {
+            let s = (1.0 + r33 - r11 - r22).sqrt() * 2.0;
+            ((r21 - r12) / s, (r13 + r31) / s, (r23 + r32) / s, s / 4.0)
+        };
+
+        let sign = if w < 0.0 { -1.0 } else { 1.0 };
+        checked::unit_quaternion("result of RotationMatrix::as_quaternion",
+                                 Quaternion::new(sign * w, Vector3d::new([x, y, z]).scalar_multiple(sign)))
     }
 
     fn as_rotation_matrix(&self) -> Self {
-        self.clone()
+        *self
     }
 
+    /// Get the angle and axis associated with a rotation. If the
+    /// rotation is the identity (and therefore there is no axis of
+    /// rotation), the z-axis will be returned.
     fn angle_axis(&self) -> (f64, Vector3d) {
-        let angle = 0.0;
-        let axis = Vector3d::z();
-        (angle, axis)
+        self.as_quaternion().angle_axis()
     }
 
-    fn multiply<T: Rotation>(&self, r: &T) -> Self {
-        let rr = r.as_rotation_matrix();
-        let rows = self.rows();
-        let cols = rr.columns();
-
-        let r11 = rows[0].dot(&cols[0]);
-        let r12 = rows[0].dot(&cols[1]);
-        let r13 = rows[0].dot(&cols[2]);
-        let r21 = rows[1].dot(&cols[0]);
-        let r22 = rows[1].dot(&cols[1]);
-        let r23 = rows[1].dot(&cols[2]);
-        let r31 = rows[2].dot(&cols[0]);
-        let r32 = rows[2].dot(&cols[1]);
-        let r33 = rows[2].dot(&cols[2]);
-
-        let r1 = Vector3d::new([r11, r12, r13]);
-        let r2 = Vector3d::new([r21, r22, r23]);
-        let r3 = Vector3d::new([r31, r32, r33]);
-
-        RotationMatrix::from_rows([r1, r2, r3])
-    }
-
-    fn before<T: Rotation<R = T>>(&self, r: &T) -> T {
-        r.multiply(self)
+    fn before<T: Rotation>(&self, r: &T) -> Self {
+        r.as_rotation_matrix().multiply(self)
     }
 
-    fn after<T: Rotation<R = T>>(&self, r: &T) -> T{
-        r.inverse_unchecked().multiply(&self.inverse_unchecked()).inverse_unchecked()
+    fn after<T: Rotation>(&self, r: &T) -> Self {
+        self.multiply(&r.as_rotation_matrix())
     }
 
     fn rotate_vector(&self, v: &Vector3d) -> Vector3d {
+        checked::rotation_matrix("rotation passed to RotationMatrix::rotate_vector", *self);
+        checked::vector("vector passed to RotationMatrix::rotate_vector", *v);
         let rows = self.rows();
         let u1 = rows[0].dot(v);
         let u2 = rows[1].dot(v);
         let u3 = rows[2].dot(v);
         Vector3d::new([u1, u2, u3])
     }
+
+    fn rotate_vector_inverse(&self, v: &Vector3d) -> Vector3d {
+        checked::rotation_matrix("rotation passed to RotationMatrix::rotate_vector_inverse", *self);
+        checked::vector("vector passed to RotationMatrix::rotate_vector_inverse", *v);
+        // R^T v is the combination of the rows weighted by v.
+        let rows = self.rows();
+        rows[0].scalar_multiple(v.data[0]) + rows[1].scalar_multiple(v.data[1]) + rows[2].scalar_multiple(v.data[2])
+    }
+
+    /// With the `simd` feature, four vectors are rotated at a time.
+    fn rotate_vectors_into(&self, vs: &[Vector3d], rotated: &mut [Vector3d]) {
+        assert_eq!(vs.len(), rotated.len(), "Input and output lengths differ");
+        checked::rotation_matrix("rotation passed to RotationMatrix::rotate_vectors_into", *self);
+        #[cfg(feature = "simd")]
+        let done = crate::simd::rotate_vectors(self, vs, rotated);
+        #[cfg(not(feature = "simd"))]
+        let done = 0;
+        for (v, out) in vs[done..].iter().zip(&mut rotated[done..]) {
+            *out = self.rotate_vector(v);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    macro_rules! as_quaternion_round_trip_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (angle, axis) = $value;
+                let q = Quaternion::from_angle_axis(angle, &axis);
+                let r = q.as_rotation_matrix().as_quaternion();
+                assert_quat_approx_eq!(q, r);
+            }
+        )*
+        }
+    }
+
+    // Cover each branch of Shepperd's method.
+    as_quaternion_round_trip_tests! {
+        as_quaternion_small_angle: (0.3, Vector3d::new([1.0, 2.0, 3.0])),
+        as_quaternion_about_x: (3.0, Vector3d::unit_x()),
+        as_quaternion_about_y: (3.0, Vector3d::unit_y()),
+        as_quaternion_about_z: (3.0, Vector3d::unit_z()),
+        as_quaternion_half_turn: (PI, Vector3d::new([1.0, 1.0, 0.0])),
+    }
+
+    #[test]
+    fn default_is_identity() {
+        assert_eq!(RotationMatrix::identity(), RotationMatrix::default());
+    }
+
+    #[test]
+    fn angle_axis() {
+        let r = Quaternion::from_angle_axis(1.2, &Vector3d::unit_y()).as_rotation_matrix();
+        let (angle, axis) = r.angle_axis();
+        assert_float_approx_eq!(1.2, angle);
+        assert_vector_approx_eq!(Vector3d::unit_y(), axis);
+    }
+
+    #[test]
+    fn integrate_matches_quaternion() {
+        let q = Quaternion::from_angle_axis(0.4, &Vector3d::unit_x());
+        let omega = Vector3d::new([0.1, 2.0, -0.5]);
+        let expected = q.integrate(&omega, 0.1);
+        let actual = q.as_rotation_matrix().integrate(&omega, 0.1);
+        assert_quat_approx_eq!(expected, actual.as_quaternion());
+    }
+
+    #[test]
+    fn rotate_tensor() {
+        let q = Quaternion::from_angle_axis(0.9, &Vector3d::new([1.0, -2.0, 0.5]));
+        let inertia = Matrix3::new([
+            [2.0, 0.1, 0.0],
+            [0.1, 3.0, -0.2],
+            [0.0, -0.2, 5.0]
+        ]);
+        let world = q.rotate_tensor(&inertia);
+        // The quadratic form is preserved: v^T J v = (Rv)^T (R J R^T) (Rv).
+        let v = Vector3d::new([0.3, 1.0, -0.7]);
+        let rv = q.rotate_vector(&v);
+        assert_float_approx_eq!(v.dot(&inertia.multiply_vector(&v)), rv.dot(&world.multiply_vector(&rv)), 1.0e-12);
+        assert_float_approx_eq!(inertia.trace(), world.trace(), 1.0e-12);
+        assert_matrix_approx_eq!(world, world.transpose(), 1.0e-12);
+        assert_matrix_approx_eq!(inertia, q.inverse_unchecked().rotate_tensor(&world), 1.0e-12);
+        assert_matrix_approx_eq!(world, q.as_rotation_matrix().rotate_tensor(&inertia), 1.0e-12);
+    }
 }
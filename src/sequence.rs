@@ -0,0 +1,196 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::error::OrientationError;
+
+/// The constant body-frame angular velocity taking `q0` to `q1` in `dt`
+/// seconds, so that `q0.integrate(&omega, dt) == q1`. The shorter of
+/// the two possible rotations is used, whatever the signs of the
+/// quaternions.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let q0 = Quaternion::identity();
+/// let q1 = Quaternion::from_angle_axis(0.1, &Vector3d::unit_x());
+/// let omega = angular_velocity_between(&q0, &q1, 0.01);
+/// assert!((omega - Vector3d::new([10.0, 0.0, 0.0])).norm() < 1.0e-9);
+/// ```
+#[must_use]
+pub fn angular_velocity_between(q0: &Quaternion, q1: &Quaternion, dt: f64) -> Vector3d {
+    q0.inverse_unchecked()
+        .multiply(q1)
+        .rotation_vector()
+        .scalar_multiple(1.0 / dt)
+}
+
+/// Estimate the body-frame angular velocity at every sample of a
+/// timestamped orientation sequence by finite differences. Interior
+/// samples use central differences across their two neighbors; the
+/// first and last samples use one-sided differences.
+///
+/// # Errors
+/// Returns an error if there are fewer than two samples, or if the
+/// timestamps are not strictly increasing.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let samples: Vec<(f64, Quaternion)> = (0..5)
+///     .map(|i| (f64::from(i) * 0.1, Quaternion::from_angle_axis(f64::from(i) * 0.05, &Vector3d::unit_z())))
+///     .collect();
+/// let rates = angular_velocities(&samples).unwrap();
+/// assert!(rates.iter().all(|w| (*w - Vector3d::new([0.0, 0.0, 0.5])).norm() < 1.0e-9));
+/// ```
+pub fn angular_velocities(samples: &[(f64, Quaternion)]) -> Result<Vec<Vector3d>, OrientationError> {
+    if samples.len() < 2 {
+        return Err(OrientationError::InsufficientData("need at least two samples to estimate angular velocity"))
+    }
+    if samples.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+        return Err(OrientationError::NotIncreasing)
+    }
+
+    let last = samples.len() - 1;
+    let rates = (0..=last)
+        .map(|i| {
+            let (t0, q0) = samples[i.saturating_sub(1)];
+            let (t1, q1) = samples[(i + 1).min(last)];
+            angular_velocity_between(&q0, &q1, t1 - t0)
+        })
+        .collect();
+    Ok(rates)
+}
+
+/// Flip the signs of quaternions in a sequence so that each sample lies
+/// in the same hemisphere as the one before it. The rotations are
+/// unchanged; only their representation is, which keeps
+/// component-wise plots and differences free of spurious jumps. The
+/// first sample is left as is.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let q = Quaternion::from_angle_axis(0.1, &Vector3d::unit_x());
+/// // The same rotation as q, represented by -q.
+/// let flipped = Quaternion::from_angle_axis(0.1 + 2.0 * std::f64::consts::PI, &Vector3d::unit_x());
+/// let mut samples = [Quaternion::identity(), flipped];
+/// make_sign_continuous(&mut samples);
+/// assert!((samples[1].as_quaternion().angle_axis().0 - 0.1).abs() < 1.0e-12);
+/// ```
+pub fn make_sign_continuous(samples: &mut [Quaternion]) {
+    for i in 1..samples.len() {
+        if samples[i].dot(&samples[i - 1]) < 0.0 {
+            samples[i] = samples[i].scale(-1.0);
+        }
+    }
+}
+
+/// An iterator adapter yielding quaternions with continuous signs, as in
+/// `make_sign_continuous`. Created by `sign_continuous`.
+#[derive(Clone, Debug)]
+pub struct SignContinuous<I> {
+    inner: I,
+    previous: Option<Quaternion>
+}
+
+impl<I: Iterator<Item = Quaternion>> Iterator for SignContinuous<I> {
+    type Item = Quaternion;
+
+    fn next(&mut self) -> Option<Quaternion> {
+        let q = self.inner.next()?;
+        let q = match self.previous {
+            Some(previous) if q.dot(&previous) < 0.0 => q.scale(-1.0),
+            _ => q
+        };
+        self.previous = Some(q);
+        Some(q)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Adapt a stream of quaternions so that each lies in the same
+/// hemisphere as the one before it.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let stream = vec![Quaternion::identity(); 3];
+/// let continuous: Vec<Quaternion> = sign_continuous(stream).collect();
+/// assert_eq!(3, continuous.len());
+/// ```
+pub fn sign_continuous<I: IntoIterator<Item = Quaternion>>(samples: I) -> SignContinuous<I::IntoIter> {
+    SignContinuous {
+        inner: samples.into_iter(),
+        previous: None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn between_inverts_integrate() {
+        let q0 = Quaternion::from_angle_axis(0.7, &Vector3d::new([1.0, 2.0, -1.0]));
+        let omega = Vector3d::new([0.3, -1.2, 2.0]);
+        let q1 = q0.integrate(&omega, 0.1);
+        assert_vector_approx_eq!(omega, angular_velocity_between(&q0, &q1, 0.1), 1.0e-9);
+    }
+
+    #[test]
+    fn between_ignores_sign() {
+        let q0 = Quaternion::identity();
+        let omega = Vector3d::new([0.0, 1.0, 0.0]);
+        // The same rotation as q0.integrate(&omega, 0.1), represented by -q.
+        let flipped = Quaternion::from_angle_axis(0.1 + 2.0 * std::f64::consts::PI, &Vector3d::unit_y());
+        assert_vector_approx_eq!(omega, angular_velocity_between(&q0, &flipped, 0.1), 1.0e-9);
+    }
+
+    #[test]
+    fn central_differences_on_irregular_times() {
+        // Constant rate, irregular spacing: every estimate is exact.
+        let omega = Vector3d::new([0.2, -0.1, 0.4]);
+        let q0 = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x());
+        let times = [0.0, 0.1, 0.15, 0.4, 0.45];
+        let samples: Vec<(f64, Quaternion)> = times.iter().map(|&t| (t, q0.integrate(&omega, t))).collect();
+        let rates = angular_velocities(&samples).unwrap();
+        assert_eq!(5, rates.len());
+        for w in rates {
+            assert_vector_approx_eq!(omega, w, 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn invalid_sequences() {
+        let q = Quaternion::identity();
+        assert!(angular_velocities(&[(0.0, q)]).is_err());
+        assert!(angular_velocities(&[(0.0, q), (0.0, q)]).is_err());
+    }
+
+    #[test]
+    fn sign_continuity() {
+        let q: Vec<Quaternion> = (0..10)
+            .map(|i| {
+                let q = Quaternion::from_angle_axis(0.3 * f64::from(i), &Vector3d::new([1.0, 1.0, 0.0]));
+                if i % 3 == 0 { q.scale(-1.0) } else { q }
+            })
+            .collect();
+
+        let mut unwrapped = q.clone();
+        make_sign_continuous(&mut unwrapped);
+        let streamed: Vec<Quaternion> = sign_continuous(q.iter().copied()).collect();
+        assert_eq!(unwrapped, streamed);
+        for (a, b) in q.iter().zip(unwrapped.iter()) {
+            assert!(a.angle_to(b) < 1.0e-12);
+        }
+        assert!(unwrapped.windows(2).all(|pair| pair[0].dot(&pair[1]) > 0.0));
+    }
+}
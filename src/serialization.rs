@@ -0,0 +1,373 @@
+//! Serde support, behind the `serde` feature.
+//!
+//! Vectors serialize as `[x, y, z]`, quaternions as `[w, x, y, z]` with
+//! the scalar part first, and rotation matrices as their three rows.
+//! `AnyRotation` is tagged with its representation, as in
+//! `{"Quaternion": [1.0, 0.0, 0.0, 0.0]}`.
+//!
+//! Deserializing a rotation checks that it is one: a quaternion must
+//! have unit norm and a matrix must be special orthogonal, to within
+//! `UNIT_TOLERANCE`. Wrap the field in `Lenient` to accept rotations
+//! that have drifted further, or were written with few digits, and
+//! project them back onto the rotations instead.
+//!
+//! Wrap a value in `Described` to store the conventions it was written
+//! with alongside it, so that a reader assuming different conventions
+//! fails loudly instead of silently misreading the numbers.
+//!
+//! # Examples
+//!
+//! ```
+//! use orientations::*;
+//! let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_z());
+//! let json = serde_json::to_string(&q).unwrap();
+//! assert!(q.approx_eq(&serde_json::from_str(&json).unwrap(), 1.0e-12));
+//! assert!(serde_json::from_str::<Quaternion>("[2.0, 0.0, 0.0, 0.0]").is_err());
+//! ```
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Error;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::any_rotation::AnyRotation;
+use crate::matrix_fisher::proper_svd;
+
+/// How far from unit norm, or from orthogonality, a stored rotation may
+/// be and still deserialize. Loose enough for values written with about
+/// six significant digits.
+pub const UNIT_TOLERANCE: f64 = 1.0e-6;
+
+/// A rotation that deserializes leniently: a finite quaternion of any
+/// nonzero norm is normalized, and a finite matrix with positive
+/// determinant is replaced by the nearest rotation matrix. Zero,
+/// non-finite and reflected inputs are still rejected. Serializes as
+/// the wrapped value.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// use orientations::serialization::Lenient;
+/// let Lenient(q) = serde_json::from_str::<Lenient<Quaternion>>("[2.0, 0.0, 0.0, 0.0]").unwrap();
+/// assert_eq!(Quaternion::identity(), q);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Lenient<T>(pub T);
+
+impl Serialize for Vector3d {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Vector3d {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <[f64; 3]>::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl Serialize for Quaternion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        <[f64; 4]>::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Quaternion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let q = Self::from(<[f64; 4]>::deserialize(deserializer)?);
+        if !q.is_finite() {
+            return Err(D::Error::custom("quaternion is not finite"))
+        }
+        if !q.is_normalized(UNIT_TOLERANCE) {
+            return Err(D::Error::custom("quaternion is not a unit quaternion"))
+        }
+        Ok(q)
+    }
+}
+
+impl Serialize for RotationMatrix {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_matrix3().data.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RotationMatrix {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let r = matrix_from_rows(<[[f64; 3]; 3]>::deserialize(deserializer)?);
+        if !r.is_finite() {
+            return Err(D::Error::custom("rotation matrix is not finite"))
+        }
+        if !r.is_special_orthogonal(UNIT_TOLERANCE) {
+            return Err(D::Error::custom("matrix is not a rotation"))
+        }
+        Ok(r)
+    }
+}
+
+impl<T: Serialize> Serialize for Lenient<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Lenient<Quaternion> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let q = Quaternion::from(<[f64; 4]>::deserialize(deserializer)?);
+        if !q.is_finite() {
+            return Err(D::Error::custom("quaternion is not finite"))
+        }
+        q.normalized().map(Lenient).map_err(D::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Lenient<RotationMatrix> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let r = matrix_from_rows(<[[f64; 3]; 3]>::deserialize(deserializer)?);
+        if !r.is_finite() {
+            return Err(D::Error::custom("rotation matrix is not finite"))
+        }
+        let m = r.as_matrix3();
+        if m.determinant() <= 0.0 {
+            return Err(D::Error::custom("matrix is singular or a reflection"))
+        }
+        let (u, _, v) = proper_svd(&m);
+        Ok(Lenient(u.after(&v.inverse_unchecked())))
+    }
+}
+
+impl<'de> Deserialize<'de> for Lenient<AnyRotation> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Mirrors the externally tagged form of `AnyRotation`.
+        #[derive(Deserialize)]
+        enum Tagged {
+            Quaternion(Lenient<Quaternion>),
+            RotationMatrix(Lenient<RotationMatrix>)
+        }
+        Ok(Lenient(match Tagged::deserialize(deserializer)? {
+            Tagged::Quaternion(Lenient(q)) => q.into(),
+            Tagged::RotationMatrix(Lenient(r)) => r.into()
+        }))
+    }
+}
+
+/// The order in which quaternion components are listed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentOrder {
+    /// Scalar first, `[w, x, y, z]`, as in this crate.
+    Wxyz,
+    /// Scalar last, `[x, y, z, w]`.
+    Xyzw
+}
+
+/// The handedness of the coordinate frames.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Handedness {
+    /// Right-handed frames, as in this crate.
+    Right,
+    /// Left-handed frames.
+    Left
+}
+
+/// The quaternion multiplication convention.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuaternionConvention {
+    /// Hamilton: `ij = k`, as in this crate.
+    Hamilton,
+    /// JPL: `ij = -k`, common in older spacecraft software.
+    Jpl
+}
+
+/// The layout of a serialized matrix.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatrixLayout {
+    /// A list of rows, as in this crate.
+    RowMajor,
+    /// A list of columns.
+    ColumnMajor
+}
+
+/// The conventions needed to interpret stored vectors and rotations.
+/// Every field is required when deserializing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Conventions {
+    /// Quaternion component order.
+    pub component_order: ComponentOrder,
+    /// Frame handedness.
+    pub handedness: Handedness,
+    /// Quaternion multiplication convention.
+    pub quaternion: QuaternionConvention,
+    /// Matrix layout.
+    pub matrix_layout: MatrixLayout
+}
+
+impl Conventions {
+    /// The conventions of this crate, which `Described` writes and
+    /// requires.
+    pub const CRATE: Self = Conventions {
+        component_order: ComponentOrder::Wxyz,
+        handedness: Handedness::Right,
+        quaternion: QuaternionConvention::Hamilton,
+        matrix_layout: MatrixLayout::RowMajor
+    };
+
+    /// Describe how `self` differs from `expected`, or `None` if they
+    /// agree.
+    fn mismatch(self, expected: Self) -> Option<String> {
+        let mut differences = Vec::new();
+        if self.component_order != expected.component_order {
+            differences.push(format!("component order {:?}, expected {:?}", self.component_order, expected.component_order));
+        }
+        if self.handedness != expected.handedness {
+            differences.push(format!("{:?}-handed frames, expected {:?}-handed", self.handedness, expected.handedness));
+        }
+        if self.quaternion != expected.quaternion {
+            differences.push(format!("{:?} quaternions, expected {:?}", self.quaternion, expected.quaternion));
+        }
+        if self.matrix_layout != expected.matrix_layout {
+            differences.push(format!("{:?} matrices, expected {:?}", self.matrix_layout, expected.matrix_layout));
+        }
+        if differences.is_empty() { None } else { Some(differences.join("; ")) }
+    }
+}
+
+/// A value stored together with the conventions it was written with,
+/// as `{"conventions": {...}, "value": ...}`. Deserialization fails,
+/// naming each difference, unless the stored conventions are
+/// `Conventions::CRATE`; nothing is converted.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// use orientations::serialization::Described;
+/// let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_z());
+/// let json = serde_json::to_string(&Described(q)).unwrap();
+/// assert!(json.contains(r#""component_order":"wxyz""#));
+/// let Described(read) = serde_json::from_str::<Described<Quaternion>>(&json).unwrap();
+/// assert!(q.approx_eq(&read, 1.0e-12));
+///
+/// let foreign = json.replace("wxyz", "xyzw");
+/// let error = serde_json::from_str::<Described<Quaternion>>(&foreign).unwrap_err();
+/// assert!(error.to_string().contains("component order Xyzw, expected Wxyz"));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Described<T>(pub T);
+
+impl<T: Serialize> Serialize for Described<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Stored<'a, T> {
+            conventions: Conventions,
+            value: &'a T
+        }
+        Stored { conventions: Conventions::CRATE, value: &self.0 }.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Described<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Stored<T> {
+            conventions: Conventions,
+            value: T
+        }
+        // The value is parsed before the conventions are checked, so a
+        // value that fails validation is reported as such even when the
+        // conventions also differ.
+        let stored = Stored::<T>::deserialize(deserializer)?;
+        match stored.conventions.mismatch(Conventions::CRATE) {
+            Some(differences) => Err(D::Error::custom(format_args!("stored with {differences}"))),
+            None => Ok(Described(stored.value))
+        }
+    }
+}
+
+fn matrix_from_rows(rows: [[f64; 3]; 3]) -> RotationMatrix {
+    RotationMatrix::from_rows(rows.map(Vector3d::new))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let v = Vector3d::new([0.1, -2.0, 3.5]);
+        assert_eq!("[0.1,-2.0,3.5]", serde_json::to_string(&v).unwrap());
+        assert_eq!(v, serde_json::from_str("[0.1, -2.0, 3.5]").unwrap());
+
+        let q = Quaternion::from_angle_axis(1.1, &Vector3d::new([1.0, -1.0, 0.5]));
+        // serde_json may round the last bit of a float.
+        assert!(q.approx_eq(&serde_json::from_str(&serde_json::to_string(&q).unwrap()).unwrap(), 1.0e-15));
+        let r = q.as_rotation_matrix();
+        assert!(r.approx_eq(&serde_json::from_str(&serde_json::to_string(&r).unwrap()).unwrap(), 1.0e-15));
+
+        for any in &[AnyRotation::from(q), AnyRotation::from(r)] {
+            let json = serde_json::to_string(any).unwrap();
+            let read: AnyRotation = serde_json::from_str(&json).unwrap();
+            assert!(matches!((any, read), (AnyRotation::Quaternion(_), AnyRotation::Quaternion(_))
+                                          | (AnyRotation::RotationMatrix(_), AnyRotation::RotationMatrix(_))));
+            assert!(any.as_quaternion().angle_to(&read.as_quaternion()) < 1.0e-12);
+            let Lenient(lenient) = serde_json::from_str::<Lenient<AnyRotation>>(&json).unwrap();
+            assert!(any.as_quaternion().angle_to(&lenient.as_quaternion()) < 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn strict_rejects_non_rotations() {
+        assert!(serde_json::from_str::<Quaternion>("[1.0, 0.01, 0.0, 0.0]").is_err());
+        assert!(serde_json::from_str::<Quaternion>("[0.0, 0.0, 0.0, 0.0]").is_err());
+        assert!(serde_json::from_str::<Quaternion>("[1.0, 0.0, 0.0]").is_err());
+        assert!(serde_json::from_str::<RotationMatrix>("[[1, 0, 0], [0, 1, 0], [0, 0, -1]]").is_err());
+        assert!(serde_json::from_str::<RotationMatrix>("[[1.01, 0, 0], [0, 1, 0], [0, 0, 1]]").is_err());
+        // Six significant digits are enough.
+        assert!(serde_json::from_str::<Quaternion>("[0.707107, 0.0, 0.707107, 0.0]").is_ok());
+    }
+
+    #[test]
+    fn lenient_projects_onto_rotations() {
+        let Lenient(q) = serde_json::from_str::<Lenient<Quaternion>>("[1.0, 0.01, 0.0, 0.0]").unwrap();
+        assert!(q.is_normalized(1.0e-12));
+
+        let truth = Quaternion::from_angle_axis(0.4, &Vector3d::new([0.0, 1.0, 1.0])).as_rotation_matrix();
+        let mut rows = truth.as_matrix3().data;
+        rows[0][1] += 0.01;
+        rows[2][2] *= 1.02;
+        let json = serde_json::to_string(&rows).unwrap();
+        let Lenient(r) = serde_json::from_str::<Lenient<RotationMatrix>>(&json).unwrap();
+        assert!(r.is_special_orthogonal(1.0e-12));
+        assert!(r.as_quaternion().angle_to(&truth.as_quaternion()) < 0.02);
+
+        assert!(serde_json::from_str::<Lenient<Quaternion>>("[0.0, 0.0, 0.0, 0.0]").is_err());
+        assert!(serde_json::from_str::<Lenient<RotationMatrix>>("[[1, 0, 0], [0, 1, 0], [0, 0, -1]]").is_err());
+    }
+
+    #[test]
+    fn described_values_check_conventions() {
+        let r = Quaternion::from_angle_axis(0.8, &Vector3d::new([1.0, 0.0, 1.0])).as_rotation_matrix();
+        let json = serde_json::to_string(&Described(r)).unwrap();
+        let Described(read) = serde_json::from_str::<Described<RotationMatrix>>(&json).unwrap();
+        assert!(read.approx_eq(&r, 1.0e-12));
+        let Described(Lenient(read)) = serde_json::from_str::<Described<Lenient<RotationMatrix>>>(&json).unwrap();
+        assert!(read.approx_eq(&r, 1.0e-12));
+
+        let foreign = json.replace("row_major", "column_major").replace("hamilton", "jpl");
+        let message = serde_json::from_str::<Described<RotationMatrix>>(&foreign).unwrap_err().to_string();
+        assert!(message.contains("Jpl quaternions, expected Hamilton"), "{}", message);
+        assert!(message.contains("ColumnMajor matrices, expected RowMajor"), "{}", message);
+
+        // Metadata cannot be left out, or bare values passed off as described.
+        let partial = json.replace(r#""handedness":"right","#, "");
+        assert!(serde_json::from_str::<Described<RotationMatrix>>(&partial).is_err());
+        assert!(serde_json::from_str::<Described<RotationMatrix>>(&serde_json::to_string(&r).unwrap()).is_err());
+    }
+}
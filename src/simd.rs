@@ -0,0 +1,110 @@
+use wide::f64x4;
+use crate::vector3d::Vector3d;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+use crate::constants::DBL_EPSILON;
+use crate::error::OrientationError;
+use crate::checked;
+
+// Kernels for the `simd` feature. Each handles four elements at a time,
+// one per lane, by transposing them into a vector of w components, one
+// of x components and so on; any remainder is left to the scalar code.
+
+/// Four quaternions, one per lane.
+struct Quaternion4 {
+    w: f64x4,
+    x: f64x4,
+    y: f64x4,
+    z: f64x4
+}
+
+impl Quaternion4 {
+    fn load(qs: &[Quaternion]) -> Self {
+        let lane = |k: usize| {
+            let (w, v) = qs[k].split();
+            [w, v.data[0], v.data[1], v.data[2]]
+        };
+        let [a, b, c, d] = [lane(0), lane(1), lane(2), lane(3)];
+        Quaternion4 {
+            w: f64x4::from([a[0], b[0], c[0], d[0]]),
+            x: f64x4::from([a[1], b[1], c[1], d[1]]),
+            y: f64x4::from([a[2], b[2], c[2], d[2]]),
+            z: f64x4::from([a[3], b[3], c[3], d[3]])
+        }
+    }
+
+    fn store(&self, what: &str, out: &mut [Quaternion]) {
+        let [w, x, y, z] = [self.w.to_array(), self.x.to_array(), self.y.to_array(), self.z.to_array()];
+        for (k, q) in out.iter_mut().enumerate().take(4) {
+            *q = checked::quaternion(what, Quaternion::new(w[k], Vector3d::new([x[k], y[k], z[k]])));
+        }
+    }
+}
+
+/// The Hamilton products `lhs[i] * rhs[i]` of whole chunks of four,
+/// returning how many were written.
+pub(crate) fn multiply(lhs: &[Quaternion], rhs: &[Quaternion], out: &mut [Quaternion]) -> usize {
+    let chunks = lhs.chunks_exact(4).zip(rhs.chunks_exact(4)).zip(out.chunks_exact_mut(4));
+    let mut done = 0;
+    for ((a, b), product) in chunks {
+        let (a, b) = (Quaternion4::load(a), Quaternion4::load(b));
+        Quaternion4 {
+            w: a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            x: a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            y: a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            z: a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w
+        }.store("result of Quaternion::after_each", product);
+        done += 4;
+    }
+    done
+}
+
+/// Normalize whole chunks of four in place, returning how many were
+/// processed. A chunk containing a quaternion too small to normalize is
+/// handled one at a time, so the others in it are still normalized.
+pub(crate) fn normalize(qs: &mut [Quaternion]) -> (usize, Result<(), OrientationError>) {
+    let mut result = Ok(());
+    let mut done = 0;
+    for chunk in qs.chunks_exact_mut(4) {
+        let q = Quaternion4::load(chunk);
+        let norm = (q.w * q.w + q.x * q.x + q.y * q.y + q.z * q.z).sqrt();
+        if norm.to_array().iter().all(|n| *n >= DBL_EPSILON) {
+            let scale = f64x4::splat(1.0) / norm;
+            Quaternion4 { w: q.w * scale, x: q.x * scale, y: q.y * scale, z: q.z * scale }
+                .store("result of Quaternion::normalize_each", chunk);
+        } else {
+            for q in chunk {
+                if let Err(e) = q.normalize_in_place() {
+                    result = Err(e);
+                }
+            }
+        }
+        done += 4;
+    }
+    (done, result)
+}
+
+/// Rotate whole chunks of four vectors by a rotation matrix, returning
+/// how many were written.
+pub(crate) fn rotate_vectors(rotation: &RotationMatrix, vs: &[Vector3d], rotated: &mut [Vector3d]) -> usize {
+    let rows = rotation.as_matrix3().data;
+    let splat = |row: [f64; 3]| [f64x4::splat(row[0]), f64x4::splat(row[1]), f64x4::splat(row[2])];
+    let [r0, r1, r2] = [splat(rows[0]), splat(rows[1]), splat(rows[2])];
+    let mut done = 0;
+    for (chunk, out) in vs.chunks_exact(4).zip(rotated.chunks_exact_mut(4)) {
+        let x = f64x4::from([chunk[0].data[0], chunk[1].data[0], chunk[2].data[0], chunk[3].data[0]]);
+        let y = f64x4::from([chunk[0].data[1], chunk[1].data[1], chunk[2].data[1], chunk[3].data[1]]);
+        let z = f64x4::from([chunk[0].data[2], chunk[1].data[2], chunk[2].data[2], chunk[3].data[2]]);
+        let components = [
+            (r0[0] * x + r0[1] * y + r0[2] * z).to_array(),
+            (r1[0] * x + r1[1] * y + r1[2] * z).to_array(),
+            (r2[0] * x + r2[1] * y + r2[2] * z).to_array()
+        ];
+        for (k, result) in out.iter_mut().enumerate() {
+            let v = Vector3d::new([components[0][k], components[1][k], components[2][k]]);
+            *result = checked::vector("result of RotationMatrix::rotate_vectors_into", v);
+        }
+        done += 4;
+    }
+    done
+}
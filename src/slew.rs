@@ -0,0 +1,425 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::constants::DBL_EPSILON;
+use crate::error::OrientationError;
+
+/// The state of a planned maneuver at an instant.
+#[derive(Copy, Clone, Debug)]
+pub struct SlewState {
+    /// The orientation.
+    pub orientation: Quaternion,
+    /// The body-frame angular velocity, in radians per second.
+    pub angular_velocity: Vector3d,
+    /// The body-frame angular acceleration, in radians per second
+    /// squared.
+    pub angular_acceleration: Vector3d
+}
+
+/// A rest-to-rest profile of the angle turned: constant acceleration up
+/// to a peak rate, a cruise at that rate, then constant deceleration.
+/// Without a cruise phase the profile is triangular.
+#[derive(Copy, Clone, Debug)]
+struct Trapezoid {
+    acceleration: f64,
+    peak_rate: f64,
+    accel_time: f64,
+    cruise_time: f64
+}
+
+impl Trapezoid {
+    /// The fastest profile covering `angle` within the limits.
+    fn fastest(angle: f64, max_rate: f64, max_acceleration: f64) -> Self {
+        let peak_rate = max_rate.min((angle * max_acceleration).sqrt());
+        let accel_time = peak_rate / max_acceleration;
+        let cruise_time = if peak_rate > 0.0 { angle / peak_rate - accel_time } else { 0.0 };
+        Self {
+            acceleration: max_acceleration,
+            peak_rate,
+            accel_time,
+            cruise_time: cruise_time.max(0.0)
+        }
+    }
+
+    /// The profile covering `angle` in exactly `duration` seconds,
+    /// spending the given fraction of it accelerating and the same
+    /// fraction decelerating.
+    fn with_duration(angle: f64, duration: f64, accel_fraction: f64) -> Self {
+        let accel_time = accel_fraction * duration;
+        let peak_rate = angle / (duration - accel_time);
+        Self {
+            acceleration: peak_rate / accel_time,
+            peak_rate,
+            accel_time,
+            cruise_time: duration - 2.0 * accel_time
+        }
+    }
+
+    fn duration(&self) -> f64 {
+        2.0 * self.accel_time + self.cruise_time
+    }
+
+    /// The angle, rate and acceleration at time `t`, holding the end
+    /// points outside the maneuver.
+    fn evaluate(&self, t: f64) -> (f64, f64, f64) {
+        let (a, v, ta) = (self.acceleration, self.peak_rate, self.accel_time);
+        let total = self.duration();
+        let ramp = 0.5 * v * ta;
+        if t <= 0.0 {
+            (0.0, 0.0, 0.0)
+        } else if t < ta {
+            (0.5 * a * t * t, a * t, a)
+        } else if t <= ta + self.cruise_time {
+            (ramp + v * (t - ta), v, 0.0)
+        } else if t < total {
+            let remaining = total - t;
+            (2.0 * ramp + v * self.cruise_time - 0.5 * a * remaining * remaining, a * remaining, -a)
+        } else {
+            (2.0 * ramp + v * self.cruise_time, 0.0, 0.0)
+        }
+    }
+}
+
+/// The shape of the angle turned over time.
+#[derive(Copy, Clone, Debug)]
+enum Profile {
+    Trapezoid(Trapezoid),
+    /// The quintic minimizing the integrated squared jerk, with zero
+    /// rate and acceleration at both ends.
+    MinimumJerk { angle: f64, duration: f64 }
+}
+
+impl Profile {
+    fn duration(&self) -> f64 {
+        match self {
+            Profile::Trapezoid(trapezoid) => trapezoid.duration(),
+            Profile::MinimumJerk { duration, .. } => *duration
+        }
+    }
+
+    /// The angle, rate and acceleration at time `t`.
+    fn evaluate(&self, t: f64) -> (f64, f64, f64) {
+        match *self {
+            Profile::Trapezoid(trapezoid) => trapezoid.evaluate(t),
+            Profile::MinimumJerk { angle, duration } => {
+                let s = (t / duration).clamp(0.0, 1.0);
+                let (s2, s3) = (s * s, s * s * s);
+                (angle * (10.0 * s3 - 15.0 * s3 * s + 6.0 * s3 * s2),
+                 angle * (30.0 * s2 - 60.0 * s3 + 30.0 * s2 * s2) / duration,
+                 angle * (60.0 * s - 180.0 * s2 + 120.0 * s3) / (duration * duration))
+            }
+        }
+    }
+}
+
+/// A rest-to-rest eigenaxis maneuver: a rotation from a start to a goal
+/// orientation about the single fixed axis of the rotation between
+/// them, taking the shorter way round.
+///
+/// Because the axis is fixed, it is the same in the body frame and the
+/// world frame throughout, and the angular velocity and acceleration
+/// are always parallel to it.
+#[derive(Copy, Clone, Debug)]
+pub struct EigenaxisSlew {
+    start: Quaternion,
+    axis: Vector3d,
+    angle: f64,
+    profile: Profile
+}
+
+impl EigenaxisSlew {
+    /// Plan the fastest eigenaxis maneuver from `start` to `goal` that
+    /// respects a maximum angular rate and acceleration about the axis.
+    ///
+    /// # Errors
+    /// Returns an error if either limit is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let goal = Quaternion::from_angle_axis(std::f64::consts::PI / 2.0, &Vector3d::unit_z());
+    /// let slew = EigenaxisSlew::plan(&Quaternion::identity(), &goal, 0.1, 0.01).unwrap();
+    /// // 10 seconds each to speed up and slow down, turning 1 radian in
+    /// // all, and the rest of the quarter turn at 0.1 radians per second.
+    /// let cruise = (std::f64::consts::PI / 2.0 - 1.0) / 0.1;
+    /// assert!((slew.duration() - (20.0 + cruise)).abs() < 1.0e-9);
+    /// assert!(slew.state(slew.duration()).orientation.angle_to(&goal) < 1.0e-12);
+    /// ```
+    pub fn plan(start: &Quaternion, goal: &Quaternion, max_rate: f64,
+                max_acceleration: f64) -> Result<Self, OrientationError> {
+        let valid = |x: f64| x > 0.0 && x.is_finite();
+        if !valid(max_rate) || !valid(max_acceleration) {
+            return Err(OrientationError::InvalidParameter("rate and acceleration limits must be positive and finite"))
+        }
+
+        let (axis, angle) = Self::eigenaxis(start, goal);
+        Ok(Self {
+            start: *start,
+            axis,
+            angle,
+            profile: Profile::Trapezoid(Trapezoid::fastest(angle, max_rate, max_acceleration))
+        })
+    }
+
+    /// Plan an eigenaxis maneuver from `start` to `goal` taking exactly
+    /// `duration` seconds, with a trapezoidal rate profile: constant
+    /// acceleration for `accel_fraction` of the duration, a cruise, and
+    /// constant deceleration for the same fraction at the end. A
+    /// fraction of one half gives a triangular profile.
+    ///
+    /// # Errors
+    /// Returns an error if the duration is not positive and finite, or
+    /// if the fraction is not in (0, 1/2].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let goal = Quaternion::from_angle_axis(1.0, &Vector3d::unit_x());
+    /// let slew = EigenaxisSlew::trapezoidal(&Quaternion::identity(), &goal, 4.0, 0.25).unwrap();
+    /// // One radian in the three seconds' worth of peak-rate motion.
+    /// let peak = slew.state(2.0).angular_velocity;
+    /// assert!((peak - Vector3d::new([1.0 / 3.0, 0.0, 0.0])).norm() < 1.0e-12);
+    /// ```
+    pub fn trapezoidal(start: &Quaternion, goal: &Quaternion, duration: f64,
+                       accel_fraction: f64) -> Result<Self, OrientationError> {
+        if duration <= 0.0 || !duration.is_finite() {
+            return Err(OrientationError::InvalidParameter("duration must be positive and finite"))
+        }
+        if accel_fraction <= 0.0 || accel_fraction > 0.5 || accel_fraction.is_nan() {
+            return Err(OrientationError::InvalidParameter("acceleration fraction must be in (0, 1/2]"))
+        }
+
+        let (axis, angle) = Self::eigenaxis(start, goal);
+        Ok(Self {
+            start: *start,
+            axis,
+            angle,
+            profile: Profile::Trapezoid(Trapezoid::with_duration(angle, duration, accel_fraction))
+        })
+    }
+
+    /// Plan a minimum-jerk eigenaxis maneuver from `start` to `goal`
+    /// taking `duration` seconds: the angle follows the quintic
+    /// `10 s^3 - 15 s^4 + 6 s^5` of the normalized time `s`, so the rate
+    /// and acceleration are continuous and zero at both ends. This suits
+    /// smooth camera moves and feed-forward control.
+    ///
+    /// # Errors
+    /// Returns an error if the duration is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let goal = Quaternion::from_angle_axis(1.0, &Vector3d::unit_y());
+    /// let slew = EigenaxisSlew::minimum_jerk(&Quaternion::identity(), &goal, 2.0).unwrap();
+    /// // The rate peaks at 15/8 times the average at the midpoint.
+    /// let peak = slew.state(1.0).angular_velocity;
+    /// assert!((peak - Vector3d::new([0.0, 15.0 / 16.0, 0.0])).norm() < 1.0e-12);
+    /// ```
+    pub fn minimum_jerk(start: &Quaternion, goal: &Quaternion, duration: f64) -> Result<Self, OrientationError> {
+        if duration <= 0.0 || !duration.is_finite() {
+            return Err(OrientationError::InvalidParameter("duration must be positive and finite"))
+        }
+
+        let (axis, angle) = Self::eigenaxis(start, goal);
+        Ok(Self {
+            start: *start,
+            axis,
+            angle,
+            profile: Profile::MinimumJerk { angle, duration }
+        })
+    }
+
+    /// The body-frame unit axis and angle of the shorter rotation from
+    /// `start` to `goal`. The axis is arbitrary if they coincide.
+    fn eigenaxis(start: &Quaternion, goal: &Quaternion) -> (Vector3d, f64) {
+        let delta = start.inverse_unchecked().multiply(goal).rotation_vector();
+        let angle = delta.norm();
+        let axis = if angle > DBL_EPSILON { delta.scalar_multiple(1.0 / angle) } else { Vector3d::unit_x() };
+        (axis, angle)
+    }
+
+    /// The duration of the maneuver, in seconds.
+    #[must_use]
+    pub fn duration(&self) -> f64 {
+        self.profile.duration()
+    }
+
+    /// The body-frame unit axis of rotation.
+    #[must_use]
+    pub fn axis(&self) -> Vector3d {
+        self.axis
+    }
+
+    /// The total angle turned, between 0 and pi.
+    #[must_use]
+    pub fn angle(&self) -> f64 {
+        self.angle
+    }
+
+    /// The state `t` seconds into the maneuver. Before the start and
+    /// after the end the vehicle is at rest at the start or goal.
+    #[must_use]
+    pub fn state(&self, t: f64) -> SlewState {
+        let (angle, rate, acceleration) = self.profile.evaluate(t);
+        SlewState {
+            orientation: self.start.multiply(&Quaternion::from_angle_axis(angle, &self.axis)),
+            angular_velocity: self.axis.scalar_multiple(rate),
+            angular_acceleration: self.axis.scalar_multiple(acceleration)
+        }
+    }
+
+    /// The state at `rate` evenly spaced times per second from the start
+    /// of the maneuver, including both ends.
+    ///
+    /// # Panics
+    /// Panics if the rate is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let goal = Quaternion::from_angle_axis(1.0, &Vector3d::unit_x());
+    /// let slew = EigenaxisSlew::trapezoidal(&Quaternion::identity(), &goal, 2.0, 0.5).unwrap();
+    /// assert_eq!(21, slew.sample(10.0).len());
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn sample(&self, rate: f64) -> Vec<(f64, SlewState)> {
+        assert!(rate > 0.0 && rate.is_finite(), "Rate must be positive and finite");
+        let duration = self.duration();
+        let steps = (duration * rate).ceil() as usize;
+        (0..=steps)
+            .map(|i| {
+                let t = (i as f64 / rate).min(duration);
+                (t, self.state(t))
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    use crate::sequence::angular_velocity_between;
+
+    fn endpoints() -> (Quaternion, Quaternion) {
+        let start = Quaternion::from_angle_axis(0.4, &Vector3d::new([1.0, -1.0, 0.3]));
+        let goal = Quaternion::from_angle_axis(2.0, &Vector3d::new([0.2, 1.0, 1.0]));
+        (start, goal)
+    }
+
+    #[test]
+    fn reaches_goal_within_limits() {
+        let (start, goal) = endpoints();
+        let slew = EigenaxisSlew::plan(&start, &goal, 0.5, 0.2).unwrap();
+        assert_quat_approx_eq!(start, slew.state(0.0).orientation, 1.0e-12);
+        assert!(slew.state(slew.duration()).orientation.angle_to(&goal) < 1.0e-12);
+
+        for i in 0..=100 {
+            let s = slew.state(slew.duration() * f64::from(i) / 100.0);
+            assert!(s.angular_velocity.norm() <= 0.5 + 1.0e-12);
+            assert!(s.angular_acceleration.norm() <= 0.2 + 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn rate_integrates_to_orientation() {
+        let (start, goal) = endpoints();
+        let slew = EigenaxisSlew::plan(&start, &goal, 0.5, 0.2).unwrap();
+        let steps = 10_000;
+        let dt = slew.duration() / f64::from(steps);
+        let integrated = (0..steps).fold(start, |q, i| {
+            q.integrate(&slew.state((f64::from(i) + 0.5) * dt).angular_velocity, dt)
+        });
+        assert!(integrated.angle_to(&goal) < 1.0e-6);
+    }
+
+    #[test]
+    fn short_slew_is_triangular() {
+        let goal = Quaternion::from_angle_axis(0.1, &Vector3d::unit_y());
+        let slew = EigenaxisSlew::plan(&Quaternion::identity(), &goal, 1.0, 0.1).unwrap();
+        // Peak rate sqrt(0.1 * 0.1) = 0.1 is reached at the midpoint.
+        assert_float_approx_eq!(2.0, slew.duration(), 1.0e-12);
+        assert_vector_approx_eq!(Vector3d::unit_y().scalar_multiple(0.1), slew.state(1.0).angular_velocity, 1.0e-12);
+    }
+
+    #[test]
+    fn takes_shorter_way_round() {
+        let goal = Quaternion::from_angle_axis(1.5 * PI, &Vector3d::unit_z());
+        let slew = EigenaxisSlew::plan(&Quaternion::identity(), &goal, 1.0, 1.0).unwrap();
+        assert_float_approx_eq!(0.5 * PI, slew.angle(), 1.0e-12);
+        assert_vector_approx_eq!(Vector3d::unit_z().negate(), slew.axis(), 1.0e-12);
+    }
+
+    #[test]
+    fn zero_length_slew() {
+        let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x());
+        let slew = EigenaxisSlew::plan(&q, &q, 1.0, 1.0).unwrap();
+        assert_float_approx_eq!(0.0, slew.duration());
+        assert_quat_approx_eq!(q, slew.state(1.0).orientation);
+    }
+
+    #[test]
+    fn invalid_limits() {
+        let (start, goal) = endpoints();
+        assert!(EigenaxisSlew::plan(&start, &goal, 0.0, 1.0).is_err());
+        assert!(EigenaxisSlew::plan(&start, &goal, 1.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn trapezoidal_takes_requested_time() {
+        let (start, goal) = endpoints();
+        let slew = EigenaxisSlew::trapezoidal(&start, &goal, 3.0, 0.2).unwrap();
+        assert_float_approx_eq!(3.0, slew.duration(), 1.0e-12);
+
+        let samples = slew.sample(100.0);
+        assert_eq!(301, samples.len());
+        assert!(samples[300].1.orientation.angle_to(&goal) < 1.0e-12);
+        // The rate ramps linearly over the first 0.6 seconds.
+        let peak = slew.angle() / 2.4;
+        assert_float_approx_eq!(peak / 2.0, samples[30].1.angular_velocity.norm(), 1.0e-12);
+        assert_float_approx_eq!(peak, samples[150].1.angular_velocity.norm(), 1.0e-12);
+    }
+
+    #[test]
+    fn trapezoidal_invalid_parameters() {
+        let (start, goal) = endpoints();
+        assert!(EigenaxisSlew::trapezoidal(&start, &goal, 0.0, 0.2).is_err());
+        assert!(EigenaxisSlew::trapezoidal(&start, &goal, 1.0, 0.0).is_err());
+        assert!(EigenaxisSlew::trapezoidal(&start, &goal, 1.0, 0.6).is_err());
+    }
+
+    #[test]
+    fn minimum_jerk_boundary_conditions() {
+        let (start, goal) = endpoints();
+        let slew = EigenaxisSlew::minimum_jerk(&start, &goal, 5.0).unwrap();
+        for &t in &[0.0, 5.0] {
+            let s = slew.state(t);
+            assert_vector_approx_eq!(Vector3d::zero(), s.angular_velocity, 1.0e-12);
+            assert_vector_approx_eq!(Vector3d::zero(), s.angular_acceleration, 1.0e-12);
+        }
+        assert!(slew.state(5.0).orientation.angle_to(&goal) < 1.0e-12);
+        assert!(slew.state(2.5).orientation.angle_to(&start.slerp(&goal, 0.5)) < 1.0e-12);
+    }
+
+    #[test]
+    fn minimum_jerk_derivatives() {
+        // Compare the rate and acceleration with finite differences.
+        let (start, goal) = endpoints();
+        let slew = EigenaxisSlew::minimum_jerk(&start, &goal, 5.0).unwrap();
+        let h = 1.0e-5;
+        for &t in &[0.7, 2.0, 4.1] {
+            let (before, at, after) = (slew.state(t - h), slew.state(t), slew.state(t + h));
+            let rate = angular_velocity_between(&before.orientation, &after.orientation, 2.0 * h);
+            assert_vector_approx_eq!(rate, at.angular_velocity, 1.0e-8);
+            let acceleration = (after.angular_velocity - before.angular_velocity).scalar_multiple(0.5 / h);
+            assert_vector_approx_eq!(acceleration, at.angular_acceleration, 1.0e-8);
+        }
+    }
+}
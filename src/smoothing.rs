@@ -0,0 +1,382 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::constants::DBL_EPSILON;
+use crate::error::OrientationError;
+
+/// The largest number of times the tangent-space fit is re-centered on
+/// its own estimate.
+const MAX_RECENTER_ITERATIONS: usize = 10;
+
+/// A smoothed sample of an orientation sequence, with whichever
+/// derivatives the fitted polynomial can provide.
+#[derive(Copy, Clone, Debug)]
+pub struct SmoothedSample {
+    /// The timestamp, in seconds.
+    pub time: f64,
+    /// The smoothed orientation.
+    pub orientation: Quaternion,
+    /// The body-frame angular velocity, in radians per second, if the
+    /// polynomial degree is at least one.
+    pub angular_velocity: Option<Vector3d>,
+    /// The body-frame angular acceleration, in radians per second
+    /// squared, if the polynomial degree is at least two.
+    pub angular_acceleration: Option<Vector3d>
+}
+
+/// A Savitzky-Golay smoother for timestamped orientation sequences.
+///
+/// Each sample is replaced by a least-squares polynomial fit over the
+/// surrounding window. The fit is done in the tangent space around the
+/// local estimate rather than on quaternion components, so the results
+/// stay unit-norm and sign flips in the input are harmless. Timestamps
+/// need not be evenly spaced.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SavitzkyGolay {
+    half_window: usize,
+    degree: usize
+}
+
+impl SavitzkyGolay {
+    /// Create a new smoother fitting polynomials of the given degree to
+    /// windows of `2 * half_window + 1` samples.
+    ///
+    /// # Errors
+    /// Returns an error if the window has too few samples to fit the
+    /// polynomial, i.e. if `2 * half_window + 1 <= degree`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// assert!(SavitzkyGolay::new(3, 2).is_ok());
+    /// assert!(SavitzkyGolay::new(1, 3).is_err());
+    /// ```
+    pub fn new(half_window: usize, degree: usize) -> Result<Self, OrientationError> {
+        if 2 * half_window < degree {
+            return Err(OrientationError::InvalidParameter("window is too short for the polynomial degree"))
+        }
+        Ok(Self {
+            half_window,
+            degree
+        })
+    }
+
+    /// Smooth a timestamped orientation sequence. Near the ends of the
+    /// sequence the window is shifted inwards rather than shortened.
+    ///
+    /// # Errors
+    /// Returns an error if the sequence is shorter than the window, or
+    /// if the timestamps are not strictly increasing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let samples: Vec<(f64, Quaternion)> = (0..20)
+    ///     .map(|i| (f64::from(i) * 0.1, Quaternion::from_angle_axis(f64::from(i) * 0.05, &Vector3d::unit_z())))
+    ///     .collect();
+    /// let smoothed = SavitzkyGolay::new(2, 2).unwrap().smooth(&samples).unwrap();
+    /// let omega = smoothed[0].angular_velocity.unwrap();
+    /// assert!((omega - Vector3d::new([0.0, 0.0, 0.5])).norm() < 1.0e-9);
+    /// ```
+    pub fn smooth(&self, samples: &[(f64, Quaternion)]) -> Result<Vec<SmoothedSample>, OrientationError> {
+        let window = 2 * self.half_window + 1;
+        if samples.len() < window {
+            return Err(OrientationError::InsufficientData("sequence is shorter than the smoothing window"))
+        }
+        if samples.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(OrientationError::NotIncreasing)
+        }
+
+        let smoothed = (0..samples.len())
+            .map(|i| {
+                let start = i.saturating_sub(self.half_window).min(samples.len() - window);
+                self.fit(&samples[start..start + window], samples[i])
+            })
+            .collect();
+        Ok(smoothed)
+    }
+
+    /// Fit the window around one sample, re-centering the tangent space
+    /// on the fitted orientation until the fit passes through it.
+    fn fit(&self, window: &[(f64, Quaternion)], sample: (f64, Quaternion)) -> SmoothedSample {
+        let (time, mut base) = sample;
+        // Scale time by the window span so the normal equations stay
+        // well conditioned whatever the sample rate.
+        let scale = window
+            .iter()
+            .map(|(t, _)| (t - time).abs())
+            .fold(0.0, f64::max);
+        let offsets: Vec<f64> = window.iter().map(|(t, _)| (t - time) / scale).collect();
+
+        let mut coefficients = Vec::new();
+        for _ in 0..MAX_RECENTER_ITERATIONS {
+            let inverse = base.inverse_unchecked();
+            let tangents: Vec<Vector3d> = window
+                .iter()
+                .map(|(_, q)| inverse.multiply(q).rotation_vector())
+                .collect();
+            coefficients = polynomial_fit(&offsets, &tangents, self.degree);
+            let offset = coefficients[0];
+            base = base.multiply(&Quaternion::from_rotation_vector(&offset));
+            if offset.norm() < DBL_EPSILON {
+                break
+            }
+        }
+
+        // With the fit centered on the estimate, the tangent-space
+        // derivatives at zero are the body-frame rates.
+        SmoothedSample {
+            time,
+            orientation: base.normalized().unwrap_or(base),
+            angular_velocity: coefficients.get(1).map(|c| c.scalar_multiple(1.0 / scale)),
+            angular_acceleration: coefficients.get(2).map(|c| c.scalar_multiple(2.0 / (scale * scale)))
+        }
+    }
+}
+
+/// A first-order low-pass (exponential smoothing) filter for live
+/// orientation streams.
+///
+/// Each update moves the estimate a fixed fraction of the way along the
+/// shorter arc to the new sample, so inputs that flip between `q` and
+/// `-q` are handled, and the estimate is renormalized as it goes.
+#[derive(Copy, Clone, Debug)]
+pub struct LowPassFilter {
+    smoothing: f64,
+    estimate: Option<Quaternion>
+}
+
+impl LowPassFilter {
+    /// Create a new filter with a smoothing factor between zero
+    /// (exclusive) and one. A factor of one passes samples through
+    /// unchanged; smaller factors smooth more heavily.
+    ///
+    /// # Panics
+    /// Panics if the smoothing factor is not in (0, 1].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let filter = LowPassFilter::new(0.2);
+    /// assert!(filter.estimate().is_none());
+    /// ```
+    #[must_use]
+    pub fn new(smoothing: f64) -> Self {
+        assert!(smoothing > 0.0 && smoothing <= 1.0, "Smoothing factor must be in (0, 1]");
+        Self {
+            smoothing,
+            estimate: None
+        }
+    }
+
+    /// Create a new filter from a cutoff frequency and the rate at
+    /// which samples arrive, both in hertz.
+    ///
+    /// # Panics
+    /// Panics if either frequency is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let filter = LowPassFilter::from_cutoff(5.0, 200.0);
+    /// assert!(filter.smoothing() < 0.2);
+    /// ```
+    #[must_use]
+    pub fn from_cutoff(cutoff: f64, sample_rate: f64) -> Self {
+        assert!(cutoff > 0.0 && cutoff.is_finite() && sample_rate > 0.0 && sample_rate.is_finite(),
+                "Frequencies must be positive and finite");
+        let dt = 1.0 / sample_rate;
+        let time_constant = 1.0 / (2.0 * std::f64::consts::PI * cutoff);
+        Self::new(dt / (time_constant + dt))
+    }
+
+    /// The smoothing factor.
+    #[must_use]
+    pub fn smoothing(&self) -> f64 {
+        self.smoothing
+    }
+
+    /// The current estimate, or `None` before the first sample.
+    #[must_use]
+    pub fn estimate(&self) -> Option<Quaternion> {
+        self.estimate
+    }
+
+    /// Filter a new sample, returning the new estimate. The first sample
+    /// is taken as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mut filter = LowPassFilter::new(0.5);
+    /// filter.update(&Quaternion::identity());
+    /// let q = filter.update(&Quaternion::from_angle_axis(0.2, &Vector3d::unit_z()));
+    /// assert!(q.angle_to(&Quaternion::from_angle_axis(0.1, &Vector3d::unit_z())) < 1.0e-12);
+    /// ```
+    pub fn update(&mut self, sample: &Quaternion) -> Quaternion {
+        let next = match self.estimate {
+            Some(estimate) => estimate.slerp(sample, self.smoothing),
+            None => *sample
+        };
+        let next = next.normalized().unwrap_or(next);
+        self.estimate = Some(next);
+        next
+    }
+
+    /// Forget the current estimate, so the next sample is taken as is.
+    pub fn reset(&mut self) {
+        self.estimate = None;
+    }
+}
+
+/// Least-squares polynomial coefficients, lowest order first, fitting
+/// vector values at the given abscissae.
+#[allow(clippy::many_single_char_names, clippy::needless_range_loop)]
+fn polynomial_fit(x: &[f64], y: &[Vector3d], degree: usize) -> Vec<Vector3d> {
+    let n = degree + 1;
+    let powers = |t: f64| (0..n).scan(1.0, move |p, _| {
+        let current = *p;
+        *p *= t;
+        Some(current)
+    });
+
+    // Normal equations, with the three right-hand sides stored
+    // alongside the matrix.
+    let mut a = vec![vec![0.0; n]; n];
+    let mut b = vec![Vector3d::zero(); n];
+    for (&t, v) in x.iter().zip(y.iter()) {
+        let p: Vec<f64> = powers(t).collect();
+        for i in 0..n {
+            for j in 0..n {
+                a[i][j] += p[i] * p[j];
+            }
+            b[i] += v.scalar_multiple(p[i]);
+        }
+    }
+
+    // Gaussian elimination with partial pivoting.
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap_or(col);
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            for k in col..n {
+                a[row][k] -= factor * a[col][k];
+            }
+            b[row] = b[row] - b[col].scalar_multiple(factor);
+        }
+    }
+    let mut c = vec![Vector3d::zero(); n];
+    for row in (0..n).rev() {
+        let sum = (row + 1..n).fold(b[row], |sum, k| sum - c[k].scalar_multiple(a[row][k]));
+        c[row] = sum.scalar_multiple(1.0 / a[row][row]);
+    }
+    c
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::random::standard_normal_vector;
+
+    fn trajectory(t: f64) -> Quaternion {
+        // Constant angular acceleration about a fixed axis.
+        Quaternion::from_angle_axis(0.5 * t + 0.8 * t * t, &Vector3d::new([1.0, -1.0, 2.0]))
+    }
+
+    #[test]
+    fn exact_for_polynomial_motion() {
+        let axis = Vector3d::new([1.0, -1.0, 2.0]).normalized().unwrap();
+        let samples: Vec<(f64, Quaternion)> = (0..15)
+            .map(|i| {
+                let t = f64::from(i) * 0.05;
+                (t, trajectory(t))
+            })
+            .collect();
+        let smoothed = SavitzkyGolay::new(3, 2).unwrap().smooth(&samples).unwrap();
+
+        for (s, (t, q)) in smoothed.iter().zip(samples.iter()) {
+            assert_quat_approx_eq!(*q, s.orientation, 1.0e-9);
+            let omega = axis.scalar_multiple(0.5 + 1.6 * t);
+            assert_vector_approx_eq!(omega, s.angular_velocity.unwrap(), 1.0e-6);
+            assert_vector_approx_eq!(axis.scalar_multiple(1.6), s.angular_acceleration.unwrap(), 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn reduces_noise_and_ignores_sign_flips() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples: Vec<(f64, Quaternion)> = (0..200)
+            .map(|i| {
+                let t = f64::from(i) * 0.01;
+                let noise = Quaternion::from_rotation_vector(&standard_normal_vector(&mut rng).scalar_multiple(0.01));
+                let q = trajectory(t).multiply(&noise);
+                // Flip every other sample into the opposite hemisphere.
+                let q = if i % 2 == 0 { q } else { q.scale(-1.0) };
+                (t, q)
+            })
+            .collect();
+        let smoothed = SavitzkyGolay::new(10, 2).unwrap().smooth(&samples).unwrap();
+
+        let rms = |errors: &mut dyn Iterator<Item = f64>| {
+            (errors.map(|e| e * e).sum::<f64>() / 200.0).sqrt()
+        };
+        let raw = rms(&mut samples.iter().map(|(t, q)| q.angle_to(&trajectory(*t))));
+        let filtered = rms(&mut smoothed.iter().map(|s| s.orientation.angle_to(&trajectory(s.time))));
+        assert!(filtered < raw / 2.0);
+    }
+
+    #[test]
+    fn derivatives_follow_degree() {
+        let samples: Vec<(f64, Quaternion)> = (0..5).map(|i| (f64::from(i), Quaternion::identity())).collect();
+        let s = SavitzkyGolay::new(2, 0).unwrap().smooth(&samples).unwrap();
+        assert!(s[0].angular_velocity.is_none());
+        let s = SavitzkyGolay::new(2, 1).unwrap().smooth(&samples).unwrap();
+        assert!(s[0].angular_velocity.is_some() && s[0].angular_acceleration.is_none());
+    }
+
+    #[test]
+    fn sequence_shorter_than_window() {
+        let samples = [(0.0, Quaternion::identity()), (1.0, Quaternion::identity())];
+        assert!(SavitzkyGolay::new(1, 1).unwrap().smooth(&samples).is_err());
+    }
+
+    #[test]
+    fn low_pass_converges_through_sign_flips() {
+        let target = Quaternion::from_angle_axis(1.0, &Vector3d::new([0.0, 1.0, 1.0]));
+        let mut filter = LowPassFilter::new(0.1);
+        filter.update(&Quaternion::identity());
+        for i in 0..500 {
+            let sample = if i % 2 == 0 { target } else { target.scale(-1.0) };
+            let q = filter.update(&sample);
+            assert_float_approx_eq!(1.0, q.dot(&q), 1.0e-12);
+        }
+        assert_quat_approx_eq!(target, filter.estimate().unwrap(), 1.0e-9);
+    }
+
+    #[test]
+    fn low_pass_reset() {
+        let mut filter = LowPassFilter::new(0.1);
+        filter.update(&Quaternion::identity());
+        filter.reset();
+        let q = Quaternion::from_angle_axis(0.5, &Vector3d::unit_x());
+        assert_quat_approx_eq!(q, filter.update(&q));
+    }
+
+    #[test]
+    #[should_panic(expected = "Smoothing factor must be in (0, 1]")]
+    fn low_pass_invalid_smoothing() {
+        let _ = LowPassFilter::new(0.0);
+    }
+}
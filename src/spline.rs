@@ -0,0 +1,361 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::constants::DBL_EPSILON;
+use crate::sequence::angular_velocity_between;
+use crate::error::OrientationError;
+
+/// The largest number of relinearizations when fitting.
+const MAX_FIT_ITERATIONS: usize = 20;
+
+/// A continuous orientation trajectory that follows the geodesic (the
+/// shortest rotation) between consecutive knots, so its angular
+/// velocity is constant between knots.
+#[derive(Clone, Debug)]
+pub struct PiecewiseGeodesic {
+    knots: Vec<(f64, Quaternion)>
+}
+
+impl PiecewiseGeodesic {
+    /// Create a trajectory through the given (time, orientation) knots.
+    ///
+    /// # Errors
+    /// Returns an error if there are no knots or the times are not
+    /// finite and strictly increasing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let knots = vec![(0.0, Quaternion::identity()), (1.0, Quaternion::from_angle_axis(0.5, &Vector3d::unit_x()))];
+    /// let trajectory = PiecewiseGeodesic::new(knots).unwrap();
+    /// let q = trajectory.evaluate(0.5);
+    /// assert!(q.angle_to(&Quaternion::from_angle_axis(0.25, &Vector3d::unit_x())) < 1.0e-12);
+    /// ```
+    pub fn new(knots: Vec<(f64, Quaternion)>) -> Result<Self, OrientationError> {
+        if knots.is_empty() {
+            return Err(OrientationError::InsufficientData("need at least one knot"))
+        }
+        if knots.iter().any(|(time, _)| !time.is_finite()) {
+            return Err(OrientationError::NotFinite)
+        }
+        if knots.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(OrientationError::NotIncreasing)
+        }
+        Ok(Self { knots })
+    }
+
+    /// Fit a trajectory to noisy timestamped orientations by penalized
+    /// least squares. Knots are spaced evenly, about `knot_spacing`
+    /// seconds apart, across the span of the samples. The fit minimizes
+    /// the sum of squared geodesic errors to the samples plus
+    /// `roughness` times the sum of squared changes in the rotation
+    /// between consecutive knots; larger values give smoother
+    /// trajectories. The cost is minimized by repeated linearization,
+    /// which assumes the trajectory turns by well under a radian
+    /// between knots.
+    ///
+    /// # Errors
+    /// Returns an error if the samples are empty or their times are not
+    /// finite and strictly increasing, if the knot spacing is not
+    /// positive and finite or the roughness is negative or not finite,
+    /// or if the fit is underdetermined (a knot without nearby samples
+    /// and no roughness penalty).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let samples: Vec<(f64, Quaternion)> = (0..50)
+    ///     .map(|i| (f64::from(i) * 0.02, Quaternion::from_angle_axis(f64::from(i) * 0.02, &Vector3d::unit_z())))
+    ///     .collect();
+    /// let trajectory = PiecewiseGeodesic::fit(&samples, 0.1, 1.0).unwrap();
+    /// let expected = Quaternion::from_angle_axis(0.55, &Vector3d::unit_z());
+    /// assert!(trajectory.evaluate(0.55).angle_to(&expected) < 1.0e-6);
+    /// ```
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn fit(samples: &[(f64, Quaternion)], knot_spacing: f64, roughness: f64) -> Result<Self, OrientationError> {
+        let (first, last) = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return Err(OrientationError::InsufficientData("cannot fit an empty sequence"))
+        };
+        if samples.iter().any(|(time, _)| !time.is_finite()) {
+            return Err(OrientationError::NotFinite)
+        }
+        if samples.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(OrientationError::NotIncreasing)
+        }
+        if !(knot_spacing > 0.0 && knot_spacing.is_finite()) {
+            return Err(OrientationError::InvalidParameter("knot spacing must be positive and finite"))
+        }
+        if !(roughness >= 0.0 && roughness.is_finite()) {
+            return Err(OrientationError::InvalidParameter("roughness must be non-negative and finite"))
+        }
+
+        let segments = (((last - first) / knot_spacing).ceil() as usize).max(1);
+        let h = ((last - first) / segments as f64).max(knot_spacing);
+        let times: Vec<f64> = (0..=segments).map(|i| first + i as f64 * h).collect();
+
+        // Start from the nearest sample at each knot.
+        let mut knots: Vec<(f64, Quaternion)> = times
+            .iter()
+            .map(|&t| {
+                let nearest = samples
+                    .iter()
+                    .min_by(|a, b| (a.0 - t).abs().total_cmp(&(b.0 - t).abs()))
+                    .map_or_else(Quaternion::identity, |s| s.1);
+                (t, nearest)
+            })
+            .collect();
+
+        for _ in 0..MAX_FIT_ITERATIONS {
+            let trajectory = Self { knots };
+            let delta = trajectory.fit_step(samples, roughness)?;
+            knots = trajectory.knots;
+            for ((_, q), d) in knots.iter_mut().zip(delta.iter()) {
+                let updated = q.multiply(&Quaternion::from_rotation_vector(d));
+                *q = updated.normalized().unwrap_or(updated);
+            }
+            if delta.iter().all(|d| d.norm() < 1.0e3 * DBL_EPSILON) {
+                break
+            }
+        }
+        Ok(Self { knots })
+    }
+
+    /// One linearized least-squares step: the body-frame correction to
+    /// each knot. Both the data and the penalty terms weight the three
+    /// components of the corrections identically, so one scalar banded
+    /// system serves all three.
+    fn fit_step(&self, samples: &[(f64, Quaternion)], roughness: f64) -> Result<Vec<Vector3d>, OrientationError> {
+        let n = self.knots.len();
+        let mut system = BandedSystem::new(n);
+
+        for (t, q) in samples {
+            let (i, u) = self.locate(*t);
+            let residual = self.evaluate(*t).inverse_unchecked().multiply(q).rotation_vector();
+            let weights = [(i, 1.0 - u), ((i + 1).min(n - 1), u)];
+            for &(row, wr) in &weights {
+                for &(col, wc) in &weights {
+                    system.add(row, col, wr * wc);
+                }
+                system.rhs[row] += residual.scalar_multiple(wr);
+            }
+        }
+
+        let steps: Vec<Vector3d> = self.knots
+            .windows(2)
+            .map(|pair| pair[0].1.inverse_unchecked().multiply(&pair[1].1).rotation_vector())
+            .collect();
+        for (i, pair) in steps.windows(2).enumerate() {
+            // The change in step across knot i + 1, which moves with the
+            // corrections as delta[i] - 2 delta[i + 1] + delta[i + 2].
+            let change = pair[1] - pair[0];
+            let stencil = [(i, 1.0), (i + 1, -2.0), (i + 2, 1.0)];
+            for &(row, wr) in &stencil {
+                for &(col, wc) in &stencil {
+                    system.add(row, col, roughness * wr * wc);
+                }
+                system.rhs[row] -= change.scalar_multiple(roughness * wr);
+            }
+        }
+
+        system.solve()
+    }
+
+    /// The segment containing a time and the fraction of the way through
+    /// it, clamped to the knot span.
+    fn locate(&self, time: f64) -> (usize, f64) {
+        let n = self.knots.len();
+        if n == 1 || time <= self.knots[0].0 {
+            return (0, 0.0)
+        }
+        if time >= self.knots[n - 1].0 {
+            return (n - 2, 1.0)
+        }
+        let i = self.knots.partition_point(|(t, _)| *t <= time) - 1;
+        let (t0, t1) = (self.knots[i].0, self.knots[i + 1].0);
+        (i, (time - t0) / (t1 - t0))
+    }
+
+    /// The knots.
+    #[must_use]
+    pub fn knots(&self) -> &[(f64, Quaternion)] {
+        &self.knots
+    }
+
+    /// The orientation at a given time. Outside the knot span the
+    /// nearest end is held.
+    #[must_use]
+    pub fn evaluate(&self, time: f64) -> Quaternion {
+        let (i, u) = self.locate(time);
+        match self.knots.get(i + 1) {
+            Some((_, next)) => self.knots[i].1.slerp(next, u),
+            None => self.knots[i].1
+        }
+    }
+
+    /// The body-frame angular velocity at a given time, which is
+    /// constant between knots and zero outside the knot span.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let knots = vec![(0.0, Quaternion::identity()), (2.0, Quaternion::from_angle_axis(0.5, &Vector3d::unit_x()))];
+    /// let trajectory = PiecewiseGeodesic::new(knots).unwrap();
+    /// assert!((trajectory.angular_velocity(1.0) - Vector3d::new([0.25, 0.0, 0.0])).norm() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn angular_velocity(&self, time: f64) -> Vector3d {
+        let n = self.knots.len();
+        if n == 1 || time < self.knots[0].0 || time > self.knots[n - 1].0 {
+            return Vector3d::zero()
+        }
+        let (i, _) = self.locate(time);
+        let ((t0, q0), (t1, q1)) = (self.knots[i], self.knots[i + 1]);
+        angular_velocity_between(&q0, &q1, t1 - t0)
+    }
+}
+
+/// A symmetric system of linear equations whose matrix has at most two
+/// nonzero entries either side of the diagonal, with vector right-hand
+/// sides.
+struct BandedSystem {
+    /// `band[i][k]` holds the entry in row `i`, column `i + k - 2`.
+    band: Vec<[f64; 5]>,
+    rhs: Vec<Vector3d>
+}
+
+impl BandedSystem {
+    fn new(n: usize) -> Self {
+        Self {
+            band: vec![[0.0; 5]; n],
+            rhs: vec![Vector3d::zero(); n]
+        }
+    }
+
+    fn get(&self, row: usize, col: usize) -> f64 {
+        self.band[row][col + 2 - row]
+    }
+
+    fn add(&mut self, row: usize, col: usize, value: f64) {
+        self.band[row][col + 2 - row] += value;
+    }
+
+    /// Solve by Gaussian elimination, which needs no pivoting as the
+    /// system is positive semi-definite.
+    fn solve(mut self) -> Result<Vec<Vector3d>, OrientationError> {
+        let n = self.rhs.len();
+        let scale = self.band.iter().map(|row| row[2]).fold(0.0, f64::max);
+        for col in 0..n {
+            let pivot = self.get(col, col);
+            if pivot <= DBL_EPSILON * scale {
+                return Err(OrientationError::InsufficientData("fit is underdetermined; add samples or a roughness penalty"))
+            }
+            for row in col + 1..n.min(col + 3) {
+                let factor = self.get(row, col) / pivot;
+                for k in col..n.min(col + 3) {
+                    let value = self.get(col, k);
+                    self.add(row, k, -factor * value);
+                }
+                self.rhs[row] = self.rhs[row] - self.rhs[col].scalar_multiple(factor);
+            }
+        }
+
+        let mut x = vec![Vector3d::zero(); n];
+        for row in (0..n).rev() {
+            let sum = (row + 1..n.min(row + 3))
+                .fold(self.rhs[row], |sum, k| sum - x[k].scalar_multiple(self.get(row, k)));
+            x[row] = sum.scalar_multiple(1.0 / self.get(row, row));
+        }
+        Ok(x)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::random::standard_normal_vector;
+
+    fn truth(t: f64) -> Quaternion {
+        Quaternion::from_angle_axis(0.8 * t, &Vector3d::new([1.0, 0.5, -0.5]))
+            .multiply(&Quaternion::from_angle_axis(0.3 * (2.0 * t).sin(), &Vector3d::unit_z()))
+    }
+
+    fn noisy_samples(rng: &mut StdRng) -> Vec<(f64, Quaternion)> {
+        (0..300)
+            .map(|i| {
+                let t = f64::from(i) * 0.01;
+                let noise = Quaternion::from_rotation_vector(&standard_normal_vector(rng).scalar_multiple(0.02));
+                (t, truth(t).multiply(&noise))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fit_reduces_noise() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let samples = noisy_samples(&mut rng);
+        let trajectory = PiecewiseGeodesic::fit(&samples, 0.1, 10.0).unwrap();
+
+        let raw: f64 = samples.iter().map(|(t, q)| q.angle_to(&truth(*t)).powi(2)).sum();
+        let fitted: f64 = samples.iter().map(|(t, _)| trajectory.evaluate(*t).angle_to(&truth(*t)).powi(2)).sum();
+        assert!(fitted < raw / 4.0);
+    }
+
+    #[test]
+    fn roughness_smooths() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let samples = noisy_samples(&mut rng);
+        let jerkiness = |trajectory: &PiecewiseGeodesic| {
+            let knots = trajectory.knots();
+            let rates: Vec<Vector3d> = knots.windows(2)
+                .map(|pair| angular_velocity_between(&pair[0].1, &pair[1].1, pair[1].0 - pair[0].0))
+                .collect();
+            rates.windows(2).map(|pair| (pair[1] - pair[0]).norm()).sum::<f64>()
+        };
+        let loose = PiecewiseGeodesic::fit(&samples, 0.05, 0.0).unwrap();
+        let stiff = PiecewiseGeodesic::fit(&samples, 0.05, 100.0).unwrap();
+        assert!(jerkiness(&stiff) < jerkiness(&loose) / 2.0);
+    }
+
+    #[test]
+    fn exact_without_noise_or_penalty() {
+        let knots: Vec<(f64, Quaternion)> = (0..5).map(|i| (f64::from(i), truth(f64::from(i)))).collect();
+        let target = PiecewiseGeodesic::new(knots).unwrap();
+        let samples: Vec<(f64, Quaternion)> = (0..=40)
+            .map(|i| {
+                let t = f64::from(i) * 0.1;
+                (t, target.evaluate(t))
+            })
+            .collect();
+        let fitted = PiecewiseGeodesic::fit(&samples, 1.0, 0.0).unwrap();
+        for ((_, a), (_, b)) in target.knots().iter().zip(fitted.knots().iter()) {
+            assert!(a.angle_to(b) < 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn underdetermined_fit() {
+        let samples = [(0.0, Quaternion::identity()), (1.0, Quaternion::identity())];
+        assert!(PiecewiseGeodesic::fit(&samples, 0.1, 0.0).is_err());
+        assert!(PiecewiseGeodesic::fit(&samples, 0.1, 1.0).is_ok());
+    }
+
+    #[test]
+    fn invalid_input() {
+        let samples = [(0.0, Quaternion::identity()), (f64::NAN, Quaternion::identity())];
+        assert!(matches!(PiecewiseGeodesic::new(samples.to_vec()), Err(OrientationError::NotFinite)));
+        assert!(matches!(PiecewiseGeodesic::fit(&samples, 0.1, 1.0), Err(OrientationError::NotFinite)));
+
+        let samples = [(0.0, Quaternion::identity()), (1.0, Quaternion::identity())];
+        for &(knot_spacing, roughness) in &[(f64::INFINITY, 1.0), (f64::NAN, 1.0), (0.1, -1.0), (0.1, f64::NAN)] {
+            assert!(matches!(PiecewiseGeodesic::fit(&samples, knot_spacing, roughness),
+                             Err(OrientationError::InvalidParameter(_))));
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::matrix3::Matrix3;
+use crate::orientation_covariance::OrientationCovariance;
+use crate::constants::DBL_EPSILON;
+use crate::error::OrientationError;
+
+/// The largest number of iterations used to find a mean rotation.
+const MAX_MEAN_ITERATIONS: usize = 50;
+
+/// The most samples tried as starting points for a robust mean.
+const MAX_MEDOID_CANDIDATES: usize = 200;
+
+/// Summary statistics of a set of rotations. All angles are in radians.
+#[derive(Copy, Clone, Debug)]
+pub struct RotationStatistics {
+    /// The number of rotations summarized.
+    pub count: usize,
+    /// The intrinsic (Karcher) mean: the rotation minimizing the sum of
+    /// squared geodesic distances to the samples.
+    pub mean: Quaternion,
+    /// The sample covariance of the body-frame rotation vectors from
+    /// the mean to each sample, in radians squared, normalized by
+    /// `count - 1`. It is zero for a single sample.
+    pub covariance: Matrix3,
+    /// The mean geodesic angle from the mean to the samples.
+    pub mean_deviation: f64,
+    /// The root-mean-square geodesic angle from the mean to the
+    /// samples.
+    pub rms_deviation: f64,
+    /// The largest geodesic angle from the mean to a sample.
+    pub max_deviation: f64
+}
+
+impl RotationStatistics {
+    /// The mean and covariance as an uncertain rotation, in the error
+    /// convention of `OrientationCovariance`.
+    #[must_use]
+    pub fn orientation_covariance(&self) -> OrientationCovariance {
+        OrientationCovariance::new(self.mean, self.covariance)
+    }
+}
+
+/// The intrinsic mean, tangent-space covariance and dispersion of a set
+/// of rotations. Rotations are compared up to sign. The samples should
+/// lie well within a hemisphere of rotations, as repeated measurements
+/// of one orientation do, for the mean to be unique.
+///
+/// # Errors
+/// Returns an error if there are no rotations.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let samples = [
+///     Quaternion::from_angle_axis(0.1, &Vector3d::unit_x()),
+///     Quaternion::from_angle_axis(-0.1, &Vector3d::unit_x()),
+/// ];
+/// let stats = rotation_statistics(&samples).unwrap();
+/// assert!(stats.mean.angle_to(&Quaternion::identity()) < 1.0e-12);
+/// assert!((stats.mean_deviation - 0.1).abs() < 1.0e-12);
+/// assert!((stats.covariance.data[0][0] - 0.02).abs() < 1.0e-12);
+/// ```
+#[allow(clippy::cast_precision_loss)]
+pub fn rotation_statistics(rotations: &[Quaternion]) -> Result<RotationStatistics, OrientationError> {
+    if rotations.is_empty() {
+        return Err(OrientationError::InsufficientData("cannot summarize an empty set of rotations"))
+    }
+    enter_span!(DEBUG, "rotation_statistics", count = rotations.len());
+    let mean = mean_rotation(rotations);
+    let inverse = mean.inverse_unchecked();
+
+    let mut sum = Matrix3::zero();
+    let (mut total, mut total_squared, mut max) = (0.0, 0.0, 0.0_f64);
+    for q in rotations {
+        let delta = inverse.multiply(q).rotation_vector();
+        let angle = delta.norm();
+        sum = sum + delta.outer(&delta);
+        total += angle;
+        total_squared += angle * angle;
+        max = max.max(angle);
+    }
+
+    let n = rotations.len() as f64;
+    let covariance = if rotations.len() > 1 { sum.scalar_multiple(1.0 / (n - 1.0)) } else { Matrix3::zero() };
+    Ok(RotationStatistics {
+        count: rotations.len(),
+        mean,
+        covariance,
+        mean_deviation: total / n,
+        rms_deviation: (total_squared / n).sqrt(),
+        max_deviation: max
+    })
+}
+
+/// An outlier-resistant mean of a set of rotations.
+#[derive(Clone, Debug)]
+pub struct RobustMean {
+    /// The intrinsic mean of the inliers.
+    pub mean: Quaternion,
+    /// The indices of the rotations within the inlier threshold of the
+    /// mean, in increasing order.
+    pub inliers: Vec<usize>
+}
+
+/// The mean of a set of rotations, ignoring outliers: rotations more
+/// than `inlier_threshold` radians from the mean are rejected.
+///
+/// The search starts from the medoid, the sample with the smallest sum
+/// of geodesic angles to the others, which a minority of outliers
+/// cannot drag away however large they are. The inliers of the current
+/// estimate are then averaged, and the inlier set recomputed, until it
+/// stops changing. This is a truncated least-squares M-estimator; with
+/// more than a few hundred samples the medoid is sought among an evenly
+/// spaced subset.
+///
+/// # Errors
+/// Returns an error if there are no rotations or the threshold is not
+/// positive.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let mut readings = vec![Quaternion::from_angle_axis(0.5, &Vector3d::unit_z()); 5];
+/// // A glitch.
+/// readings.push(Quaternion::from_angle_axis(2.0, &Vector3d::unit_x()));
+/// let robust = robust_mean_rotation(&readings, 0.1).unwrap();
+/// assert!(robust.mean.angle_to(&readings[0]) < 1.0e-12);
+/// assert_eq!(vec![0, 1, 2, 3, 4], robust.inliers);
+/// ```
+pub fn robust_mean_rotation(rotations: &[Quaternion], inlier_threshold: f64) -> Result<RobustMean, OrientationError> {
+    if rotations.is_empty() {
+        return Err(OrientationError::InsufficientData("cannot average an empty set of rotations"))
+    }
+    if inlier_threshold.is_nan() || inlier_threshold <= 0.0 {
+        return Err(OrientationError::InvalidParameter("inlier threshold must be positive"))
+    }
+
+    enter_span!(DEBUG, "robust_mean_rotation", count = rotations.len(), inlier_threshold);
+    let mut mean = medoid(rotations);
+    let mut inliers = inliers_of(rotations, &mean, inlier_threshold);
+    for _ in 0..MAX_MEAN_ITERATIONS {
+        let members: Vec<Quaternion> = inliers.iter().map(|&i| rotations[i]).collect();
+        mean = mean_rotation(&members);
+        let updated = inliers_of(rotations, &mean, inlier_threshold);
+        emit_event!(TRACE, inliers = updated.len(), "robust mean iteration");
+        // Averaging can move the mean far enough to lose every inlier
+        // only with a threshold far smaller than the spread; keep the
+        // last non-empty set.
+        if updated == inliers || updated.is_empty() {
+            break
+        }
+        inliers = updated;
+    }
+    emit_event!(DEBUG, inliers = inliers.len(), outliers = rotations.len() - inliers.len(), "robust mean converged");
+    Ok(RobustMean { mean, inliers })
+}
+
+/// The sample, among evenly spaced candidates, with the smallest sum of
+/// geodesic angles to all samples.
+fn medoid(rotations: &[Quaternion]) -> Quaternion {
+    let stride = rotations.len().div_ceil(MAX_MEDOID_CANDIDATES);
+    let cost = |c: &Quaternion| rotations.iter().map(|q| q.angle_to(c)).sum::<f64>();
+    rotations.iter()
+        .step_by(stride)
+        .map(|c| (cost(c), c))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(rotations[0], |(_, c)| *c)
+}
+
+/// The indices of the rotations within `threshold` of `mean`.
+fn inliers_of(rotations: &[Quaternion], mean: &Quaternion, threshold: f64) -> Vec<usize> {
+    rotations.iter()
+        .enumerate()
+        .filter(|(_, q)| q.angle_to(mean) <= threshold)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// The rotation minimizing the sum of squared geodesic distances to a
+/// set of rotations, found by repeatedly averaging in the tangent space.
+pub(crate) fn mean_rotation(rotations: &[Quaternion]) -> Quaternion {
+    iterate_mean(rotations, |inverse| rotations.iter().map(|q| inverse.multiply(q).rotation_vector()).sum())
+}
+
+/// The averaging loop of `mean_rotation`, given a function that sums the
+/// rotation vectors taking the current mean to each rotation, from the
+/// inverse of the mean; the parallel mean supplies its own.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn iterate_mean<F: Fn(&Quaternion) -> Vector3d>(rotations: &[Quaternion], sum_offsets: F) -> Quaternion {
+    let n = rotations.len() as f64;
+    let mut mean = rotations[0];
+    for _ in 0..MAX_MEAN_ITERATIONS {
+        let step = sum_offsets(&mean.inverse_unchecked()).scalar_multiple(1.0 / n);
+        mean = mean.multiply(&Quaternion::from_rotation_vector(&step));
+        emit_event!(TRACE, step = step.norm(), "mean rotation iteration");
+        if step.norm() < DBL_EPSILON {
+            break
+        }
+    }
+    mean.normalized().unwrap_or(mean)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn recovers_sampling_distribution() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let mean = Quaternion::from_angle_axis(2.2, &Vector3d::new([-0.4, 1.0, 0.3]));
+        let covariance = Matrix3::new([[4.0, 1.0, 0.0], [1.0, 2.0, -0.5], [0.0, -0.5, 1.0]]).scalar_multiple(1.0e-4);
+        let samples = OrientationCovariance::new(mean, covariance).samples(&mut rng, 20000).unwrap();
+        let stats = rotation_statistics(&samples).unwrap();
+        assert_eq!(20000, stats.count);
+        assert!(stats.mean.angle_to(&mean) < 1.0e-3);
+        assert_matrix_approx_eq!(covariance, stats.covariance, 1.0e-5);
+        // The squared deviation sums the variances.
+        assert_float_approx_eq!(covariance.trace().sqrt(), stats.rms_deviation, 1.0e-3);
+        assert!(stats.mean_deviation < stats.rms_deviation && stats.rms_deviation < stats.max_deviation);
+        assert!(stats.orientation_covariance().rotation().angle_to(&stats.mean) < 1.0e-12);
+    }
+
+    #[test]
+    fn robust_mean_rejects_outliers() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let truth = Quaternion::from_angle_axis(0.9, &Vector3d::new([1.0, 1.0, -0.5]));
+        let mut rotations = Vec::new();
+        for i in 0..100 {
+            let q = if i % 4 == 3 {
+                // A quarter of the samples are arbitrary.
+                Quaternion::random_within_angle(&mut rng, &truth, std::f64::consts::PI).unwrap()
+            } else {
+                Quaternion::random_perturbation(&mut rng, &truth, 0.01).unwrap()
+            };
+            rotations.push(q);
+        }
+        let plain = rotation_statistics(&rotations).unwrap().mean;
+        let robust = robust_mean_rotation(&rotations, 0.1).unwrap();
+        assert!(robust.mean.angle_to(&truth) < 0.005);
+        assert!(robust.mean.angle_to(&truth) < plain.angle_to(&truth));
+        for i in &robust.inliers {
+            assert!(rotations[*i].angle_to(&robust.mean) <= 0.1);
+        }
+        assert!((75..=80).contains(&robust.inliers.len()));
+        assert!(robust_mean_rotation(&rotations, 0.0).is_err());
+        assert!(robust_mean_rotation(&[], 0.1).is_err());
+    }
+
+    #[test]
+    fn sign_and_single_sample() {
+        let q = Quaternion::from_angle_axis(1.0, &Vector3d::unit_z());
+        let stats = rotation_statistics(&[q, q.scale(-1.0)]).unwrap();
+        assert!(stats.mean.angle_to(&q) < 1.0e-12);
+        assert_float_approx_eq!(0.0, stats.max_deviation, 1.0e-12);
+        let single = rotation_statistics(&[q]).unwrap();
+        assert_matrix_approx_eq!(Matrix3::zero(), single.covariance, 1.0e-12);
+        assert!(rotation_statistics(&[]).is_err());
+    }
+}
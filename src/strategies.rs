@@ -0,0 +1,122 @@
+//! `proptest` strategies for the crate's types, for property-testing
+//! code built on them. Enable the `proptest` feature to use them.
+//!
+//! The uniform strategies draw from the uniform distribution on the
+//! sphere or on rotations, built from independent uniform variables so
+//! that failing cases shrink towards simple values. The edge-case
+//! strategies cover the places rotation code tends to break: the
+//! identity, tiny angles, and half turns, where the axis of a rotation
+//! is ill-conditioned and `q` and `-q` are nearly equidistant from
+//! everything.
+//!
+//! ```
+//! use orientations::*;
+//! use orientations::strategies::unit_quaternion;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn inverse_undoes_rotation(q in unit_quaternion()) {
+//!         let v = Vector3d::new([1.0, 2.0, 3.0]);
+//!         let back = q.inverse_unchecked().rotate_vector(&q.rotate_vector(&v));
+//!         prop_assert!((back - v).norm() < 1.0e-12);
+//!     }
+//! }
+//! # inverse_undoes_rotation();
+//! ```
+
+use std::f64::consts::PI;
+use proptest::prelude::*;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::rotation_matrix::RotationMatrix;
+
+/// Unit vectors distributed uniformly over the sphere.
+pub fn unit_vector() -> impl Strategy<Value = Vector3d> {
+    // By Archimedes' hat-box theorem, z is uniform for a uniform
+    // distribution on the sphere.
+    (-1.0..=1.0_f64, 0.0..2.0 * PI).prop_map(|(z, azimuth)| {
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        Vector3d::new([r * azimuth.cos(), r * azimuth.sin(), z])
+    })
+}
+
+/// Unit quaternions distributed uniformly over rotations, by the method
+/// of Shoemake (1992).
+pub fn unit_quaternion() -> impl Strategy<Value = Quaternion> {
+    (0.0..=1.0_f64, 0.0..2.0 * PI, 0.0..2.0 * PI).prop_map(|(u, a, b)| {
+        let (r1, r2) = ((1.0 - u).sqrt(), u.sqrt());
+        Quaternion::new(r2 * b.cos(), Vector3d::new([r1 * a.sin(), r1 * a.cos(), r2 * b.sin()]))
+    })
+}
+
+/// Rotation matrices distributed uniformly over rotations.
+pub fn rotation_matrix() -> impl Strategy<Value = RotationMatrix> {
+    unit_quaternion().prop_map(|q| q.as_rotation_matrix())
+}
+
+/// Rotations by angles within `1e-6` radians of a half turn, about
+/// uniformly random axes, with either sign.
+pub fn near_half_turn() -> impl Strategy<Value = Quaternion> {
+    (PI - 1.0e-6..=PI, unit_vector(), any::<bool>()).prop_map(|(angle, axis, negate)| {
+        let q = Quaternion::from_angle_axis(angle, &axis);
+        if negate { q.scale(-1.0) } else { q }
+    })
+}
+
+/// Rotations by angles under `1e-6` radians, including the identity,
+/// about uniformly random axes, with either sign.
+pub fn near_identity() -> impl Strategy<Value = Quaternion> {
+    (0.0..1.0e-6_f64, unit_vector(), any::<bool>()).prop_map(|(angle, axis, negate)| {
+        let q = Quaternion::from_angle_axis(angle, &axis);
+        if negate { q.scale(-1.0) } else { q }
+    })
+}
+
+/// Degenerate rotations: the identity and exact half turns about the
+/// coordinate axes, with either sign, and rotations near the identity
+/// or a half turn.
+pub fn degenerate_quaternion() -> impl Strategy<Value = Quaternion> {
+    let exact = (0..4_usize, any::<bool>()).prop_map(|(i, negate)| {
+        let q = match i {
+            0 => Quaternion::identity(),
+            1 => Quaternion::new(0.0, Vector3d::unit_x()),
+            2 => Quaternion::new(0.0, Vector3d::unit_y()),
+            _ => Quaternion::new(0.0, Vector3d::unit_z())
+        };
+        if negate { q.scale(-1.0) } else { q }
+    });
+    prop_oneof![exact, near_identity(), near_half_turn()]
+}
+
+/// Unit quaternions that are mostly uniform, with a quarter drawn from
+/// `degenerate_quaternion`.
+pub fn quaternion_with_edge_cases() -> impl Strategy<Value = Quaternion> {
+    prop_oneof![3 => unit_quaternion(), 1 => degenerate_quaternion()]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn generated_values_are_unit(v in unit_vector(), q in quaternion_with_edge_cases()) {
+            prop_assert!((v.norm() - 1.0).abs() < 1.0e-12);
+            prop_assert!((q.dot(&q) - 1.0).abs() < 1.0e-12);
+        }
+
+        #[test]
+        fn half_turns_are_near_pi(q in near_half_turn()) {
+            prop_assert!(q.angle_to(&Quaternion::identity()) > PI - 1.0e-5);
+        }
+
+        #[test]
+        fn matrices_are_rotations(r in rotation_matrix()) {
+            let m = r.as_matrix3();
+            assert_matrix_approx_eq!(crate::matrix3::Matrix3::identity(), m.multiply(&m.transpose()), 1.0e-12);
+            prop_assert!((m.determinant() - 1.0).abs() < 1.0e-12);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use std::f64::consts::PI;
+use crate::vector3d::Vector3d;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::error::OrientationError;
+
+/// Two rotations closer than this angle are taken to be the same
+/// element when generating a group.
+const SAME_ELEMENT_THRESHOLD: f64 = 1.0e-9;
+
+/// The largest group `SymmetryGroup::from_generators` will build.
+const MAX_GROUP_ORDER: usize = 1000;
+
+/// A finite group of rotations, such as the symmetries of an object or
+/// a crystal lattice, for use with `symmetric_representative` and
+/// `symmetric_distance`.
+///
+/// The catalog constructors follow the usual orientation: the principal
+/// axis along z and, where there are two-fold axes perpendicular to it,
+/// one along x. The tetrahedral, octahedral and icosahedral groups all
+/// have two-fold axes along x, y and z.
+#[derive(Clone, Debug)]
+pub struct SymmetryGroup {
+    rotations: Vec<Quaternion>
+}
+
+impl SymmetryGroup {
+    /// The group generated by the given rotations: every product of
+    /// them.
+    ///
+    /// # Errors
+    /// Returns an error if the generators produce more than 1000
+    /// rotations, as they do when they generate an infinite group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let quarter = Quaternion::from_angle_axis(std::f64::consts::FRAC_PI_2, &Vector3d::unit_z());
+    /// let group = SymmetryGroup::from_generators(&[quarter]).unwrap();
+    /// assert_eq!(4, group.order());
+    /// ```
+    pub fn from_generators(generators: &[Quaternion]) -> Result<Self, OrientationError> {
+        let rotations = closure(generators, MAX_GROUP_ORDER + 1);
+        if rotations.len() > MAX_GROUP_ORDER {
+            return Err(OrientationError::InvalidParameter("generators do not form a group of at most 1000 rotations"))
+        }
+        Ok(Self { rotations })
+    }
+
+    /// The cyclic group `C_n`: turns by multiples of `2 pi / n` about z.
+    ///
+    /// # Panics
+    /// Panics if `n` is zero.
+    #[must_use]
+    pub fn cyclic(n: u32) -> Self {
+        assert!(n > 0, "Order must be positive");
+        let rotations = (0..n)
+            .map(|k| turn(2.0 * PI * f64::from(k) / f64::from(n), &Vector3d::unit_z()))
+            .collect();
+        Self { rotations }
+    }
+
+    /// The dihedral group `D_n`, of order `2n`: the cyclic group `C_n`
+    /// about z together with `n` half turns about axes in the xy-plane,
+    /// the first along x.
+    ///
+    /// # Panics
+    /// Panics if `n` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// assert_eq!(4, SymmetryGroup::dihedral(2).order());
+    /// ```
+    #[must_use]
+    pub fn dihedral(n: u32) -> Self {
+        let mut group = Self::cyclic(n);
+        group.rotations.extend((0..n).map(|k| {
+            let angle = PI * f64::from(k) / f64::from(n);
+            turn(PI, &Vector3d::new([angle.cos(), angle.sin(), 0.0]))
+        }));
+        group
+    }
+
+    /// The tetrahedral group `T`: the 12 rotations of a regular
+    /// tetrahedron.
+    #[must_use]
+    pub fn tetrahedral() -> Self {
+        Self::generated(&[(2.0, [0.0, 0.0, 1.0]), (3.0, [1.0, 1.0, 1.0])])
+    }
+
+    /// The octahedral group `O`: the 24 rotations of a cube or regular
+    /// octahedron.
+    #[must_use]
+    pub fn octahedral() -> Self {
+        Self::generated(&[(4.0, [0.0, 0.0, 1.0]), (3.0, [1.0, 1.0, 1.0])])
+    }
+
+    /// The icosahedral group `I`: the 60 rotations of a regular
+    /// icosahedron or dodecahedron.
+    #[must_use]
+    pub fn icosahedral() -> Self {
+        let golden = 0.5 + 1.25_f64.sqrt();
+        Self::generated(&[(5.0, [0.0, 1.0, golden]), (3.0, [1.0, 1.0, 1.0])])
+    }
+
+    /// The rotations of the group, starting with the identity.
+    #[must_use]
+    pub fn rotations(&self) -> &[Quaternion] {
+        &self.rotations
+    }
+
+    /// The number of rotations in the group.
+    #[must_use]
+    pub fn order(&self) -> usize {
+        self.rotations.len()
+    }
+
+    /// The group generated by turns of `2 pi / n` about each axis.
+    pub(crate) fn generated(generators: &[(f64, [f64; 3])]) -> Self {
+        let generators: Vec<Quaternion> = generators.iter()
+            .map(|(n, axis)| turn(2.0 * PI / n, &Vector3d::new(*axis)))
+            .collect();
+        Self { rotations: closure(&generators, MAX_GROUP_ORDER) }
+    }
+}
+
+/// A rotation by `angle` about `axis`, computed with the standard
+/// library's sine and cosine even under the `fast-math` feature, so the
+/// catalog groups close with their exact orders.
+fn turn(angle: f64, axis: &Vector3d) -> Quaternion {
+    let (sin, cos) = (angle / 2.0).sin_cos();
+    Quaternion::new(cos, axis.scalar_multiple(sin / axis.norm()))
+}
+
+/// Products of the generators, found by multiplying until no new
+/// rotations appear or `limit` have been found. Rotations are
+/// identified up to sign.
+fn closure(generators: &[Quaternion], limit: usize) -> Vec<Quaternion> {
+    let mut elements = vec![Quaternion::identity()];
+    let mut i = 0;
+    while i < elements.len() && elements.len() < limit {
+        for g in generators {
+            let product = elements[i].multiply(g);
+            if elements.len() < limit && elements.iter().all(|e| e.angle_to(&product) > SAME_ELEMENT_THRESHOLD) {
+                elements.push(product);
+            }
+        }
+        i += 1;
+    }
+    elements
+}
+
+/// The rotation equivalent to `rotation` under a set of body-frame
+/// symmetries that is closest to `reference`. If an object looks the
+/// same after being turned by any of `symmetries` about its own axes,
+/// the orientations `rotation * s` are indistinguishable, and this picks
+/// the one nearest the reference; use the identity as the reference for
+/// a canonical representative. The set should include the identity.
+///
+/// The result is returned with the sign placing it in the same
+/// hemisphere as the reference, so representatives of nearby
+/// orientations are also close as 4-vectors. If `symmetries` is empty,
+/// the rotation itself is used.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// use std::f64::consts::PI;
+/// // A box symmetric under a half turn about its z axis.
+/// let symmetries = [Quaternion::identity(), Quaternion::from_angle_axis(PI, &Vector3d::unit_z())];
+/// let pose = Quaternion::from_angle_axis(2.8, &Vector3d::unit_z());
+/// let canonical = symmetric_representative(&pose, &symmetries, &Quaternion::identity());
+/// assert!((canonical.angle_to(&Quaternion::identity()) - (PI - 2.8)).abs() < 1.0e-12);
+/// ```
+#[must_use]
+pub fn symmetric_representative(rotation: &Quaternion, symmetries: &[Quaternion],
+                                reference: &Quaternion) -> Quaternion {
+    let nearest = symmetries.iter()
+        .map(|s| rotation.multiply(s))
+        .min_by(|a, b| a.angle_to(reference).total_cmp(&b.angle_to(reference)))
+        .unwrap_or(*rotation);
+    if nearest.dot(reference) < 0.0 { nearest.scale(-1.0) } else { nearest }
+}
+
+/// The smallest angle, in radians, between two orientations of an
+/// object with the given body-frame symmetries: the angle from `a` to
+/// the nearest orientation equivalent to `b`. Orientations that differ
+/// only by a symmetry are zero apart, which is what deduplicating
+/// grasps or scoring pose estimates of symmetric objects needs.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// use std::f64::consts::PI;
+/// let symmetries = [Quaternion::identity(), Quaternion::from_angle_axis(PI, &Vector3d::unit_z())];
+/// let a = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x());
+/// let b = a.after(&Quaternion::from_angle_axis(PI, &Vector3d::unit_z()));
+/// assert!(symmetric_distance(&a, &b, &symmetries) < 1.0e-12);
+/// ```
+#[must_use]
+pub fn symmetric_distance(a: &Quaternion, b: &Quaternion, symmetries: &[Quaternion]) -> f64 {
+    symmetric_representative(b, symmetries, a).angle_to(a)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn square_prism() -> Vec<Quaternion> {
+        SymmetryGroup::cyclic(4).rotations().to_vec()
+    }
+
+    #[test]
+    fn representative_is_nearest_equivalent() {
+        let symmetries = square_prism();
+        let rotation = Quaternion::from_angle_axis(1.0, &Vector3d::new([1.0, 0.5, 2.0]));
+        let reference = Quaternion::from_angle_axis(-2.0, &Vector3d::new([0.0, 1.0, 1.0]));
+        let nearest = symmetric_representative(&rotation, &symmetries, &reference);
+        assert!(nearest.dot(&reference) >= 0.0);
+        for s in &symmetries {
+            let candidate = rotation.multiply(s);
+            assert!(nearest.angle_to(&reference) <= candidate.angle_to(&reference) + 1.0e-12);
+        }
+        // It is one of the equivalents.
+        assert!(symmetries.iter().any(|s| rotation.multiply(s).angle_to(&nearest) < 1.0e-12));
+    }
+
+    #[test]
+    fn equivalent_orientations_share_a_representative() {
+        let symmetries = square_prism();
+        let rotation = Quaternion::from_angle_axis(0.7, &Vector3d::new([-1.0, 2.0, 0.3]));
+        let canonical = symmetric_representative(&rotation, &symmetries, &Quaternion::identity());
+        for s in &symmetries {
+            let other = symmetric_representative(&rotation.multiply(s), &symmetries, &Quaternion::identity());
+            assert!(canonical.angle_to(&other) < 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn catalog_groups_are_closed() {
+        let groups = [
+            (SymmetryGroup::cyclic(1), 1), (SymmetryGroup::cyclic(5), 5), (SymmetryGroup::dihedral(3), 6),
+            (SymmetryGroup::tetrahedral(), 12), (SymmetryGroup::octahedral(), 24),
+            (SymmetryGroup::icosahedral(), 60)
+        ];
+        for (group, order) in &groups {
+            let rotations = group.rotations();
+            assert_eq!(*order, group.order());
+            assert!(rotations[0].angle_to(&Quaternion::identity()) < 1.0e-12);
+            for a in rotations {
+                for b in rotations {
+                    let product = a.multiply(b);
+                    assert!(rotations.iter().any(|r| r.angle_to(&product) < 1.0e-9));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn subgroups_share_axes() {
+        // T, O and I all contain the half turns about the coordinate
+        // axes, as D2 does.
+        let d2 = SymmetryGroup::dihedral(2);
+        for group in &[SymmetryGroup::tetrahedral(), SymmetryGroup::octahedral(), SymmetryGroup::icosahedral()] {
+            for s in d2.rotations() {
+                assert!(group.rotations().iter().any(|r| r.angle_to(s) < 1.0e-9));
+            }
+        }
+    }
+
+    #[test]
+    fn infinite_generators() {
+        assert!(SymmetryGroup::from_generators(&[Quaternion::from_angle_axis(1.0, &Vector3d::unit_x())]).is_err());
+    }
+
+    #[test]
+    fn distance() {
+        let symmetries = square_prism();
+        let a = Quaternion::from_angle_axis(0.2, &Vector3d::unit_y());
+        let b = a.multiply(&Quaternion::from_angle_axis(FRAC_PI_2 + 0.1, &Vector3d::unit_z()));
+        assert_float_approx_eq!(0.1, symmetric_distance(&a, &b, &symmetries), 1.0e-12);
+        assert_float_approx_eq!(a.angle_to(&b), symmetric_distance(&a, &b, &[]), 1.0e-12);
+    }
+}
@@ -0,0 +1,158 @@
+//! Approximate-equality assertions shared by the unit tests.
+
+/// Asserts that two vectors are approximately (~1.0e-6) equal to each other.
+///
+/// On panic, this macro will print the values of the expressions with their
+/// debug representations. You can optionally add an optional diff value. If you
+/// don't supply a diff value as an argument, `1.0e-6` is the default used.
+///
+/// Source: <https://github.com/ashleygwilliams/assert_approx_eq>
+macro_rules! assert_vector_approx_eq {
+    ($a:expr, $b:expr) => {{
+        let eps = 1.0e-6;
+        let err = $a - $b;
+        assert!(
+            err.norm() < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
+            &$a,
+            &$b,
+            eps,
+            err.norm()
+        );
+    }};
+    ($a:expr, $b:expr, $eps:expr) => {{
+        let eps = $eps;
+        let err = $a - $b;
+        assert!(
+            err.norm() < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
+            &$a,
+            &$b,
+            eps,
+            err.norm()
+        );
+    }};
+}
+
+macro_rules! assert_float_approx_eq {
+    ($a:expr, $b:expr) => {{
+        let eps = 1.0e-6;
+        let err = $a - $b;
+        assert!(
+            err.abs() < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
+            &$a,
+            &$b,
+            eps,
+            err.abs()
+        );
+    }};
+    ($a:expr, $b:expr, $eps:expr) => {{
+        let eps = $eps;
+        let err = $a - $b;
+        assert!(
+            err.abs() < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
+            &$a,
+            &$b,
+            eps,
+            err.abs()
+        );
+    }};
+}
+
+macro_rules! assert_quat_approx_eq {
+    ($a:expr, $b:expr) => {{
+        let eps = 1.0e-6;
+
+        let (angle_a, axis_a) = $a.angle_axis();
+        let (angle_b, axis_b) = $b.angle_axis();
+
+        assert!(
+            (angle_a - angle_b).abs() < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, \
+             expect angle: `{:?}`, real angle: `{:?}`) \
+             expect axis: `{:?}`, real axis: `{:?}`",
+            &$a,
+            &$b,
+            angle_a,
+            angle_b,
+            axis_a,
+            axis_b
+        );
+
+        assert!(
+            (axis_a - axis_b).norm() < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, \
+             expect angle: `{:?}`, real angle: `{:?}`) \
+             expect axis: `{:?}`, real axis: `{:?}`",
+            &$a,
+            &$b,
+            angle_a,
+            angle_b,
+            axis_a,
+            axis_b
+        );
+    }};
+    ($a:expr, $b:expr, $eps:expr) => {{
+        let eps = $eps;
+
+        let (angle_a, axis_a) = $a.angle_axis();
+        let (angle_b, axis_b) = $b.angle_axis();
+
+        assert!(
+            (angle_a - angle_b).abs() < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, \
+             expect angle: `{:?}`, real angle: `{:?}`) \
+             expect axis: `{:?}`, real axis: `{:?}`",
+            &$a,
+            &$b,
+            angle_a,
+            angle_b,
+            axis_a,
+            axis_b
+        );
+
+        assert!(
+            (axis_a - axis_b).norm() < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, \
+             expect angle: `{:?}`, real angle: `{:?}`) \
+             expect axis: `{:?}`, real axis: `{:?}`",
+            &$a,
+            &$b,
+            angle_a,
+            angle_b,
+            axis_a,
+            axis_b
+        );
+    }};
+}
+
+/// Asserts that two matrices are approximately (~1.0e-6) equal to each
+/// other, using the Frobenius norm of their difference.
+macro_rules! assert_matrix_approx_eq {
+    ($a:expr, $b:expr) => {{
+        assert_matrix_approx_eq!($a, $b, 1.0e-6)
+    }};
+    ($a:expr, $b:expr, $eps:expr) => {{
+        let eps = $eps;
+        let err: f64 = ($a - $b).rows().iter().map(|r| r.norm_squared()).sum::<f64>().sqrt();
+        assert!(
+            err < eps,
+            "assertion failed: `(left !== right)` \
+             (left: `{:?}`, right: `{:?}`, expect diff: `{:?}`, real diff: `{:?}`)",
+            &$a,
+            &$b,
+            eps,
+            err
+        );
+    }};
+}
@@ -0,0 +1,210 @@
+use crate::quaternion::Quaternion;
+use crate::sequence::angular_velocities;
+use crate::error::OrientationError;
+
+/// The fewest overlapping samples over which a correlation is trusted.
+const MIN_OVERLAP: usize = 10;
+
+/// An estimated clock offset between two orientation streams.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimeOffset {
+    /// The offset, in seconds, to add to the timestamps of the second
+    /// stream to bring it into line with the first.
+    pub offset: f64,
+    /// The normalized correlation of the angular speeds at that offset,
+    /// between -1 and 1. Values near 1 indicate a confident match.
+    pub correlation: f64
+}
+
+/// Estimate the clock offset between two timestamped orientation
+/// streams recording the same motion, such as camera and IMU logs.
+///
+/// The angular speed of each stream is resampled at `rate` hertz and
+/// the normalized cross-correlation is maximized over offsets up to
+/// `max_offset` seconds either way, then refined between samples.
+/// Angular speed does not depend on the frames the streams are
+/// expressed in, so the streams need not share a body or world frame.
+/// The motion must be rich enough to have a distinct speed profile.
+///
+/// # Errors
+/// Returns an error if either stream has fewer than two samples or
+/// timestamps that are not finite and strictly increasing, if the rate
+/// or maximum offset is not positive and finite, or if no candidate
+/// offset leaves enough overlap between the streams.
+///
+/// Offsets larger than the spans of the streams leave no overlap, so
+/// `max_offset` is capped there.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let motion = |t: f64| Quaternion::from_angle_axis(t.sin() + (2.3 * t).cos(), &Vector3d::unit_z());
+/// let a: Vec<(f64, Quaternion)> = (0..500).map(|i| (f64::from(i) * 0.01, motion(f64::from(i) * 0.01))).collect();
+/// // The second clock reads 0.25 seconds behind.
+/// let b: Vec<(f64, Quaternion)> = a.iter().map(|(t, q)| (t - 0.25, *q)).collect();
+/// let estimate = estimate_time_offset(&a, &b, 100.0, 1.0).unwrap();
+/// assert!((estimate.offset - 0.25).abs() < 1.0e-3);
+/// ```
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_possible_wrap,
+        clippy::cast_sign_loss)]
+pub fn estimate_time_offset(a: &[(f64, Quaternion)], b: &[(f64, Quaternion)], rate: f64,
+                            max_offset: f64) -> Result<TimeOffset, OrientationError> {
+    if !(rate > 0.0 && rate.is_finite() && max_offset > 0.0 && max_offset.is_finite()) {
+        return Err(OrientationError::InvalidParameter("rate and maximum offset must be positive and finite"))
+    }
+    if a.iter().chain(b).any(|(time, _)| !time.is_finite()) {
+        return Err(OrientationError::NotFinite)
+    }
+    let speed_a = angular_speeds(a)?;
+    let speed_b = angular_speeds(b)?;
+
+    let dt = 1.0 / rate;
+    let (start, end) = (speed_a[0].0, speed_a[speed_a.len() - 1].0);
+    let (start_b, end_b) = (speed_b[0].0, speed_b[speed_b.len() - 1].0);
+    let reach = (end - start_b).abs().max((start - end_b).abs());
+    let max_lag = (max_offset.min(reach) * rate).ceil() as i64;
+    let grid: Vec<f64> = (0..=((end - start) * rate).floor() as usize)
+        .map(|k| start + k as f64 * dt)
+        .collect();
+
+    let correlations: Vec<Option<f64>> = (-max_lag..=max_lag)
+        .map(|lag| {
+            let offset = lag as f64 * dt;
+            let pairs: Vec<(f64, f64)> = grid.iter()
+                .filter_map(|&t| Some((interpolate(&speed_a, t)?, interpolate(&speed_b, t - offset)?)))
+                .collect();
+            correlation(&pairs)
+        })
+        .collect();
+
+    let (best, peak) = correlations.iter()
+        .enumerate()
+        .filter_map(|(i, c)| c.map(|c| (i, c)))
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+        .ok_or(OrientationError::InsufficientData("the streams do not overlap enough to align"))?;
+
+    // Refine with a parabola through the peak and its neighbors.
+    let neighbor = |i: Option<usize>| i.and_then(|i| correlations.get(i).copied().flatten());
+    let refinement = match (neighbor(best.checked_sub(1)), neighbor(Some(best + 1))) {
+        (Some(left), Some(right)) => {
+            let curvature = left - 2.0 * peak + right;
+            if curvature < 0.0 { 0.5 * (left - right) / curvature } else { 0.0 }
+        },
+        _ => 0.0
+    };
+
+    Ok(TimeOffset {
+        offset: (best as f64 - max_lag as f64 + refinement) * dt,
+        correlation: peak
+    })
+}
+
+/// The angular speed at each sample of a timestamped sequence.
+fn angular_speeds(samples: &[(f64, Quaternion)]) -> Result<Vec<(f64, f64)>, OrientationError> {
+    let rates = angular_velocities(samples)?;
+    Ok(samples.iter().zip(rates.iter()).map(|((t, _), w)| (*t, w.norm())).collect())
+}
+
+/// Linearly interpolate a sampled signal, or `None` outside its span.
+fn interpolate(signal: &[(f64, f64)], time: f64) -> Option<f64> {
+    let (first, last) = (signal.first()?, signal.last()?);
+    if time < first.0 || time > last.0 {
+        return None
+    }
+    let i = signal.partition_point(|(t, _)| *t <= time);
+    if i == signal.len() {
+        return Some(last.1)
+    }
+    let ((t0, v0), (t1, v1)) = (signal[i - 1], signal[i]);
+    Some(v0 + (v1 - v0) * (time - t0) / (t1 - t0))
+}
+
+/// The Pearson correlation of paired values, or `None` if there are too
+/// few or either side is constant.
+#[allow(clippy::cast_precision_loss)]
+fn correlation(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < MIN_OVERLAP {
+        return None
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx <= 0.0 || syy <= 0.0 {
+        return None
+    }
+    Some(sxy / (sxx * syy).sqrt())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector3d::Vector3d;
+    use crate::rotation::Rotation;
+
+    fn motion(t: f64) -> Quaternion {
+        Quaternion::from_angle_axis(0.7 * t.sin(), &Vector3d::unit_x())
+            .before(&Quaternion::from_angle_axis((1.7 * t).cos(), &Vector3d::unit_y()))
+    }
+
+    #[test]
+    fn recovers_offset_across_frames_and_rates() {
+        // The second stream is sampled at a different, irregular rate,
+        // in a different body and world frame, with its clock 0.137
+        // seconds ahead.
+        let a: Vec<(f64, Quaternion)> = (0..600).map(|i| {
+            let t = f64::from(i) * 0.01;
+            (t, motion(t))
+        }).collect();
+        let body = Quaternion::from_angle_axis(0.5, &Vector3d::new([1.0, 1.0, 0.0]));
+        let world = Quaternion::from_angle_axis(2.0, &Vector3d::unit_z());
+        let b: Vec<(f64, Quaternion)> = (0..250).map(|i| {
+            let t = 0.5 + f64::from(i) * 0.02 + 0.003 * f64::from(i % 3);
+            (t + 0.137, body.before(&motion(t)).before(&world))
+        }).collect();
+
+        let estimate = estimate_time_offset(&a, &b, 200.0, 0.5).unwrap();
+        assert!((estimate.offset + 0.137).abs() < 5.0e-3, "offset {}", estimate.offset);
+        assert!(estimate.correlation > 0.95);
+
+        // A very large search range is capped at the spans of the data.
+        assert!(estimate_time_offset(&a, &b, 200.0, 1.0e12).is_ok());
+        assert!(estimate_time_offset(&a, &b, 200.0, f64::INFINITY).is_err());
+        assert!(estimate_time_offset(&a, &b, f64::INFINITY, 0.5).is_err());
+    }
+
+    #[test]
+    fn interpolation() {
+        let signal = [(0.0, 1.0), (1.0, 3.0), (3.0, 4.0)];
+        assert_eq!(Some(2.0), interpolate(&signal, 0.5));
+        assert_eq!(Some(3.5), interpolate(&signal, 2.0));
+        assert_eq!(Some(4.0), interpolate(&signal, 3.0));
+        assert_eq!(None, interpolate(&signal, 3.5));
+    }
+
+    #[test]
+    fn no_overlap() {
+        let a: Vec<(f64, Quaternion)> = (0..20).map(|i| (f64::from(i), motion(f64::from(i)))).collect();
+        let b: Vec<(f64, Quaternion)> = (0..20).map(|i| (f64::from(i) + 100.0, motion(f64::from(i)))).collect();
+        assert!(estimate_time_offset(&a, &b, 1.0, 2.0).is_err());
+    }
+
+    #[test]
+    fn non_finite_timestamps() {
+        let a: Vec<(f64, Quaternion)> = (0..20).map(|i| (f64::from(i), motion(f64::from(i)))).collect();
+        for &time in &[f64::INFINITY, f64::NAN] {
+            let mut b = a.clone();
+            b[19].0 = time;
+            assert_eq!(Err(OrientationError::NotFinite), estimate_time_offset(&a, &b, 1.0, 2.0));
+            assert_eq!(Err(OrientationError::NotFinite), estimate_time_offset(&b, &a, 1.0, 2.0));
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Optional `tracing` instrumentation.
+//!
+//! The macros below wrap `tracing` spans and events so the rest of the
+//! crate can instrument itself without a `cfg` at every call site.
+//! Without the `tracing` feature they expand to nothing and their
+//! arguments are never evaluated.
+
+#[cfg(feature = "tracing")]
+use crate::rotation::Rotation;
+
+/// Enter a span at the given level for the rest of the enclosing block.
+macro_rules! enter_span {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::span!(tracing::Level::$level, $($arg)+).entered();
+    };
+}
+
+/// Emit an event at the given level.
+macro_rules! emit_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!(tracing::Level::$level, $($arg)+);
+    };
+}
+
+/// Emit a debug-level event recording a rotation as structured fields:
+/// `label`, the rotation `angle` in radians, and the components
+/// `axis_x`, `axis_y` and `axis_z` of its unit axis. Subscribers can
+/// then filter and plot rotations without parsing `Debug` output.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let q = Quaternion::from_angle_axis(0.3, &Vector3d::unit_z());
+/// trace_rotation("mounting", &q);
+/// ```
+#[cfg(feature = "tracing")]
+pub fn trace_rotation<R: Rotation>(label: &str, rotation: &R) {
+    let (angle, axis) = rotation.angle_axis();
+    tracing::debug!(label, angle, axis_x = axis[0], axis_y = axis[1], axis_z = axis[2], "rotation");
+}
+
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::{Event, Metadata, Subscriber};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use crate::vector3d::Vector3d;
+    use crate::quaternion::Quaternion;
+
+    /// Collects the floating point fields of every event.
+    #[derive(Default)]
+    struct Recorder {
+        fields: Arc<Mutex<Vec<(String, f64)>>>
+    }
+
+    impl Visit for Recorder {
+        fn record_f64(&mut self, field: &Field, value: f64) {
+            self.fields.lock().unwrap().push((field.name().to_string(), value));
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut Recorder { fields: Arc::clone(&self.fields) });
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn rotation_fields_are_recorded() {
+        let recorder = Recorder::default();
+        let fields = Arc::clone(&recorder.fields);
+        let q = Quaternion::from_angle_axis(0.3, &Vector3d::new([0.0, 0.0, 2.0]));
+        tracing::subscriber::with_default(recorder, || trace_rotation("mounting", &q));
+
+        let fields = fields.lock().unwrap();
+        let value = |name: &str| fields.iter().find(|(n, _)| n == name).map(|(_, v)| *v).unwrap();
+        assert_float_approx_eq!(0.3, value("angle"), 1.0e-12);
+        assert_float_approx_eq!(0.0, value("axis_x"), 1.0e-12);
+        assert_float_approx_eq!(1.0, value("axis_z"), 1.0e-12);
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use crate::rotation::Rotation;
+use crate::quaternion::Quaternion;
+use crate::buffer::{OrientationBuffer, Extrapolation};
+use crate::error::OrientationError;
+
+/// The rotation from a child frame to its parent.
+#[derive(Clone, Debug)]
+enum Edge {
+    Static(Quaternion),
+    Dynamic(OrientationBuffer)
+}
+
+impl Edge {
+    fn at(&self, time: f64) -> Result<Quaternion, OrientationError> {
+        match self {
+            Edge::Static(q) => Ok(*q),
+            Edge::Dynamic(buffer) => buffer.query(time)
+        }
+    }
+}
+
+/// A tree of named frames linked by rotations, each either fixed or
+/// sampled over time, answering lookups between any two connected
+/// frames at any time by chaining and interpolating the rotations along
+/// the way.
+///
+/// Every frame has at most one parent. Each link stores the rotation
+/// carrying child-frame vectors into the parent frame.
+#[derive(Clone, Debug)]
+pub struct TransformGraph {
+    parents: HashMap<String, (String, Edge)>,
+    capacity: usize,
+    extrapolation: Extrapolation
+}
+
+impl TransformGraph {
+    /// Create an empty graph. Time-stamped links buffer at most
+    /// `capacity` samples each and extrapolate as specified.
+    ///
+    /// # Panics
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let mut graph = TransformGraph::new(100, Extrapolation::Refuse);
+    /// let mount = Quaternion::from_angle_axis(std::f64::consts::FRAC_PI_2, &Vector3d::unit_x());
+    /// graph.set_static("body", "camera", mount).unwrap();
+    /// graph.insert("world", "body", 0.0, Quaternion::identity()).unwrap();
+    /// graph.insert("world", "body", 1.0, Quaternion::from_angle_axis(1.0, &Vector3d::unit_z())).unwrap();
+    ///
+    /// let q = graph.lookup("camera", "world", 0.5).unwrap();
+    /// let expected = mount.before(&Quaternion::from_angle_axis(0.5, &Vector3d::unit_z()));
+    /// assert!(q.angle_to(&expected) < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn new(capacity: usize, extrapolation: Extrapolation) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        Self {
+            parents: HashMap::new(),
+            capacity,
+            extrapolation
+        }
+    }
+
+    /// Link `child` to `parent` by a fixed rotation carrying child-frame
+    /// vectors into the parent frame, replacing any existing link from
+    /// `child` to `parent`.
+    ///
+    /// # Errors
+    /// Returns an error if `child` already has a different parent, or if
+    /// the link would close a loop.
+    pub fn set_static(&mut self, parent: &str, child: &str, rotation: Quaternion) -> Result<(), OrientationError> {
+        self.check_link(parent, child)?;
+        self.parents.insert(child.to_string(), (parent.to_string(), Edge::Static(rotation)));
+        Ok(())
+    }
+
+    /// Record the rotation carrying `child`-frame vectors into the
+    /// `parent` frame at `time`, creating a time-stamped link if there
+    /// is none.
+    ///
+    /// # Errors
+    /// Returns an error if the time is not finite, if `child` already
+    /// has a different parent or a fixed link, or if the link would
+    /// close a loop.
+    pub fn insert(&mut self, parent: &str, child: &str, time: f64, rotation: Quaternion) -> Result<(), OrientationError> {
+        if !time.is_finite() {
+            return Err(OrientationError::NotFinite)
+        }
+        self.check_link(parent, child)?;
+        let (capacity, extrapolation) = (self.capacity, self.extrapolation);
+        let (_, edge) = self.parents
+            .entry(child.to_string())
+            .or_insert_with(|| (parent.to_string(), Edge::Dynamic(OrientationBuffer::new(capacity, extrapolation))));
+        match edge {
+            Edge::Dynamic(buffer) => {
+                buffer.insert(time, rotation);
+                Ok(())
+            },
+            Edge::Static(_) => Err(OrientationError::InvalidLink("the link is static"))
+        }
+    }
+
+    /// The parent of a frame, if it has one.
+    #[must_use]
+    pub fn parent(&self, frame: &str) -> Option<&str> {
+        self.parents.get(frame).map(|(parent, _)| parent.as_str())
+    }
+
+    /// The rotation carrying `from`-frame vectors into the `to` frame at
+    /// `time`, chaining links through their nearest common ancestor.
+    /// Time-stamped links are interpolated; fixed links hold at all
+    /// times. Only the links along that path are consulted.
+    ///
+    /// # Errors
+    /// Returns an error if the frames are not connected, or if a
+    /// time-stamped link along the way cannot answer at `time`.
+    pub fn lookup(&self, from: &str, to: &str, time: f64) -> Result<Quaternion, OrientationError> {
+        let to_ancestors = self.ancestors(to);
+        let common = self.ancestors(from)
+            .into_iter()
+            .find(|frame| to_ancestors.contains(frame))
+            .ok_or(OrientationError::NotConnected)?;
+        let from_to_common = self.to_ancestor(from, common, time)?;
+        let to_to_common = self.to_ancestor(to, common, time)?;
+        Ok(from_to_common.before(&to_to_common.inverse_unchecked()))
+    }
+
+    /// A frame followed by its parent, grandparent and so on to the root.
+    fn ancestors<'a>(&'a self, frame: &'a str) -> Vec<&'a str> {
+        let mut ancestors = vec![frame];
+        while let Some(parent) = self.parent(ancestors[ancestors.len() - 1]) {
+            ancestors.push(parent);
+        }
+        ancestors
+    }
+
+    /// The rotation carrying `frame` vectors into the frame of one of
+    /// its ancestors at `time`.
+    fn to_ancestor(&self, frame: &str, ancestor: &str, time: f64) -> Result<Quaternion, OrientationError> {
+        let mut current = frame;
+        let mut rotation = Quaternion::identity();
+        while current != ancestor {
+            let (parent, edge) = &self.parents[current];
+            rotation = rotation.before(&edge.at(time)?);
+            current = parent;
+        }
+        Ok(rotation)
+    }
+
+    fn check_link(&self, parent: &str, child: &str) -> Result<(), OrientationError> {
+        if let Some(existing) = self.parent(child) {
+            if existing != parent {
+                return Err(OrientationError::InvalidLink("the child frame already has a different parent"))
+            }
+        }
+        let mut current = Some(parent);
+        while let Some(frame) = current {
+            if frame == child {
+                return Err(OrientationError::InvalidLink("the link would close a loop"))
+            }
+            current = self.parent(frame);
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector3d::Vector3d;
+
+    fn graph() -> TransformGraph {
+        // world -> body -> {camera, imu}, plus an unconnected frame.
+        let mut graph = TransformGraph::new(10, Extrapolation::Refuse);
+        graph.set_static("body", "camera", Quaternion::from_angle_axis(0.3, &Vector3d::unit_x())).unwrap();
+        graph.set_static("body", "imu", Quaternion::from_angle_axis(-0.8, &Vector3d::unit_y())).unwrap();
+        graph.insert("world", "body", 0.0, Quaternion::identity()).unwrap();
+        graph.insert("world", "body", 2.0, Quaternion::from_angle_axis(1.0, &Vector3d::unit_z())).unwrap();
+        graph.set_static("map", "elsewhere", Quaternion::identity()).unwrap();
+        graph
+    }
+
+    #[test]
+    fn lookups_chain_through_common_ancestor() {
+        let graph = graph();
+        let camera = Quaternion::from_angle_axis(0.3, &Vector3d::unit_x());
+        let imu = Quaternion::from_angle_axis(-0.8, &Vector3d::unit_y());
+        let body = Quaternion::from_angle_axis(0.25, &Vector3d::unit_z());
+
+        let camera_to_imu = graph.lookup("camera", "imu", 0.5).unwrap();
+        assert!(camera_to_imu.angle_to(&camera.before(&imu.inverse_unchecked())) < 1.0e-12);
+
+        let camera_to_world = graph.lookup("camera", "world", 0.5).unwrap();
+        assert!(camera_to_world.angle_to(&camera.before(&body)) < 1.0e-12);
+
+        let world_to_camera = graph.lookup("world", "camera", 0.5).unwrap();
+        assert!(world_to_camera.angle_to(&camera_to_world.inverse_unchecked()) < 1.0e-12);
+        assert!(graph.lookup("imu", "imu", 0.5).unwrap().angle_to(&Quaternion::identity()) < 1.0e-12);
+    }
+
+    #[test]
+    fn lookup_failures() {
+        let graph = graph();
+        assert!(graph.lookup("camera", "elsewhere", 0.5).is_err());
+        assert!(graph.lookup("camera", "world", 3.0).is_err());
+        // Fixed links do not depend on time.
+        assert!(graph.lookup("camera", "imu", 3.0).is_ok());
+    }
+
+    #[test]
+    fn tree_is_enforced() {
+        let mut graph = graph();
+        assert!(graph.set_static("map", "camera", Quaternion::identity()).is_err());
+        assert!(graph.set_static("camera", "world", Quaternion::identity()).is_err());
+        assert!(graph.insert("body", "camera", 0.0, Quaternion::identity()).is_err());
+        assert!(graph.set_static("body", "camera", Quaternion::identity()).is_ok());
+        assert_eq!(Some("world"), graph.parent("body"));
+        assert_eq!(None, graph.parent("world"));
+    }
+
+    #[test]
+    fn non_finite_time() {
+        let mut graph = graph();
+        assert_eq!(Err(OrientationError::NotFinite), graph.insert("world", "body", f64::NAN, Quaternion::identity()));
+        assert_eq!(Err(OrientationError::NotFinite), graph.insert("world", "rover", f64::INFINITY, Quaternion::identity()));
+        assert_eq!(None, graph.parent("rover"));
+    }
+}
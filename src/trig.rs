@@ -0,0 +1,94 @@
+use std::f64::consts::FRAC_2_PI;
+
+// Sine and cosine for the rotation constructors and slerp. With the
+// `fast-math` feature these are the polynomial approximations below,
+// which avoid the libm calls that dominate an orientation update on
+// small processors; otherwise they are the standard library functions.
+
+/// `pi / 2` split in two: the first 33 bits, so that multiples of it by
+/// integers below 2^20 are exact, and the remainder.
+const FRAC_PI_2_HI: f64 = 1.570_796_326_734_125_6;
+const FRAC_PI_2_LO: f64 = 6.077_100_506_506_192e-11;
+
+/// Minimax coefficients for `sin(r) = r + r^3 (S1 + r^2 S2 + ...)` and
+/// `cos(r) = 1 - r^2 / 2 + r^4 (C1 + r^2 C2 + ...)` on `[-pi/4, pi/4]`,
+/// from fdlibm's `__kernel_sin` and `__kernel_cos`.
+const SIN: [f64; 6] = [
+    -1.666_666_666_666_663_2e-1,
+    8.333_333_333_322_49e-3,
+    -1.984_126_982_985_795e-4,
+    2.755_731_370_707_006_8e-6,
+    -2.505_076_025_340_686_3e-8,
+    1.589_690_995_211_55e-10
+];
+const COS: [f64; 6] = [
+    4.166_666_666_666_66e-2,
+    -1.388_888_888_887_411e-3,
+    2.480_158_728_947_673e-5,
+    -2.755_731_435_139_066_3e-7,
+    2.087_572_321_298_175e-9,
+    -1.135_964_755_778_819_5e-11
+];
+
+/// The sine and cosine of `x`.
+#[inline]
+pub(crate) fn sin_cos(x: f64) -> (f64, f64) {
+    if cfg!(feature = "fast-math") {
+        approximate_sin_cos(x)
+    } else {
+        x.sin_cos()
+    }
+}
+
+/// The sine of `x`.
+#[inline]
+pub(crate) fn sin(x: f64) -> f64 {
+    sin_cos(x).0
+}
+
+/// Sine and cosine to within 1e-15 absolute error for `|x|` up to 1e6,
+/// beyond which the error grows slowly with `|x|`. NaN and infinite
+/// inputs give NaN.
+///
+/// The argument is reduced to `r` in `[-pi/4, pi/4]` and a quadrant,
+/// and both functions are evaluated there as polynomials in `r^2`.
+#[allow(clippy::cast_possible_truncation)]
+fn approximate_sin_cos(x: f64) -> (f64, f64) {
+    let quadrant = (x * FRAC_2_PI).round();
+    let r = (x - quadrant * FRAC_PI_2_HI) - quadrant * FRAC_PI_2_LO;
+    let r2 = r * r;
+    let sin_tail = SIN[1] + r2 * (SIN[2] + r2 * (SIN[3] + r2 * (SIN[4] + r2 * SIN[5])));
+    let sin = r + r * r2 * (SIN[0] + r2 * sin_tail);
+    let cos_tail = COS[0] + r2 * (COS[1] + r2 * (COS[2] + r2 * (COS[3] + r2 * (COS[4] + r2 * COS[5]))));
+    // Add the small terms to 1 - r^2 / 2 together with its rounding
+    // error, as fdlibm does, to keep the cosine accurate near pi/4.
+    let half = 0.5 * r2;
+    let leading = 1.0 - half;
+    let cos = leading + (((1.0 - leading) - half) + r2 * r2 * cos_tail);
+    match (quadrant as i64) & 3 {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        2 => (-sin, -cos),
+        _ => (-cos, sin)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approximation_within_bound() {
+        let mut worst: f64 = 0.0;
+        for i in -200_000..=200_000 {
+            for &x in &[f64::from(i) * 1.0e-4, f64::from(i) * 5.0] {
+                let (s, c) = approximate_sin_cos(x);
+                worst = worst.max((s - x.sin()).abs()).max((c - x.cos()).abs());
+            }
+        }
+        assert!(worst < 1.0e-15, "worst error {}", worst);
+        assert!(approximate_sin_cos(f64::NAN).0.is_nan());
+        assert!(approximate_sin_cos(f64::INFINITY).1.is_nan());
+    }
+}
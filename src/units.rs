@@ -0,0 +1,71 @@
+use std::f64::consts::PI;
+
+/// An angle in radians.
+///
+/// Functions that take an angle accept `Into<Radians>`, so they take a
+/// `Radians`, a `Degrees`, or a bare `f64`, which is read as radians as
+/// everywhere else in the crate. Wrapping angles in these types makes
+/// the unit part of the value, so a degree reading can no longer be
+/// passed where radians are meant.
+///
+/// # Examples
+///
+/// ```
+/// use orientations::*;
+/// let a = Quaternion::from_angle_axis(Degrees(90.0), &Vector3d::unit_z());
+/// let b = Quaternion::from_angle_axis(Radians(std::f64::consts::FRAC_PI_2), &Vector3d::unit_z());
+/// assert!(a.angle_to(&b) < 1.0e-12);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Radians(pub f64);
+
+/// An angle in degrees. See `Radians`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Degrees(pub f64);
+
+impl From<f64> for Radians {
+    /// A bare number is taken to be in radians.
+    fn from(radians: f64) -> Self {
+        Radians(radians)
+    }
+}
+
+impl From<Degrees> for Radians {
+    fn from(degrees: Degrees) -> Self {
+        Radians(degrees.0 * (PI / 180.0))
+    }
+}
+
+impl From<Radians> for Degrees {
+    fn from(radians: Radians) -> Self {
+        Degrees(radians.0 * (180.0 / PI))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector3d::Vector3d;
+    use crate::quaternion::Quaternion;
+    use crate::euler::EulerSequence;
+
+    #[test]
+    fn conversions() {
+        assert_float_approx_eq!(PI, Radians::from(Degrees(180.0)).0, 1.0e-15);
+        assert_float_approx_eq!(-45.0, Degrees::from(Radians(-0.25 * PI)).0, 1.0e-12);
+        assert_float_approx_eq!(33.0, Degrees::from(Radians::from(Degrees(33.0))).0, 1.0e-12);
+        assert_eq!(Radians(0.5), 0.5.into());
+    }
+
+    #[test]
+    fn constructors_accept_degrees() {
+        let radians = Quaternion::from_euler_angles(EulerSequence::Zyx, [0.5, -0.2, 1.0]);
+        let degrees = Quaternion::from_euler_angles(EulerSequence::Zyx, [
+            Degrees(0.5_f64.to_degrees()), Degrees((-0.2_f64).to_degrees()), Degrees(1.0_f64.to_degrees())
+        ]);
+        assert!(radians.angle_to(&degrees) < 1.0e-12);
+        assert_vector_approx_eq!(Vector3d::from_azimuth_elevation(0.3, Radians(0.4)),
+                                 Vector3d::from_azimuth_elevation(Degrees(0.3_f64.to_degrees()), 0.4), 1.0e-12);
+    }
+}
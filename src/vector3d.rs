@@ -1,6 +1,16 @@
-use std::ops::{Add, Sub};
+use std::ops::{Add, Sub, Neg, Mul, Div, AddAssign, SubAssign, MulAssign, DivAssign, Index, IndexMut};
+use std::{array, slice};
+use std::iter::Sum;
 use std::fmt;
 use crate::constants::DBL_EPSILON;
+use crate::error::OrientationError;
+use crate::checked;
+use crate::units::Radians;
+use crate::matrix3::Matrix3;
+
+/// Below this separation, directions are treated as parallel or
+/// antiparallel by `slerp`.
+const SLERP_PARALLEL_THRESHOLD: f64 = 1.0e-9;
 
 /// A 3-d vector
 #[derive(Copy, Clone, PartialEq)]
@@ -10,6 +20,18 @@ pub struct Vector3d {
 }
 
 impl Vector3d {
+    /// The zero vector.
+    pub const ZERO: Self = Self::new([0.0, 0.0, 0.0]);
+
+    /// The unit vector along the x-axis.
+    pub const X: Self = Self::new([1.0, 0.0, 0.0]);
+
+    /// The unit vector along the y-axis.
+    pub const Y: Self = Self::new([0.0, 1.0, 0.0]);
+
+    /// The unit vector along the z-axis.
+    pub const Z: Self = Self::new([0.0, 0.0, 1.0]);
+
     /// Create a new Vector3d.
     ///
     /// # Examples
@@ -18,7 +40,8 @@ impl Vector3d {
     /// use orientations::Vector3d;
     /// let x = Vector3d::new([1.0, 2.0, 3.0]);
     /// ```
-    pub fn new(data: [f64; 3]) -> Self {
+    #[must_use]
+    pub const fn new(data: [f64; 3]) -> Self {
         Self{ data }
     }
 
@@ -32,6 +55,7 @@ impl Vector3d {
     /// let y = Vector3d::new([4.0, 5.0, 6.0]);
     /// assert_eq!(32.0, x.dot(&y));
     /// ```
+    #[must_use]
     pub fn dot(&self, other: &Self) -> f64 {
         let mut dot_product: f64 = 0.0;
         for i in 0..3 {
@@ -51,6 +75,7 @@ impl Vector3d {
     /// let expected = Vector3d::new([-3.0, 6.0, -3.0]);
     /// assert_eq!(expected, x.cross(&y));
     /// ```
+    #[must_use]
     pub fn cross(&self, other: &Self) -> Self {
         let x1 = self.data[1] * other.data[2] - self.data[2] * other.data[1];
         let x2 = self.data[2] * other.data[0] - self.data[0] * other.data[2];
@@ -67,8 +92,9 @@ impl Vector3d {
     /// let x = Vector3d::new([1.0, 2.0, 3.0]);
     /// assert_eq!(14.0, x.norm_squared());
     /// ```
+    #[must_use]
     pub fn norm_squared(&self) -> f64 {
-        self.dot(&self)
+        self.dot(self)
     }
 
     /// Computes the (l2) norm of a vector.
@@ -80,6 +106,7 @@ impl Vector3d {
     /// let x = Vector3d::new([1.0, 2.0, 2.0]);
     /// assert_eq!(3.0, x.norm());
     /// ```
+    #[must_use]
     pub fn norm(&self) -> f64 {
         self.norm_squared().sqrt()
     }
@@ -95,6 +122,7 @@ impl Vector3d {
     /// let expected = Vector3d::new([2.0, 4.0, 6.0]);
     /// assert_eq!(expected, x.scalar_multiple(alpha));
     /// ```
+    #[must_use]
     pub fn scalar_multiple(&self, alpha: f64) -> Self {
         Self::new(
             [
@@ -115,6 +143,7 @@ impl Vector3d {
     /// let expected = Vector3d::new([-1.0, -2.0, -3.0]);
     /// assert_eq!(expected, x.negate());
     /// ```
+    #[must_use]
     pub fn negate(&self) -> Self {
         Self::new(
             [
@@ -125,6 +154,155 @@ impl Vector3d {
         )
     }
 
+    /// The smaller of each pair of components, as for the lower corner
+    /// of a bounding box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let a = Vector3d::new([1.0, -2.0, 3.0]);
+    /// let b = Vector3d::new([0.0, 5.0, 3.5]);
+    /// assert_eq!(Vector3d::new([0.0, -2.0, 3.0]), a.component_min(&b));
+    /// ```
+    #[must_use]
+    pub fn component_min(&self, other: &Self) -> Self {
+        Self::new(
+            [
+                self.data[0].min(other.data[0]),
+                self.data[1].min(other.data[1]),
+                self.data[2].min(other.data[2])
+            ]
+        )
+    }
+
+    /// The larger of each pair of components.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let a = Vector3d::new([1.0, -2.0, 3.0]);
+    /// let b = Vector3d::new([0.0, 5.0, 3.5]);
+    /// assert_eq!(Vector3d::new([1.0, 5.0, 3.5]), a.component_max(&b));
+    /// ```
+    #[must_use]
+    pub fn component_max(&self, other: &Self) -> Self {
+        Self::new(
+            [
+                self.data[0].max(other.data[0]),
+                self.data[1].max(other.data[1]),
+                self.data[2].max(other.data[2])
+            ]
+        )
+    }
+
+    /// The absolute value of each component.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// assert_eq!(Vector3d::new([1.0, 2.0, 0.0]), Vector3d::new([-1.0, 2.0, -0.0]).abs());
+    /// ```
+    #[must_use]
+    pub fn abs(&self) -> Self {
+        Self::new(
+            [
+                self.data[0].abs(),
+                self.data[1].abs(),
+                self.data[2].abs()
+            ]
+        )
+    }
+
+    /// Restrict each component to the range given by the matching
+    /// components of `min` and `max`, as when enforcing per-axis rate
+    /// limits.
+    ///
+    /// # Panics
+    /// Panics if a component of `min` is greater than the matching
+    /// component of `max`, or either is NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let limit = Vector3d::new([1.0, 1.0, 0.5]);
+    /// let rate = Vector3d::new([2.0, -0.3, -0.8]);
+    /// assert_eq!(Vector3d::new([1.0, -0.3, -0.5]), rate.clamp(&-limit, &limit));
+    /// ```
+    #[must_use]
+    pub fn clamp(&self, min: &Self, max: &Self) -> Self {
+        Self::new(
+            [
+                self.data[0].clamp(min.data[0], max.data[0]),
+                self.data[1].clamp(min.data[1], max.data[1]),
+                self.data[2].clamp(min.data[2], max.data[2])
+            ]
+        )
+    }
+
+    /// The outer product `self * other^T`, whose row `i` is `other`
+    /// scaled by component `i` of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let a = Vector3d::new([1.0, 2.0, 3.0]);
+    /// let b = Vector3d::new([0.5, -1.0, 2.0]);
+    /// // (a b^T) c = a (b . c)
+    /// let c = Vector3d::new([2.0, 1.0, 1.0]);
+    /// assert_eq!(a.scalar_multiple(b.dot(&c)), a.outer(&b).multiply_vector(&c));
+    /// ```
+    #[must_use]
+    pub fn outer(&self, other: &Self) -> Matrix3 {
+        Matrix3::from_rows(self.data.map(|x| other.scalar_multiple(x)))
+    }
+
+    /// The scalar triple product `a . (b x c)`: the signed volume of the
+    /// parallelepiped spanned by the three vectors, positive when they
+    /// form a right-handed set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let volume = Vector3d::triple_product(&Vector3d::unit_x(), &Vector3d::unit_y(), &Vector3d::unit_z());
+    /// assert_eq!(1.0, volume);
+    /// ```
+    #[must_use]
+    pub fn triple_product(a: &Self, b: &Self, c: &Self) -> f64 {
+        a.dot(&b.cross(c))
+    }
+
+    /// Two unit vectors that, with the direction of `self`, complete a
+    /// right-handed orthonormal basis: for the result `(u, v)`, the
+    /// columns `[self / |self|, u, v]` form a rotation matrix. Which
+    /// pair is returned is arbitrary, but it depends smoothly on `self`
+    /// away from a few switch-over directions.
+    ///
+    /// # Errors
+    /// If vector has norm close to zero, the result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let normal = Vector3d::new([0.0, 0.0, 2.0]);
+    /// let (u, v) = normal.any_orthonormal_pair().unwrap();
+    /// assert!(u.dot(&normal).abs() < 1.0e-12 && v.dot(&normal).abs() < 1.0e-12);
+    /// assert!((u.cross(&v) - Vector3d::unit_z()).norm() < 1.0e-12);
+    /// ```
+    pub fn any_orthonormal_pair(&self) -> Result<(Self, Self), OrientationError> {
+        let n = self.normalized()?;
+        // Cross with whichever basis vector is least aligned with n.
+        let basis = if n.data[0].abs() < 0.9 { Self::unit_x() } else { Self::unit_y() };
+        let u = n.cross(&basis).normalized()?;
+        Ok((u, n.cross(&u)))
+    }
+
     /// Return a vector with the same direction as self but unit
     /// magnitude. The return value is wrapped in a Result in case
     /// the vector has zero mangitude, in which case the result will
@@ -138,17 +316,318 @@ impl Vector3d {
     /// ```
     /// use orientations::Vector3d;
     /// let x = Vector3d::new([2.0, 0.0, 0.0]);
-    /// assert_eq!(Vector3d::x(), x.normalized().unwrap());
+    /// assert_eq!(Vector3d::unit_x(), x.normalized().unwrap());
     /// ```
-    pub fn normalized(&self) -> Result<Self, String> {
+    pub fn normalized(&self) -> Result<Self, OrientationError> {
         let n = self.norm();
         if n < DBL_EPSILON {
-            Err(String::from("Cannot normalize vector with zero magnitude"))
+            Err(OrientationError::ZeroNorm)
         } else {
-            Ok(self.scalar_multiple(1.0 / n))
+            Ok(checked::vector("result of Vector3d::normalized", self.scalar_multiple(1.0 / n)))
         }
     }
 
+    /// Scale the vector to unit magnitude in place.
+    ///
+    /// # Errors
+    /// If vector has norm close to zero, the result will be an Error
+    /// and the vector is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let mut v = Vector3d::new([0.0, 0.0, -3.0]);
+    /// v.normalize_in_place().unwrap();
+    /// assert_eq!(-Vector3d::unit_z(), v);
+    /// ```
+    pub fn normalize_in_place(&mut self) -> Result<(), OrientationError> {
+        let n = self.norm();
+        if n < DBL_EPSILON {
+            Err(OrientationError::ZeroNorm)
+        } else {
+            *self /= n;
+            checked::vector("result of Vector3d::normalize_in_place", *self);
+            Ok(())
+        }
+    }
+
+    /// The mean of a set of vectors.
+    ///
+    /// # Errors
+    /// If `vs` is empty, the result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let vs = [Vector3d::new([1.0, 0.0, 2.0]), Vector3d::new([3.0, 2.0, 0.0])];
+    /// assert_eq!(Vector3d::new([2.0, 1.0, 1.0]), Vector3d::mean(&vs).unwrap());
+    /// ```
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean(vs: &[Self]) -> Result<Self, OrientationError> {
+        if vs.is_empty() {
+            return Err(OrientationError::InsufficientData("cannot average an empty set of vectors"))
+        }
+        Ok(vs.iter().sum::<Self>() / vs.len() as f64)
+    }
+
+    /// The centroid of a set of points with the given weights, such as
+    /// masses or measurement confidences. With equal weights this is
+    /// the `mean`.
+    ///
+    /// # Errors
+    /// If there are no points, if `points` and `weights` differ in
+    /// length, or if the weights do not have a positive, finite sum, the
+    /// result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let points = [Vector3d::zero(), Vector3d::new([4.0, 0.0, 0.0])];
+    /// let centroid = Vector3d::centroid(&points, &[3.0, 1.0]).unwrap();
+    /// assert_eq!(Vector3d::new([1.0, 0.0, 0.0]), centroid);
+    /// ```
+    pub fn centroid(points: &[Self], weights: &[f64]) -> Result<Self, OrientationError> {
+        if points.is_empty() {
+            return Err(OrientationError::InsufficientData("cannot take the centroid of no points"))
+        }
+        if points.len() != weights.len() {
+            return Err(OrientationError::InvalidParameter("points and weights differ in length"))
+        }
+        let total: f64 = weights.iter().sum();
+        if !(total.is_finite() && total > 0.0) {
+            return Err(OrientationError::InvalidParameter("weights must have a positive, finite sum"))
+        }
+        let weighted: Self = points.iter().zip(weights).map(|(p, w)| p.scalar_multiple(*w)).sum();
+        Ok(weighted / total)
+    }
+
+    /// Whether every component is finite, neither infinite nor NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// assert!(Vector3d::unit_x().is_finite());
+    /// assert!(!Vector3d::new([0.0, f64::NAN, 0.0]).is_finite());
+    /// ```
+    #[must_use]
+    pub fn is_finite(&self) -> bool {
+        self.data.iter().all(|x| x.is_finite())
+    }
+
+    /// Whether two vectors are within `tolerance` of each other, by
+    /// Euclidean distance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let v = Vector3d::new([0.1 + 0.2, 0.0, 0.0]);
+    /// assert!(v.approx_eq(&Vector3d::new([0.3, 0.0, 0.0]), 1.0e-12));
+    /// assert!(!v.approx_eq(&Vector3d::zero(), 0.1));
+    /// ```
+    #[must_use]
+    pub fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        self.distance(other) <= tolerance
+    }
+
+    /// The Euclidean distance between two points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let a = Vector3d::new([1.0, 2.0, 3.0]);
+    /// assert_eq!(5.0, a.distance(&Vector3d::new([1.0, 5.0, 7.0])));
+    /// ```
+    #[must_use]
+    pub fn distance(&self, other: &Self) -> f64 {
+        self.distance_squared(other).sqrt()
+    }
+
+    /// The squared Euclidean distance between two points, which avoids a
+    /// square root when only comparing distances.
+    #[must_use]
+    pub fn distance_squared(&self, other: &Self) -> f64 {
+        (*self - *other).norm_squared()
+    }
+
+    /// The angle between two vectors, in `[0, pi]`. Computed from both
+    /// the sine and cosine, so it stays accurate for nearly parallel or
+    /// antiparallel vectors, where `acos` of the normalized dot product
+    /// loses precision. The angle involving a zero vector is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let a = Vector3d::new([1.0, 1.0e-9, 0.0]);
+    /// assert!((a.angle_between(&Vector3d::unit_x()) - 1.0e-9).abs() < 1.0e-20);
+    /// ```
+    #[must_use]
+    pub fn angle_between(&self, other: &Self) -> f64 {
+        self.cross(other).norm().atan2(self.dot(other))
+    }
+
+    /// The component of `self` along `other`.
+    ///
+    /// # Errors
+    /// If `other` has norm close to zero, the result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let v = Vector3d::new([1.0, 2.0, 3.0]);
+    /// assert_eq!(Vector3d::new([0.0, 0.0, 3.0]), v.project_onto(&Vector3d::new([0.0, 0.0, -2.0])).unwrap());
+    /// ```
+    pub fn project_onto(&self, other: &Self) -> Result<Self, OrientationError> {
+        let norm_squared = other.norm_squared();
+        if norm_squared.sqrt() < DBL_EPSILON {
+            Err(OrientationError::ZeroNorm)
+        } else {
+            Ok(other.scalar_multiple(self.dot(other) / norm_squared))
+        }
+    }
+
+    /// The component of `self` perpendicular to `other`, so that the
+    /// projection and the rejection sum to `self`.
+    ///
+    /// # Errors
+    /// If `other` has norm close to zero, the result will be an Error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let v = Vector3d::new([1.0, 2.0, 3.0]);
+    /// assert_eq!(Vector3d::new([1.0, 2.0, 0.0]), v.reject_from(&Vector3d::unit_z()).unwrap());
+    /// ```
+    pub fn reject_from(&self, other: &Self) -> Result<Self, OrientationError> {
+        Ok(*self - self.project_onto(other)?)
+    }
+
+    /// Linear interpolation: `self` at `t = 0`, `other` at `t = 1`, and
+    /// extrapolation along the same line outside that range. For
+    /// directions, where the result should stay a unit vector, use
+    /// `slerp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let a = Vector3d::new([0.0, 2.0, 4.0]);
+    /// let b = Vector3d::new([2.0, 2.0, 0.0]);
+    /// assert_eq!(Vector3d::new([0.5, 2.0, 3.0]), a.lerp(&b, 0.25));
+    /// ```
+    #[must_use]
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        self.scalar_multiple(1.0 - t) + other.scalar_multiple(t)
+    }
+
+    /// Spherical linear interpolation between unit directions: the point
+    /// a fraction `t` of the way along the great circle from `self` to
+    /// `other`, moving at constant angular speed. Nearly parallel
+    /// directions are interpolated linearly and renormalized. For
+    /// antiparallel directions every great circle is as short as any
+    /// other; an arbitrary one is chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let halfway = Vector3d::unit_x().slerp(&Vector3d::unit_y(), 0.5);
+    /// let expected = Vector3d::new([1.0, 1.0, 0.0]).normalized().unwrap();
+    /// assert!((halfway - expected).norm() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        let cos_theta = self.dot(other);
+        let tangent = *other - self.scalar_multiple(cos_theta);
+        let sin_theta = tangent.norm();
+
+        let tangent = if sin_theta > SLERP_PARALLEL_THRESHOLD {
+            tangent.scalar_multiple(1.0 / sin_theta)
+        } else if cos_theta > 0.0 {
+            return self.lerp(other, t).normalized().unwrap_or(*self)
+        } else {
+            // Any direction perpendicular to self will do.
+            self.any_orthonormal_pair().map_or(Self::unit_x(), |(u, _)| u)
+        };
+        let (sin, cos) = (t * sin_theta.atan2(cos_theta)).sin_cos();
+        checked::vector("result of Vector3d::slerp", self.scalar_multiple(cos) + tangent.scalar_multiple(sin))
+    }
+
+    /// Rotate the vector by `angle` about `axis`, right-handed, using
+    /// Rodrigues' formula directly. The angle is in radians, or given
+    /// as `Degrees`. Agrees with rotating by
+    /// `Quaternion::from_angle_axis(angle, axis)`, without building the
+    /// quaternion.
+    ///
+    /// # Panics
+    /// Panics if axis has norm close to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::*;
+    /// let v = Vector3d::unit_x().rotated_about(&Vector3d::unit_z(), Degrees(90.0));
+    /// assert!((v - Vector3d::unit_y()).norm() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn rotated_about<A: Into<Radians>>(&self, axis: &Self, angle: A) -> Self {
+        let axis_norm = axis.norm();
+        assert!(axis_norm >= DBL_EPSILON, "Axis has zero norm");
+        let k = axis.scalar_multiple(1.0 / axis_norm);
+        let (sin, cos) = angle.into().0.sin_cos();
+        let rotated = self.scalar_multiple(cos)
+            + k.cross(self).scalar_multiple(sin)
+            + k.scalar_multiple(k.dot(self) * (1.0 - cos));
+        checked::vector("result of Vector3d::rotated_about", rotated)
+    }
+
+    /// The unit vector at an azimuth and elevation, in radians unless
+    /// given as `Degrees`. Azimuth
+    /// is measured from the y-axis towards the x-axis, clockwise seen
+    /// from above, and elevation up from the xy-plane towards the
+    /// z-axis. In an east-north-up frame this is the usual compass
+    /// azimuth from north.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let east = Vector3d::from_azimuth_elevation(std::f64::consts::FRAC_PI_2, 0.0);
+    /// assert!((east - Vector3d::unit_x()).norm() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn from_azimuth_elevation<A: Into<Radians>, E: Into<Radians>>(azimuth: A, elevation: E) -> Self {
+        let (sin_az, cos_az) = azimuth.into().0.sin_cos();
+        let (sin_el, cos_el) = elevation.into().0.sin_cos();
+        Self::new([cos_el * sin_az, cos_el * cos_az, sin_el])
+    }
+
+    /// The azimuth and elevation of the vector's direction, in radians,
+    /// in the convention of `from_azimuth_elevation`. The azimuth is in
+    /// `(-pi, pi]` and is zero for vertical vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let (azimuth, elevation) = Vector3d::new([1.0, 1.0, 0.0]).azimuth_elevation();
+    /// assert!((azimuth - std::f64::consts::FRAC_PI_4).abs() < 1.0e-12);
+    /// assert!(elevation.abs() < 1.0e-12);
+    /// ```
+    #[must_use]
+    pub fn azimuth_elevation(&self) -> (f64, f64) {
+        let [x, y, z] = self.data;
+        (x.atan2(y), z.atan2(x.hypot(y)))
+    }
+
     /// Returns the zero vector.
     ///
     /// # Examples
@@ -158,23 +637,100 @@ impl Vector3d {
     /// let expected = Vector3d::new([0.0, 0.0, 0.0]);
     /// assert_eq!(expected, Vector3d::zero());
     /// ```
-    pub fn zero() -> Self {
-        Self::new( [0.0, 0.0, 0.0] )
+    #[must_use]
+    pub const fn zero() -> Self {
+        Self::ZERO
     }
 
     /// Create a new unit Vector3d aligned with the x-axis.
-    pub fn x() -> Self {
-        Self::new( [1.0, 0.0, 0.0] )
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// assert_eq!(Vector3d::new([1.0, 0.0, 0.0]), Vector3d::unit_x());
+    /// ```
+    #[must_use]
+    pub const fn unit_x() -> Self {
+        Self::X
     }
 
-    /// Create a new unit Vector3d aligned with the x-axis.
-    pub fn y() -> Self {
-        Self::new( [0.0, 1.0, 0.0] )
+    /// Create a new unit Vector3d aligned with the y-axis.
+    #[must_use]
+    pub const fn unit_y() -> Self {
+        Self::Y
+    }
+
+    /// Create a new unit Vector3d aligned with the z-axis.
+    #[must_use]
+    pub const fn unit_z() -> Self {
+        Self::Z
     }
 
     /// Create a new unit Vector3d aligned with the x-axis.
-    pub fn z() -> Self {
-        Self::new( [0.0, 0.0, 1.0] )
+    #[deprecated(since = "0.1.0", note = "use `unit_x` or `Vector3d::X`; `x` will become a component accessor")]
+    #[must_use]
+    pub const fn x() -> Self {
+        Self::X
+    }
+
+    /// Create a new unit Vector3d aligned with the y-axis.
+    #[deprecated(since = "0.1.0", note = "use `unit_y` or `Vector3d::Y`; `y` will become a component accessor")]
+    #[must_use]
+    pub const fn y() -> Self {
+        Self::Y
+    }
+
+    /// Create a new unit Vector3d aligned with the z-axis.
+    #[deprecated(since = "0.1.0", note = "use `unit_z` or `Vector3d::Z`; `z` will become a component accessor")]
+    #[must_use]
+    pub const fn z() -> Self {
+        Self::Z
+    }
+
+    /// The x component. Until the deprecated constructor `x` is
+    /// removed and its name freed, this is the accessor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let v = Vector3d::new([1.0, 2.0, 3.0]);
+    /// assert_eq!((1.0, 2.0, 3.0), (v.x_component(), v.y_component(), v.z_component()));
+    /// ```
+    #[must_use]
+    pub const fn x_component(&self) -> f64 {
+        self.data[0]
+    }
+
+    /// The y component.
+    #[must_use]
+    pub const fn y_component(&self) -> f64 {
+        self.data[1]
+    }
+
+    /// The z component.
+    #[must_use]
+    pub const fn z_component(&self) -> f64 {
+        self.data[2]
+    }
+
+    /// Iterate over the components.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use orientations::Vector3d;
+    /// let v = Vector3d::new([1.0, -2.0, 3.0]);
+    /// assert_eq!(3.0, v.iter().fold(0.0, |m: f64, c| m.max(c.abs())));
+    /// ```
+    pub fn iter(&self) -> slice::Iter<'_, f64> {
+        self.data.iter()
+    }
+
+    /// Iterate mutably over the components.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, f64> {
+        self.data.iter_mut()
     }
 }
 
@@ -206,6 +762,210 @@ impl Sub for Vector3d {
 
 }
 
+impl Neg for Vector3d {
+    type Output = Self;
+
+    /// Negate a vector.
+    fn neg(self) -> Self {
+        self.negate()
+    }
+}
+
+impl Neg for &Vector3d {
+    type Output = Vector3d;
+
+    fn neg(self) -> Vector3d {
+        self.negate()
+    }
+}
+
+impl Mul<f64> for Vector3d {
+    type Output = Self;
+
+    /// Scale a vector.
+    fn mul(self, alpha: f64) -> Self {
+        self.scalar_multiple(alpha)
+    }
+}
+
+impl Mul<Vector3d> for f64 {
+    type Output = Vector3d;
+
+    /// Scale a vector.
+    fn mul(self, v: Vector3d) -> Vector3d {
+        v.scalar_multiple(self)
+    }
+}
+
+impl Div<f64> for Vector3d {
+    type Output = Self;
+
+    /// Divide each component by a scalar.
+    fn div(self, alpha: f64) -> Self {
+        Self::new([self.data[0] / alpha, self.data[1] / alpha, self.data[2] / alpha])
+    }
+}
+
+impl AddAssign for Vector3d {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vector3d {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f64> for Vector3d {
+    fn mul_assign(&mut self, alpha: f64) {
+        *self = *self * alpha;
+    }
+}
+
+impl DivAssign<f64> for Vector3d {
+    fn div_assign(&mut self, alpha: f64) {
+        *self = *self / alpha;
+    }
+}
+
+/// Implement a binary operator for borrowed operands by copying them,
+/// so `&a + &b` works without writing `*a + *b`.
+macro_rules! forward_ref_binop {
+    (impl $imp:ident, $method:ident for $t:ty, $u:ty) => {
+        impl $imp<&$u> for $t {
+            type Output = <$t as $imp<$u>>::Output;
+
+            fn $method(self, other: &$u) -> Self::Output {
+                $imp::$method(self, *other)
+            }
+        }
+
+        impl $imp<$u> for &$t {
+            type Output = <$t as $imp<$u>>::Output;
+
+            fn $method(self, other: $u) -> Self::Output {
+                $imp::$method(*self, other)
+            }
+        }
+
+        impl $imp<&$u> for &$t {
+            type Output = <$t as $imp<$u>>::Output;
+
+            fn $method(self, other: &$u) -> Self::Output {
+                $imp::$method(*self, *other)
+            }
+        }
+    };
+}
+
+/// Implement a compound assignment for a borrowed right-hand side.
+macro_rules! forward_ref_op_assign {
+    (impl $imp:ident, $method:ident for $t:ty, $u:ty) => {
+        impl $imp<&$u> for $t {
+            fn $method(&mut self, other: &$u) {
+                $imp::$method(self, *other);
+            }
+        }
+    };
+}
+
+forward_ref_binop!(impl Add, add for Vector3d, Vector3d);
+forward_ref_binop!(impl Sub, sub for Vector3d, Vector3d);
+forward_ref_binop!(impl Mul, mul for Vector3d, f64);
+forward_ref_binop!(impl Mul, mul for f64, Vector3d);
+forward_ref_binop!(impl Div, div for Vector3d, f64);
+forward_ref_op_assign!(impl AddAssign, add_assign for Vector3d, Vector3d);
+forward_ref_op_assign!(impl SubAssign, sub_assign for Vector3d, Vector3d);
+forward_ref_op_assign!(impl MulAssign, mul_assign for Vector3d, f64);
+forward_ref_op_assign!(impl DivAssign, div_assign for Vector3d, f64);
+
+impl Index<usize> for Vector3d {
+    type Output = f64;
+
+    /// The component at an index: 0 for x, 1 for y, 2 for z.
+    fn index(&self, i: usize) -> &f64 {
+        &self.data[i]
+    }
+}
+
+impl IndexMut<usize> for Vector3d {
+    fn index_mut(&mut self, i: usize) -> &mut f64 {
+        &mut self.data[i]
+    }
+}
+
+impl IntoIterator for Vector3d {
+    type Item = f64;
+    type IntoIter = array::IntoIter<f64, 3>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIterator::into_iter(self.data)
+    }
+}
+
+impl<'a> IntoIterator for &'a Vector3d {
+    type Item = &'a f64;
+    type IntoIter = slice::Iter<'a, f64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Vector3d {
+    type Item = &'a mut f64;
+    type IntoIter = slice::IterMut<'a, f64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl From<[f64; 3]> for Vector3d {
+    fn from(data: [f64; 3]) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<(f64, f64, f64)> for Vector3d {
+    fn from((x, y, z): (f64, f64, f64)) -> Self {
+        Self::new([x, y, z])
+    }
+}
+
+impl From<Vector3d> for [f64; 3] {
+    fn from(v: Vector3d) -> Self {
+        v.data
+    }
+}
+
+impl From<Vector3d> for (f64, f64, f64) {
+    fn from(v: Vector3d) -> Self {
+        (v.data[0], v.data[1], v.data[2])
+    }
+}
+
+impl Sum for Vector3d {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |sum, v| sum + v)
+    }
+}
+
+impl<'a> Sum<&'a Vector3d> for Vector3d {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |sum, v| sum + v)
+    }
+}
+
+impl Default for Vector3d {
+    /// The zero vector.
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
 impl fmt::Debug for Vector3d {
     /// Pretty-print a vector.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -216,8 +976,34 @@ impl fmt::Debug for Vector3d {
 
 
 #[cfg(test)]
+#[allow(clippy::float_cmp)]
 mod tests {
     use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn constants() {
+        const AXES: [Vector3d; 3] = [Vector3d::unit_x(), Vector3d::unit_y(), Vector3d::unit_z()];
+        assert_eq!([Vector3d::X, Vector3d::Y, Vector3d::Z], AXES);
+        assert_eq!(Vector3d::zero(), Vector3d::ZERO);
+    }
+
+    #[test]
+    fn default_is_zero() {
+        assert_eq!(Vector3d::zero(), Vector3d::default());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_axis_constructors() {
+        assert_eq!([Vector3d::X, Vector3d::Y, Vector3d::Z], [Vector3d::x(), Vector3d::y(), Vector3d::z()]);
+    }
+
+    #[test]
+    fn component_accessors() {
+        let v = Vector3d::new([4.0, 5.0, 6.0]);
+        assert_eq!(v.data, [v.x_component(), v.y_component(), v.z_component()]);
+    }
 
     #[test]
     fn dot() {
@@ -237,12 +1023,18 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn x_cross_y_equals_z() {
         let x = Vector3d::x();
         let y = Vector3d::y();
         assert_eq!(Vector3d::z(), x.cross(&y));
     }
 
+    #[test]
+    fn unit_x_cross_unit_y_equals_unit_z() {
+        assert_eq!(Vector3d::unit_z(), Vector3d::unit_x().cross(&Vector3d::unit_y()));
+    }
+
     macro_rules! norm_squared_tests {
         ($($name:ident: $value:expr,)*) => {
         $(
@@ -282,6 +1074,133 @@ mod tests {
     }
 
     #[test]
+    fn sum_mean_and_centroid() {
+        let vs = [Vector3d::new([1.0, -2.0, 0.5]), Vector3d::new([3.0, 0.0, -1.5]), Vector3d::new([-1.0, 5.0, 4.0])];
+        assert_vector_approx_eq!(Vector3d::new([3.0, 3.0, 3.0]), vs.iter().sum::<Vector3d>(), 1.0e-15);
+        assert_vector_approx_eq!(Vector3d::new([3.0, 3.0, 3.0]), IntoIterator::into_iter(vs).sum::<Vector3d>(), 1.0e-15);
+        assert_eq!(Vector3d::ZERO, std::iter::empty::<Vector3d>().sum());
+        assert_vector_approx_eq!(Vector3d::new([1.0, 1.0, 1.0]), Vector3d::mean(&vs).unwrap(), 1.0e-15);
+        assert_vector_approx_eq!(Vector3d::mean(&vs).unwrap(), Vector3d::centroid(&vs, &[2.0, 2.0, 2.0]).unwrap(), 1.0e-15);
+        assert!(Vector3d::mean(&[]).is_err());
+        assert!(Vector3d::centroid(&vs, &[1.0, 1.0]).is_err());
+        assert!(Vector3d::centroid(&vs, &[1.0, -1.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn angle_projection_and_rejection() {
+        let v = Vector3d::new([2.0, -1.0, 0.5]);
+        let axis = Vector3d::new([1.0, 1.0, 1.0]);
+        let along = v.project_onto(&axis).unwrap();
+        let across = v.reject_from(&axis).unwrap();
+        assert_vector_approx_eq!(v, along + across, 1.0e-15);
+        assert_float_approx_eq!(0.0, across.dot(&axis), 1.0e-15);
+        assert_float_approx_eq!(0.0, along.cross(&axis).norm(), 1.0e-15);
+        assert!(v.project_onto(&Vector3d::zero()).is_err());
+
+        let cos_angle = v.dot(&axis) / (v.norm() * axis.norm());
+        assert_float_approx_eq!(cos_angle.acos(), v.angle_between(&axis), 1.0e-12);
+        assert_float_approx_eq!(std::f64::consts::PI, v.angle_between(&-v), 1.0e-15);
+        assert_float_approx_eq!(0.0, v.angle_between(&v.scalar_multiple(3.0)), 1.0e-15);
+    }
+
+    #[test]
+    fn lerp_and_distance() {
+        let a = Vector3d::new([1.0, -2.0, 0.5]);
+        let b = Vector3d::new([-3.0, 4.0, 2.5]);
+        assert_eq!(a, a.lerp(&b, 0.0));
+        assert_eq!(b, a.lerp(&b, 1.0));
+        assert_vector_approx_eq!(Vector3d::new([-1.0, 1.0, 1.5]), a.lerp(&b, 0.5), 1.0e-15);
+        assert_vector_approx_eq!(Vector3d::new([5.0, -8.0, -1.5]), a.lerp(&b, -1.0), 1.0e-15);
+        assert_float_approx_eq!(56.0, a.distance_squared(&b), 1.0e-12);
+        assert_float_approx_eq!(56.0_f64.sqrt(), b.distance(&a), 1.0e-12);
+        assert_float_approx_eq!(0.25 * a.distance(&b), a.distance(&a.lerp(&b, 0.25)), 1.0e-12);
+    }
+
+    #[test]
+    fn componentwise_operations() {
+        let points = [Vector3d::new([1.0, -2.0, 0.5]), Vector3d::new([-3.0, 4.0, 2.5]), Vector3d::new([0.0, 1.0, -1.0])];
+        let lower = points.iter().fold(points[0], |m, p| m.component_min(p));
+        let upper = points.iter().fold(points[0], |m, p| m.component_max(p));
+        assert_eq!(Vector3d::new([-3.0, -2.0, -1.0]), lower);
+        assert_eq!(Vector3d::new([1.0, 4.0, 2.5]), upper);
+        for p in &points {
+            assert_eq!(*p, p.clamp(&lower, &upper));
+        }
+        assert_eq!(Vector3d::new([3.0, 4.0, 2.5]), points[1].abs());
+        assert_eq!(Vector3d::new([0.0, 0.5, 0.0]), Vector3d::new([-1.0, 0.5, 1.0]).clamp(&Vector3d::zero(), &Vector3d::new([0.0, 1.0, 0.0])));
+    }
+
+    #[test]
+    #[should_panic(expected = "min > max")]
+    fn clamp_rejects_inverted_bounds() {
+        let _ = Vector3d::zero().clamp(&Vector3d::unit_x(), &Vector3d::zero());
+    }
+
+    #[test]
+    fn orthonormal_completion() {
+        let directions = [Vector3d::new([0.3, -2.0, 1.0]), Vector3d::unit_x(), -Vector3d::unit_x(),
+                          Vector3d::new([0.95, 0.1, 0.0]), Vector3d::new([0.0, 0.0, -1.0e-3])];
+        for d in &directions {
+            let (u, v) = d.any_orthonormal_pair().unwrap();
+            let n = d.normalized().unwrap();
+            let r = crate::rotation_matrix::RotationMatrix::from_columns([n, u, v]);
+            assert!(r.is_special_orthogonal(1.0e-12));
+            assert_float_approx_eq!(1.0, Vector3d::triple_product(&n, &u, &v), 1.0e-12);
+        }
+        assert!(Vector3d::zero().any_orthonormal_pair().is_err());
+
+        let a = Vector3d::new([1.0, 2.0, -1.0]);
+        let b = Vector3d::new([0.5, -1.0, 3.0]);
+        let c = Vector3d::new([-2.0, 0.0, 1.0]);
+        assert_float_approx_eq!(Vector3d::triple_product(&a, &b, &c), Vector3d::triple_product(&b, &c, &a), 1.0e-12);
+        assert_float_approx_eq!(-Vector3d::triple_product(&a, &b, &c), Vector3d::triple_product(&b, &a, &c), 1.0e-12);
+        assert_float_approx_eq!(0.0, Vector3d::triple_product(&a, &b, &(a + b)), 1.0e-12);
+    }
+
+    #[test]
+    fn outer_product() {
+        let a = Vector3d::new([1.0, -2.0, 0.5]);
+        let b = Vector3d::new([3.0, 0.0, -1.0]);
+        let m = a.outer(&b);
+        for i in 0..3 {
+            for j in 0..3 {
+                assert_eq!(a[i] * b[j], m.data[i][j]);
+            }
+        }
+        assert_matrix_approx_eq!(m.transpose(), b.outer(&a), 1.0e-15);
+        assert_float_approx_eq!(a.dot(&b), m.trace(), 1.0e-15);
+    }
+
+    #[test]
+    fn rodrigues_matches_quaternion() {
+        use crate::quaternion::Quaternion;
+        use crate::rotation::Rotation;
+        let v = Vector3d::new([0.3, -1.2, 2.0]);
+        for (axis, angle) in [(Vector3d::new([1.0, 2.0, -0.5]), 0.7), (Vector3d::unit_z(), -2.9), (Vector3d::new([0.0, -3.0, 0.0]), 3.1)] {
+            let expected = Quaternion::from_angle_axis(angle, &axis).rotate_vector(&v);
+            assert_vector_approx_eq!(expected, v.rotated_about(&axis, angle), 1.0e-12);
+        }
+        assert_vector_approx_eq!(v, v.rotated_about(&Vector3d::unit_x(), 0.0), 1.0e-15);
+    }
+
+    #[test]
+    #[should_panic(expected = "Axis has zero norm")]
+    fn rodrigues_requires_axis() {
+        let _ = Vector3d::unit_x().rotated_about(&Vector3d::zero(), 1.0);
+    }
+
+    #[test]
+    fn array_and_tuple_conversions() {
+        let v = Vector3d::new([1.0, -2.0, 3.5]);
+        assert_eq!(v, Vector3d::from([1.0, -2.0, 3.5]));
+        assert_eq!(v, (1.0, -2.0, 3.5).into());
+        assert_eq!([1.0, -2.0, 3.5], <[f64; 3]>::from(v));
+        let (x, y, z) = v.into();
+        assert_eq!((1.0, -2.0, 3.5), (x, y, z));
+    }
+
+    #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn zero_normalized() {
         let zero = Vector3d::zero();
         match zero.normalized() {
@@ -290,6 +1209,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn normalize_in_place() {
+        let mut v = Vector3d::new([1.0, -2.0, 2.0]);
+        let expected = v.normalized().unwrap();
+        v.normalize_in_place().unwrap();
+        assert_vector_approx_eq!(expected, v, 1.0e-15);
+        let mut zero = Vector3d::zero();
+        assert!(zero.normalize_in_place().is_err());
+        assert_eq!(Vector3d::zero(), zero);
+    }
+
     #[test]
     fn norm() {
         let x = Vector3d::new([1.0, 2.0, 2.0]);
@@ -305,10 +1235,89 @@ mod tests {
         assert_eq!(expected, x.scalar_multiple(alpha));
     }
 
+    #[test]
+    fn operators() {
+        let a = Vector3d::new([1.0, -2.0, 4.0]);
+        let b = Vector3d::new([0.5, 3.0, -1.0]);
+        assert_eq!(a.negate(), -a);
+        assert_eq!(a.negate(), -&a);
+        assert_eq!(a.scalar_multiple(2.0), a * 2.0);
+        assert_eq!(a.scalar_multiple(2.0), 2.0 * &a);
+        assert_eq!(Vector3d::new([0.5, -1.0, 2.0]), &a / 2.0);
+        assert_eq!(a + b, a + b);
+        assert_eq!(a - b, a - b);
+        assert_eq!(a - b, a - b);
+
+        let mut c = a;
+        c += b;
+        c -= &b;
+        assert_eq!(a, c);
+        c *= 4.0;
+        c /= 2.0;
+        assert_eq!(a * 2.0, c);
+    }
+
+    #[test]
+    fn indexing_and_iteration() {
+        let mut v = Vector3d::new([1.0, 2.0, 3.0]);
+        assert_eq!(2.0, v[1]);
+        v[2] = -3.0;
+        assert_eq!(-3.0, v.data[2]);
+        for c in &mut v {
+            *c *= 2.0;
+        }
+        assert_eq!(vec![2.0, 4.0, -6.0], v.into_iter().collect::<Vec<f64>>());
+        assert_eq!(v.norm_squared(), (&v).into_iter().map(|c| c * c).sum::<f64>());
+        v.iter_mut().for_each(|c| *c = 0.0);
+        assert_eq!(Vector3d::zero(), v);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn index_out_of_range() {
+        let v = Vector3d::zero();
+        let _ = v[3];
+    }
+
     #[test]
     fn negate() {
         let x = Vector3d::new([1.0, 2.0, 3.0]);
         let expected = Vector3d::new([-1.0, -2.0, -3.0]);
         assert_eq!(expected, x.negate());
     }
+
+    #[test]
+    fn azimuth_elevation_round_trip() {
+        let v = Vector3d::from_azimuth_elevation(-2.5, 0.4);
+        assert_float_approx_eq!(1.0, v.norm(), 1.0e-12);
+        let (azimuth, elevation) = v.scalar_multiple(3.0).azimuth_elevation();
+        assert_float_approx_eq!(-2.5, azimuth, 1.0e-12);
+        assert_float_approx_eq!(0.4, elevation, 1.0e-12);
+        assert_vector_approx_eq!(Vector3d::unit_z(), Vector3d::from_azimuth_elevation(1.0, FRAC_PI_2), 1.0e-12);
+    }
+
+    #[test]
+    fn slerp() {
+        let a = Vector3d::new([1.0, 2.0, -0.5]).normalized().unwrap();
+        let b = Vector3d::new([-0.3, 0.4, 1.0]).normalized().unwrap();
+        let angle = |u: &Vector3d, v: &Vector3d| u.cross(v).norm().atan2(u.dot(v));
+        for &t in &[0.0, 0.25, 0.6, 1.0] {
+            let v = a.slerp(&b, t);
+            assert_float_approx_eq!(1.0, v.norm(), 1.0e-12);
+            assert_float_approx_eq!(t * angle(&a, &b), angle(&a, &v), 1.0e-12);
+            assert_float_approx_eq!((1.0 - t) * angle(&a, &b), angle(&v, &b), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn slerp_degenerate() {
+        let a = Vector3d::new([0.0, 0.6, 0.8]);
+        let nearly = Vector3d::new([0.0, 0.6 + 1.0e-12, 0.8]).normalized().unwrap();
+        assert_vector_approx_eq!(a, a.slerp(&nearly, 0.5), 1.0e-11);
+
+        let halfway = a.slerp(&a.negate(), 0.5);
+        assert_float_approx_eq!(1.0, halfway.norm(), 1.0e-12);
+        assert_float_approx_eq!(0.0, halfway.dot(&a), 1.0e-12);
+        assert_vector_approx_eq!(a.negate(), a.slerp(&a.negate(), 1.0), 1.0e-12);
+    }
 }